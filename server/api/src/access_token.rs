@@ -0,0 +1,50 @@
+use actix_web::{
+    get, post, delete, HttpResponse, web
+};
+use serde::Serialize;
+use super::{auth, AppState};
+use entity::{
+    entities::personal_access_tokens,
+    mutation::*
+};
+use myerror::ServerError;
+
+#[derive(Serialize)]
+struct AccessTokenReturnForm {
+    #[serde(flatten)]
+    access_token: personal_access_tokens::Model,
+    /// Raw token, returned only once.
+    token: String,
+}
+
+#[get("")]
+async fn fetch_access_tokens(
+    state: web::Data<AppState>,
+    user_id: web::Path<i32>,
+) -> Result<HttpResponse, ServerError> {
+    let models = Mutation::get_access_tokens(&state.conn, user_id.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(models))
+}
+
+#[post("/register")]
+async fn create_access_token(
+    state: web::Data<AppState>,
+    user_id: web::Path<i32>,
+    token_data: web::Json<personal_access_tokens::Model>,
+) -> Result<HttpResponse, ServerError> {
+    let mut token_data = token_data.into_inner();
+    token_data.user_id = user_id.into_inner();
+    auth::require_verified_email(&state, token_data.user_id).await?;
+    let (access_token, token) = Mutation::create_access_token(&state.conn, token_data).await?;
+    Ok(HttpResponse::Created().json(AccessTokenReturnForm { access_token, token }))
+}
+
+#[delete("/{token_id}")]
+async fn remove_access_token(
+    state: web::Data<AppState>,
+    path_para: web::Path<(i32, i32)>,
+) -> Result<HttpResponse, ServerError> {
+    let (user_id, token_id) = path_para.into_inner();
+    Mutation::delete_access_token(&state.conn, user_id, token_id).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
@@ -0,0 +1,187 @@
+#![allow(dead_code)]
+
+use std::time::Duration;
+use actix_web::{
+    App, web, test, rt, http::{Method, header::ContentType},
+};
+use actix_web_httpauth::middleware::HttpAuthentication;
+use chrono::Utc;
+use serde::Deserialize;
+use serde_json::json;
+use super::{user, auth, todo, session, access_token};
+use super::user_test::*;
+use super::logout_test::status_of;
+
+#[derive(Deserialize, Debug)]
+struct AccessTokenReturnForm {
+    id: i32,
+    user_id: i32,
+    name: String,
+    scopes: String,
+    token: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct AccessTokenListForm {
+    id: i32,
+    name: String,
+    scopes: String,
+    token_hash: Option<String>,
+}
+
+#[actix_web::test]
+async fn test_access_token() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(db_connect().await.clone()))
+            .service(user::signup_user)
+            .service(user::login_user)
+            .service(
+                web::scope("/user/{user_id}")
+                    .wrap(HttpAuthentication::bearer(auth::jwt_validator))
+                    .service(user::modify_user)
+                    .service(user::withdrawal_user)
+                    .service(user::logout_user)
+                    .service(
+                        web::scope("/todo")
+                            .service(todo::fetch_todos)
+                            .service(todo::create_todo)
+                    )
+                    .service(
+                        web::scope("/session")
+                            .service(session::fetch_sessions)
+                    )
+                    .service(
+                        web::scope("/token")
+                            .service(access_token::fetch_access_tokens)
+                            .service(access_token::create_access_token)
+                            .service(access_token::remove_access_token)
+                    )
+            )
+    ).await;
+
+    let req = test::TestRequest::post().uri("/user/register")
+        .insert_header(ContentType::form_url_encoded())
+        .set_form(UserSignUpForm{ username: "pat", password: "World123!!", email: "pat@gmail.com" })
+        .to_request();
+    let signup_resp: UserSignUpReturnForm = test::call_and_read_body_json(&app, req).await;
+    let user_id = signup_resp.id;
+
+    let req = test::TestRequest::post().uri("/user/login")
+        .insert_header(ContentType::form_url_encoded())
+        .set_form(UserLoginForm{ username: "pat", password: "World123!!" })
+        .to_request();
+    let login_resp: UserLoginReturnForm = test::call_and_read_body_json(&app, req).await;
+
+    // CREATE TOKEN //
+    // (token data, expected status)
+    let test_datas = [
+        (json!({ "name": "", "scopes": "todo:read" }), 400),
+        (json!({ "name": "ci", "scopes": "" }), 400),
+        (json!({ "name": "ci", "scopes": "todo:read todo:delete" }), 400),
+        (json!({ "name": "ci", "scopes": "todo:read", "expires_at": "2020-01-01T00:00:00Z" }), 400),
+        (json!({ "name": "read only", "scopes": "todo:read" }), 201),
+        (json!({ "name": "script", "scopes": "todo:write user:write todo:write", "expires_at": (Utc::now() + chrono::Duration::days(30)).to_rfc3339() }), 201),
+        (json!({ "name": "short", "scopes": "todo:read", "expires_at": (Utc::now() + chrono::Duration::seconds(1)).to_rfc3339() }), 201),
+    ];
+    let mut tokens = Vec::new();
+    for (data, status) in test_datas {
+        let req = test::TestRequest::post()
+            .uri(format!("/user/{}/token/register", user_id).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+            .set_json(data)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), status);
+        if resp.status() == 201 {
+            let token_resp: AccessTokenReturnForm = test::read_body_json(resp).await;
+            assert_eq!(token_resp.user_id, user_id);
+            assert!(token_resp.token.starts_with("todo_pat_"));
+            tokens.push(token_resp);
+        }
+    }
+    let (read_token, write_token, short_token) = (&tokens[0], &tokens[1], &tokens[2]);
+    // Duplicated scope is saved once.
+    assert_eq!(write_token.scopes, "todo:write user:write");
+
+    // FETCH TOKENS //
+    let req = test::TestRequest::get()
+        .uri(format!("/user/{}/token", user_id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .to_request();
+    let token_list: Vec<AccessTokenListForm> = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(token_list.len(), 3);
+    assert!(token_list.iter().all(|t| t.token_hash.is_none()));
+    assert_eq!(token_list.iter().map(|t| t.id).collect::<Vec<i32>>(), vec![short_token.id, write_token.id, read_token.id]);
+
+    // SCOPES //
+    let todo_data = json!({ "name": "from script", "completed": false });
+    // (token, method, uri, expected status)
+    let test_datas = [
+        (&read_token.token, Method::GET, format!("/user/{}/todo", user_id), 200),
+        (&read_token.token, Method::POST, format!("/user/{}/todo/register", user_id), 403),
+        (&write_token.token, Method::POST, format!("/user/{}/todo/register", user_id), 201),
+        // Write scope includes read scope.
+        (&write_token.token, Method::GET, format!("/user/{}/todo", user_id), 200),
+        (&write_token.token, Method::GET, format!("/user/{}/session", user_id), 200),
+        // Account can be changed or deleted only by login session.
+        (&write_token.token, Method::PUT, format!("/user/{}", user_id), 403),
+        (&write_token.token, Method::DELETE, format!("/user/{}", user_id), 403),
+        // Personal access token can not manage tokens or log out.
+        (&write_token.token, Method::POST, format!("/user/{}/token/register", user_id), 403),
+        (&write_token.token, Method::GET, format!("/user/{}/token", user_id), 403),
+        (&write_token.token, Method::POST, format!("/user/{}/logout", user_id), 403),
+        // Other user's path
+        (&write_token.token, Method::GET, format!("/user/{}/todo", user_id + 1), 401),
+        (&"todo_pat_not_a_token".to_owned(), Method::GET, format!("/user/{}/todo", user_id), 401),
+    ];
+    for (token, method, uri, status) in test_datas {
+        let req = test::TestRequest::default()
+            .method(method)
+            .uri(uri.as_str())
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .set_json(todo_data.clone())
+            .to_request();
+        assert_eq!(status_of(test::try_call_service(&app, req).await), status);
+    }
+
+    // Expired token is not accepted.
+    rt::time::sleep(Duration::from_millis(1100)).await;
+    let req = test::TestRequest::get()
+        .uri(format!("/user/{}/todo", user_id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", short_token.token)))
+        .to_request();
+    assert_eq!(status_of(test::try_call_service(&app, req).await), 401);
+
+    // DELETE TOKEN //
+    // (token_id, expected status)
+    let test_datas = [
+        (read_token.id, 204),
+        (read_token.id, 404),
+        (0, 404),
+    ];
+    for (token_id, status) in test_datas {
+        let req = test::TestRequest::delete()
+            .uri(format!("/user/{}/token/{}", user_id, token_id).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), status);
+    }
+
+    let req = test::TestRequest::get()
+        .uri(format!("/user/{}/todo", user_id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", read_token.token)))
+        .to_request();
+    assert_eq!(status_of(test::try_call_service(&app, req).await), 401);
+
+    // Delete test user
+    let req = test::TestRequest::delete()
+        .uri(format!("/user/{}", user_id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .to_request();
+    test::call_service(&app, req).await;
+}
@@ -0,0 +1,103 @@
+use actix_web::{
+    get, post, HttpRequest, HttpResponse, web
+};
+use super::{auth, user, AppState};
+use entity::mutation::*;
+use myerror::ServerError;
+
+/// Admin of the token and its address, every action is recorded with it.
+fn actor(req: &HttpRequest, claim: &auth::JwtClaim) -> AuditActor {
+    AuditActor {
+        admin_id: Some(claim.user_id),
+        ip: req.peer_addr().map(|addr| addr.ip().to_string()),
+    }
+}
+
+#[get("/users")]
+async fn fetch_users(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    req_data: web::ReqData<auth::JwtClaim>,
+    query: web::Query<UserQuery>
+) -> Result<HttpResponse, ServerError> {
+    Mutation::create_audit_log(&state.conn, &actor(&req, &req_data), "user.list", None, Some(req.query_string().to_owned())).await?;
+    let user_page = Mutation::search_users(&state.conn, query.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(user_page))
+}
+
+#[get("/users/{user_id}")]
+async fn fetch_user(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    req_data: web::ReqData<auth::JwtClaim>,
+    user_id: web::Path<i32>
+) -> Result<HttpResponse, ServerError> {
+    let user = Mutation::get_user(&state.conn, user_id.into_inner()).await?;
+    Mutation::create_audit_log(&state.conn, &actor(&req, &req_data), "user.view", Some(user.id), None).await?;
+    Ok(HttpResponse::Ok().json(user))
+}
+
+#[post("/users/{user_id}/disable")]
+async fn disable_user(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    req_data: web::ReqData<auth::JwtClaim>,
+    user_id: web::Path<i32>
+) -> Result<HttpResponse, ServerError> {
+    let (user, valid_after) = Mutation::set_user_disabled(&state.conn, &actor(&req, &req_data), user_id.into_inner(), true).await?;
+    // Every session of the user is logged out.
+    if let Some(valid_after) = valid_after {
+        state.revocations.revoke_all(user.id, valid_after);
+    }
+    Ok(HttpResponse::Ok().json(user))
+}
+
+#[post("/users/{user_id}/enable")]
+async fn enable_user(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    req_data: web::ReqData<auth::JwtClaim>,
+    user_id: web::Path<i32>
+) -> Result<HttpResponse, ServerError> {
+    let (user, _) = Mutation::set_user_disabled(&state.conn, &actor(&req, &req_data), user_id.into_inner(), false).await?;
+    Ok(HttpResponse::Ok().json(user))
+}
+
+#[post("/users/{user_id}/password/reset")]
+async fn force_password_reset(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    req_data: web::ReqData<auth::JwtClaim>,
+    user_id: web::Path<i32>
+) -> Result<HttpResponse, ServerError> {
+    let (user, token, valid_after) = Mutation::require_password_reset(&state.conn, &state.config.auth, &actor(&req, &req_data), user_id.into_inner()).await?;
+    state.revocations.revoke_all(user.id, valid_after);
+    user::send_password_reset_mail(&state, &user, &token, true).await;
+    Ok(HttpResponse::Ok().json(user))
+}
+
+#[get("/users/{user_id}/todo")]
+async fn fetch_user_todos(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    req_data: web::ReqData<auth::JwtClaim>,
+    user_id: web::Path<i32>,
+    query: web::Query<TodoQuery>
+) -> Result<HttpResponse, ServerError> {
+    let user = Mutation::get_user(&state.conn, user_id.into_inner()).await?;
+    Mutation::create_audit_log(&state.conn, &actor(&req, &req_data), "todo.list", Some(user.id), Some(req.query_string().to_owned())).await?;
+    let todo_page = Mutation::get_todo(&state.conn, user.id, query.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(todo_page))
+}
+
+#[get("/audit")]
+async fn fetch_audit_logs(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    req_data: web::ReqData<auth::JwtClaim>,
+    query: web::Query<AuditQuery>
+) -> Result<HttpResponse, ServerError> {
+    Mutation::create_audit_log(&state.conn, &actor(&req, &req_data), "audit.list", None, Some(req.query_string().to_owned())).await?;
+    let audit_page = Mutation::get_audit_logs(&state.conn, query.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(audit_page))
+}
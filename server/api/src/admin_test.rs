@@ -0,0 +1,220 @@
+#![allow(dead_code)]
+
+use std::fs;
+use actix_web::{
+    App, web, test, http::header::ContentType,
+};
+use actix_web_httpauth::middleware::HttpAuthentication;
+use serde::Serialize;
+use serde_json::{json, Value};
+use entity::mutation::{AuditActor, Mutation};
+use super::{user, auth, todo, admin};
+use super::user_test::*;
+use super::logout_test::status_of;
+use super::password_reset_test::mail_tokens;
+
+const RESET_URL: &str = "https://todo.example.com/reset-password?token=";
+
+#[derive(Serialize)]
+struct ResetPasswordForm {
+    token: String,
+    password: &'static str,
+}
+
+#[actix_web::test]
+async fn test_admin() {
+    let mail_dir = std::env::temp_dir().join(format!("todo-mail-{}", rand::random::<u32>()));
+    let mut config = test_config();
+    config.mail.dir = Some(mail_dir.to_string_lossy().into_owned());
+    config.auth.password_reset_url = Some(format!("{}{{token}}", RESET_URL));
+    let state = app_state(config).await;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state.clone()))
+            .service(user::signup_user)
+            .service(user::login_user)
+            .service(user::reset_password)
+            .service(
+                web::scope("/admin")
+                    .wrap(HttpAuthentication::bearer(auth::admin_validator))
+                    .service(admin::fetch_users)
+                    .service(admin::fetch_user)
+                    .service(admin::disable_user)
+                    .service(admin::enable_user)
+                    .service(admin::force_password_reset)
+                    .service(admin::fetch_user_todos)
+                    .service(admin::fetch_audit_logs)
+            )
+            .service(
+                web::scope("/user/{user_id}")
+                    .wrap(HttpAuthentication::bearer(auth::jwt_validator))
+                    .service(user::withdrawal_user)
+                    .service(
+                        web::scope("/todo")
+                            .service(todo::fetch_todos)
+                            .service(todo::create_todo)
+                    )
+            )
+    ).await;
+
+    let mut user_ids = Vec::new();
+    for (username, email) in [("admintest", "admintest@gmail.com"), ("admintest_member", "admintest_member@gmail.com")] {
+        let req = test::TestRequest::post().uri("/user/register")
+            .insert_header(ContentType::form_url_encoded())
+            .set_form(UserSignUpForm{ username, password: "World123!!", email })
+            .to_request();
+        let signup_resp: UserSignUpReturnForm = test::call_and_read_body_json(&app, req).await;
+        user_ids.push(signup_resp.id);
+    }
+    let (admin_id, member_id) = (user_ids[0], user_ids[1]);
+
+    // Admin is made by server command.
+    let command = AuditActor { admin_id: None, ip: None };
+    assert!(Mutation::set_user_role(&state.conn, &command, "admintest", "root").await.is_err());
+    let admin = Mutation::set_user_role(&state.conn, &command, "admintest", "admin").await.unwrap();
+    assert_eq!(admin.role, "admin");
+
+    let login = |username: &'static str, password: &'static str| {
+        test::TestRequest::post().uri("/user/login")
+            .insert_header(ContentType::form_url_encoded())
+            .set_form(UserLoginForm{ username, password })
+            .to_request()
+    };
+    let login_resp: Value = test::call_and_read_body_json(&app, login("admintest", "World123!!")).await;
+    assert_eq!(login_resp["user"]["role"], "admin");
+    let admin_token = login_resp["access_token"].as_str().unwrap().to_owned();
+    let login_resp: Value = test::call_and_read_body_json(&app, login("admintest_member", "World123!!")).await;
+    assert_eq!(login_resp["user"]["role"], "user");
+    let member_token = login_resp["access_token"].as_str().unwrap().to_owned();
+
+    let req = test::TestRequest::post()
+        .uri(format!("/user/{}/todo/register", member_id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", member_token)))
+        .set_json(json!({ "name": "Private", "completed": false }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 201);
+
+    // ROLE //
+    // (token, expected status)
+    let test_datas = [
+        (None, 401),
+        (Some(member_token.clone()), 403),
+        (Some(admin_token.clone()), 200),
+    ];
+    for (token, status) in test_datas {
+        let mut req = test::TestRequest::get().uri("/admin/users");
+        if let Some(token) = token {
+            req = req.insert_header(("Authorization", format!("Bearer {}", token)));
+        }
+        assert_eq!(status_of(test::try_call_service(&app, req.to_request()).await), status);
+    }
+    // Admin token is still limited to own user routes.
+    let req = test::TestRequest::get()
+        .uri(format!("/user/{}/todo", member_id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", admin_token)))
+        .to_request();
+    assert_eq!(status_of(test::try_call_service(&app, req).await), 401);
+
+    let admin_get = |uri: String| {
+        test::TestRequest::get().uri(&uri)
+            .insert_header(("Authorization", format!("Bearer {}", admin_token)))
+            .to_request()
+    };
+    let admin_post = |uri: String| {
+        test::TestRequest::post().uri(&uri)
+            .insert_header(("Authorization", format!("Bearer {}", admin_token)))
+            .to_request()
+    };
+
+    // USERS //
+    // (query, expected usernames)
+    let test_datas = [
+        ("q=ADMINTEST", vec!["admintest", "admintest_member"]),
+        ("q=admintest&role=admin", vec!["admintest"]),
+        ("q=admintest_member@", vec!["admintest_member"]),
+        ("q=admintest&per_page=1&page=1", vec!["admintest_member"]),
+        ("q=admintest&disabled=true", vec![]),
+    ];
+    for (query, usernames) in test_datas {
+        let resp: Value = test::call_and_read_body_json(&app, admin_get(format!("/admin/users?{}", query))).await;
+        let names: Vec<_> = resp["items"].as_array().unwrap().iter()
+            .map(|u| u["username"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, usernames, "{}", query);
+        assert!(resp["items"].as_array().unwrap().iter().all(|u| u.get("password").is_none()));
+    }
+    let resp: Value = test::call_and_read_body_json(&app, admin_get("/admin/users?q=admintest&per_page=1".to_owned())).await;
+    assert_eq!((resp["total"].as_u64(), resp["total_pages"].as_u64()), (Some(2), Some(2)));
+
+    let resp: Value = test::call_and_read_body_json(&app, admin_get(format!("/admin/users/{}", member_id))).await;
+    assert_eq!(resp["username"], "admintest_member");
+    assert_eq!(status_of(test::try_call_service(&app, admin_get("/admin/users/0".to_owned())).await), 404);
+
+    // Todos of any user.
+    let resp: Value = test::call_and_read_body_json(&app, admin_get(format!("/admin/users/{}/todo?q=priv", member_id))).await;
+    assert_eq!(resp["total"], 1);
+    assert_eq!(resp["items"][0]["name"], "Private");
+
+    // DISABLE //
+    assert_eq!(test::call_service(&app, admin_post(format!("/admin/users/{}/disable", admin_id))).await.status(), 400);
+    let resp: Value = test::call_and_read_body_json(&app, admin_post(format!("/admin/users/{}/disable", member_id))).await;
+    assert!(resp["disabled_at"].is_string());
+
+    // Sessions are logged out and login is refused.
+    let req = test::TestRequest::get()
+        .uri(format!("/user/{}/todo", member_id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", member_token)))
+        .to_request();
+    assert_eq!(status_of(test::try_call_service(&app, req).await), 401);
+    assert_eq!(test::call_service(&app, login("admintest_member", "World123!!")).await.status(), 403);
+    let resp: Value = test::call_and_read_body_json(&app, admin_get("/admin/users?q=admintest&disabled=true".to_owned())).await;
+    assert_eq!(resp["total"], 1);
+
+    let resp: Value = test::call_and_read_body_json(&app, admin_post(format!("/admin/users/{}/enable", member_id))).await;
+    assert!(resp["disabled_at"].is_null());
+    assert_eq!(test::call_service(&app, login("admintest_member", "World123!!")).await.status(), 200);
+
+    // FORCE PASSWORD RESET //
+    let resp: Value = test::call_and_read_body_json(&app, admin_post(format!("/admin/users/{}/password/reset", member_id))).await;
+    assert_eq!(resp["password_reset_required"], true);
+    assert_eq!(test::call_service(&app, login("admintest_member", "World123!!")).await.status(), 403);
+
+    let token = mail_tokens(&mail_dir, RESET_URL).pop().unwrap();
+    let req = test::TestRequest::post().uri("/user/password/reset")
+        .insert_header(ContentType::form_url_encoded())
+        .set_form(ResetPasswordForm{ token, password: "Hello123!!" })
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 204);
+    let login_resp: Value = test::call_and_read_body_json(&app, login("admintest_member", "Hello123!!")).await;
+    assert_eq!(login_resp["user"]["password_reset_required"], false);
+    let member_token = login_resp["access_token"].as_str().unwrap().to_owned();
+
+    // AUDIT //
+    let resp: Value = test::call_and_read_body_json(&app, admin_get(format!("/admin/audit?target_user_id={}", member_id))).await;
+    let actions: Vec<_> = resp["items"].as_array().unwrap().iter()
+        .map(|log| log["action"].as_str().unwrap())
+        .collect();
+    // Newest first, failed disable of admin itself is not recorded.
+    assert_eq!(actions, ["user.password_reset", "user.enable", "user.disable", "todo.list", "user.view"]);
+    assert!(resp["items"].as_array().unwrap().iter().all(|log| log["admin_id"] == admin_id));
+    assert_eq!(resp["items"][3]["detail"], "q=priv");
+
+    let resp: Value = test::call_and_read_body_json(&app, admin_get(format!("/admin/audit?target_user_id={}", admin_id))).await;
+    assert_eq!(resp["items"][0]["action"], "user.role");
+    assert!(resp["items"][0]["admin_id"].is_null());
+
+    // Reading audit logs is recorded too.
+    let resp: Value = test::call_and_read_body_json(&app, admin_get(format!("/admin/audit?admin_id={}&action=audit.list", admin_id))).await;
+    assert_eq!(resp["total"], 3);
+
+    // Delete test users
+    for (user_id, token) in [(member_id, member_token), (admin_id, admin_token.clone())] {
+        let req = test::TestRequest::delete()
+            .uri(format!("/user/{}", user_id).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .to_request();
+        test::call_service(&app, req).await;
+    }
+    fs::remove_dir_all(&mail_dir).unwrap();
+}
@@ -1,47 +1,81 @@
-use lazy_static::lazy_static;
-use serde_json::json;
 use std::{time::{SystemTime, UNIX_EPOCH, Duration}, ops::Add};
 use serde::{Deserialize, Serialize};
-use jsonwebtoken::{
-    encode, decode, Header, Validation, EncodingKey, DecodingKey
-};
 use actix_web_httpauth::extractors::bearer::BearerAuth;
 use actix_web::{
-    dev::ServiceRequest, HttpMessage
+    dev::ServiceRequest, http::Method, HttpMessage, web
+};
+use entity::{
+    entities::{personal_access_tokens, users},
+    mutation::Mutation
 };
 use myerror::ServerError;
+use myconfig::AuthConfig;
+use super::keys::JwtKeys;
+use super::AppState;
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JwtClaim {
+    pub user_id: i32,
+    /// Unique token id, used to revoke the token.
+    pub jti: String,
+    /// Session of the login, token is revoked with it.
+    pub sid: i32,
+    /// Role of user when token is issued. Tokens are revoked when role is changed.
+    #[serde(default = "default_role")]
+    pub role: String,
+    pub iat: usize,
+    /// Issue time in milliseconds, iat has only seconds.
+    /// Compared with logout of all sessions, so new login in same second is not revoked.
+    #[serde(default)]
+    pub iat_ms: u64,
+    pub exp: usize
+}
 
-#[allow(dead_code)]
-const JWT_EXP_MIN: u64 = 15;
-
-lazy_static! {
-    static ref TOKEN_VALIDATOR: Validation = {
-        let mut validator = Validation::default();
-        validator.leeway = 0;
-        validator
-    };
-
-    static ref FOR_DECODE: DecodingKey = DecodingKey::from_secret(SECRET_KEY.as_ref());
-    static ref FOR_ENCODE: EncodingKey = EncodingKey::from_secret(SECRET_KEY.as_ref());
+impl JwtClaim {
+    /// Tokens issued before iat_ms are regarded as issued at start of the second.
+    pub fn issued_at_ms(&self) -> i64 {
+        match self.iat_ms {
+            0 => self.iat as i64 * 1000,
+            iat_ms => iat_ms as i64,
+        }
+    }
 }
 
-const SECRET_KEY: &str = "jwt_scret_key";
+/// Tokens issued before roles have no role claim.
+fn default_role() -> String {
+    users::ROLE_USER.to_owned()
+}
 
+/// Claim of first login step of user with two-factor.
+/// It is exchanged with TOTP code for access token, and can not be used as access token.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct JwtClaim {
+pub struct MfaClaim {
     pub user_id: i32,
-    exp: usize
+    pub mfa: bool,
+    pub iat: usize,
+    pub exp: usize
 }
 
 pub async fn jwt_validator(
     req: ServiceRequest,
     _credentials: BearerAuth
 ) -> Result<ServiceRequest, (actix_web::Error, ServiceRequest)> {
+    let state = req.app_data::<web::Data<AppState>>().unwrap();
+
+    // Personal access token
+    if _credentials.token().starts_with(personal_access_tokens::TOKEN_PREFIX) {
+        return access_token_validator(req, _credentials).await
+    }
+
     // Decode Token
-    match verify_token(_credentials.token()).await {
+    match verify_token(&state.keys, _credentials.token()).await {
         // Token decode error
-        Err(e) => Err((actix_web::error::ErrorUnauthorized(json!(e)), req)),
+        Err(e) => Err((e.into(), req)),
+        // Revoked by logout, checked in memory
+        Ok(v) if state.revocations.is_revoked(&v) => {
+            let e = ServerError::UnauthorizedError { code: "TOKEN_REVOKED", msg: "JWT verfy error", detail: "Token is revoked".to_owned() };
+            Err((e.into(), req))
+        },
         // Valid
         Ok(v) => {
             // Compare jwt's user_id and path's user_id
@@ -56,44 +90,150 @@ pub async fn jwt_validator(
                 req.extensions_mut().insert(v);
                 return Ok(req)
             }
-            Err((actix_web::error::ErrorUnauthorized("NotFound"), req))
+            Err((user_mismatch().into(), req))
+        },
+    }
+}
+
+/// Token is valid but path is other user's.
+fn user_mismatch() -> ServerError {
+    ServerError::UnauthorizedError { code: "TOKEN_USER_MISMATCH", msg: "Invalid user", detail: "Token is not issued for this user".to_owned() }
+}
+
+/// Only access token of admin is allowed, personal access token can not be used.
+pub async fn admin_validator(
+    req: ServiceRequest,
+    _credentials: BearerAuth
+) -> Result<ServiceRequest, (actix_web::Error, ServiceRequest)> {
+    let state = req.app_data::<web::Data<AppState>>().unwrap();
+
+    if _credentials.token().starts_with(personal_access_tokens::TOKEN_PREFIX) {
+        let e = ServerError::ForbiddenError { code: "INSUFFICIENT_SCOPE", msg: "Insufficient scope", detail: "Personal access token is not allowed".to_owned() };
+        return Err((e.into(), req))
+    }
+
+    match verify_token(&state.keys, _credentials.token()).await {
+        Err(e) => Err((e.into(), req)),
+        Ok(v) if state.revocations.is_revoked(&v) => {
+            let e = ServerError::UnauthorizedError { code: "TOKEN_REVOKED", msg: "JWT verfy error", detail: "Token is revoked".to_owned() };
+            Err((e.into(), req))
+        },
+        Ok(v) if v.role == users::ROLE_ADMIN => {
+            req.extensions_mut().insert(v);
+            Ok(req)
+        },
+        Ok(_) => {
+            let e = ServerError::ForbiddenError { code: "ADMIN_REQUIRED", msg: "Admin only", detail: "Admin role is required".to_owned() };
+            Err((e.into(), req))
         },
     }
 }
 
+/// Personal access token is checked in database and allowed only routes of its scopes.
+async fn access_token_validator(
+    req: ServiceRequest,
+    _credentials: BearerAuth
+) -> Result<ServiceRequest, (actix_web::Error, ServiceRequest)> {
+    let state = req.app_data::<web::Data<AppState>>().unwrap();
+
+    let model = match Mutation::find_access_token(&state.conn, _credentials.token()).await {
+        Err(e) => return Err((e.into(), req)),
+        Ok(None) => {
+            let e = ServerError::UnauthorizedError { code: "TOKEN_INVALID", msg: "Access token verify error", detail: "Token is invalid or expired".to_owned() };
+            return Err((e.into(), req))
+        },
+        Ok(Some(model)) => model,
+    };
+
+    // Compare token's user_id and path's user_id
+    let path_user_id = req.match_info().query("user_id").as_bytes();
+    if path_user_id != model.user_id.to_string().as_bytes() {
+        return Err((user_mismatch().into(), req))
+    }
+
+    match required_scope(req.method(), req.path()) {
+        Some(scope) if model.allows(scope) => {
+            req.extensions_mut().insert(model);
+            Ok(req)
+        },
+        Some(scope) => {
+            let e = ServerError::ForbiddenError { code: "INSUFFICIENT_SCOPE", msg: "Insufficient scope", detail: format!("{} scope is required", scope) };
+            Err((e.into(), req))
+        },
+        None => {
+            let e = ServerError::ForbiddenError { code: "INSUFFICIENT_SCOPE", msg: "Insufficient scope", detail: "Personal access token is not allowed".to_owned() };
+            Err((e.into(), req))
+        },
+    }
+}
+
+/// Scope required for route under /user/{user_id}.
+/// None if personal access token can not be used, e.g. logout, token management or changing account.
+fn required_scope(method: &Method, path: &str) -> Option<&'static str> {
+    let read = method == Method::GET;
+    // "/user/{user_id}/{resource}/..."
+    match path.split('/').nth(3).unwrap_or("") {
+        "todo" | "tag" | "project" | "series" | "notification" if read => Some("todo:read"),
+        "todo" | "tag" | "project" | "series" | "notification" => Some("todo:write"),
+        "" | "session" if read => Some("user:read"),
+        "session" => Some("user:write"),
+        _ => None,
+    }
+}
 
-pub async fn generate_token(user_id: i32) -> Result<String, ServerError> {
+
+pub async fn generate_token(keys: &JwtKeys, config: &AuthConfig, user_id: i32, session_id: i32, role: &str) -> Result<String, ServerError> {
     // Make Custom Claim
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
     let claims = JwtClaim{
-            user_id: user_id,
-            exp: SystemTime::now()
-                .duration_since(UNIX_EPOCH).unwrap()
-                .add(Duration::from_secs(60 * JWT_EXP_MIN))
+            user_id,
+            jti: hex::encode(rand::random::<[u8; 16]>()),
+            sid: session_id,
+            role: role.to_owned(),
+            iat: now.as_secs() as usize,
+            iat_ms: now.as_millis() as u64,
+            exp: now
+                .add(Duration::from_secs(60 * config.access_token_exp_min))
                 .as_secs() as usize
         };
 
-    // Make Signed Token
-    let signed_token = encode(
-            &Header::default(), 
-            &claims, 
-            &FOR_ENCODE
-        )
-        .map_err(|e|
-            ServerError::InternalServerError { msg: "JWT generate error", detail: e.to_string() }
-        )?;
-
-    Ok(signed_token)
+    // Make Signed Token, kid of signing key is in header.
+    keys.encode(&claims)
+}
+
+pub async fn verify_token(keys: &JwtKeys, token: &str) -> Result<JwtClaim, ServerError> {
+    keys.decode::<JwtClaim>(token)
 }
 
-pub async fn verify_token(token: &str) -> Result<JwtClaim, ServerError> {
-    let token = decode::<JwtClaim>(
-            &token, 
-            &FOR_DECODE, 
-            &TOKEN_VALIDATOR
-        )
-        .map_err(|e|
-            ServerError::InternalServerError { msg: "JWT verfy error", detail: e.to_string() }
-        )?;
-
-    Ok(token.claims)
-}
\ No newline at end of file
+pub async fn generate_mfa_token(keys: &JwtKeys, config: &AuthConfig, user_id: i32) -> Result<String, ServerError> {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+    let claims = MfaClaim{
+            user_id,
+            mfa: true,
+            iat: now.as_secs() as usize,
+            exp: now
+                .add(Duration::from_secs(60 * config.mfa_token_exp_min))
+                .as_secs() as usize
+        };
+    keys.encode(&claims)
+}
+
+/// user_id of valid MFA token.
+pub async fn verify_mfa_token(keys: &JwtKeys, token: &str) -> Result<i32, ServerError> {
+    match keys.decode::<MfaClaim>(token) {
+        Ok(claim) if claim.mfa => Ok(claim.user_id),
+        _ => Err(ServerError::UnauthorizedError { code: "MFA_TOKEN_INVALID", msg: "Invalid MFA token", detail: "MFA token is invalid or expired".to_owned() }),
+    }
+}
+
+/// Some actions need verified email if auth.require_verified_email is set.
+pub async fn require_verified_email(state: &AppState, user_id: i32) -> Result<(), ServerError> {
+    if !state.config.auth.require_verified_email {
+        return Ok(())
+    }
+    let user = Mutation::get_user(&state.conn, user_id).await?;
+    if user.email_verified_at.is_none() {
+        return Err(ServerError::ForbiddenError { code: "EMAIL_NOT_VERIFIED", msg: "Email not verified", detail: "Verify email to use this feature".to_owned() })
+    }
+    Ok(())
+}
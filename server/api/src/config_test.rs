@@ -0,0 +1,136 @@
+use std::{fs, path::PathBuf};
+use clap::Parser;
+use myconfig::{Config, ConfigArgs, ConfigError};
+
+// Flags and environment variables as server binary.
+#[derive(Parser)]
+struct Cli {
+    #[command(flatten)]
+    config: ConfigArgs,
+}
+
+fn parse(flags: &[&str]) -> ConfigArgs {
+    let args = std::iter::once("server").chain(flags.iter().copied());
+    Cli::try_parse_from(args).unwrap().config
+}
+
+// Config file in temporary directory, removed by caller.
+fn config_file(name: &str, toml: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("todo-config-{}-{}.toml", std::process::id(), name));
+    fs::write(&path, toml).unwrap();
+    path
+}
+
+const SECRET: &str = "config_test_jwt_secret_32_bytes!";
+
+#[test]
+fn test_config_priority() {
+    let path = config_file("priority", &format!(r#"
+        [server]
+        host = "127.0.0.1"
+        port = 9000
+
+        [database]
+        url = "postgres://file/todo"
+
+        [auth]
+        jwt_secret = "{}"
+        access_token_exp_min = 20
+        refresh_token_exp_days = 7
+    "#, SECRET));
+    let config_flag = format!("--config={}", path.display());
+
+    // Only this test uses these variables.
+    std::env::set_var("TODO_PORT", "9100");
+    std::env::set_var("TODO_ACCESS_TOKEN_EXP_MIN", "30");
+
+    // Flag > environment variable > file > default
+    let config = Config::load(&parse(&[&config_flag, "--port", "9200"])).unwrap();
+    assert_eq!(config.server.port, 9200);
+    assert_eq!(config.auth.access_token_exp_min, 30);
+    assert_eq!(config.auth.refresh_token_exp_days, 7);
+    assert_eq!(config.server.host, "127.0.0.1");
+    assert_eq!(config.database.url, "postgres://file/todo");
+    assert_eq!(config.database.max_connections, 100);
+
+    // Environment variable > file
+    let config = Config::load(&parse(&[&config_flag])).unwrap();
+    assert_eq!(config.server.port, 9100);
+
+    std::env::remove_var("TODO_PORT");
+    std::env::remove_var("TODO_ACCESS_TOKEN_EXP_MIN");
+
+    // File > default
+    let config = Config::load(&parse(&[&config_flag])).unwrap();
+    assert_eq!(config.server.port, 9000);
+    assert_eq!(config.auth.access_token_exp_min, 20);
+
+    // Flag > file, without file every value is default.
+    let config = Config::load(&parse(&[&config_flag, "--database-url", "postgres://flag/todo"])).unwrap();
+    assert_eq!(config.database.url, "postgres://flag/todo");
+    let config = Config::load(&parse(&["--database-url", "postgres://flag/todo", "--jwt-secret", SECRET])).unwrap();
+    assert_eq!((config.server.host.as_str(), config.server.port), ("0.0.0.0", 8080));
+
+    fs::remove_file(&path).unwrap();
+
+    // Example config is valid.
+    let example = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../config.example.toml");
+    Config::load(&ConfigArgs { config_path: Some(example.display().to_string()), ..Default::default() }).unwrap();
+}
+
+#[test]
+fn test_config_validation() {
+    let args = ConfigArgs {
+        database_url: Some("postgres://localhost/todo".to_owned()),
+        jwt_secret: Some(SECRET.to_owned()),
+        ..Default::default()
+    };
+    assert!(Config::load(&args).is_ok());
+
+    // (args, expected field of missing value)
+    let test_datas = [
+        (ConfigArgs { jwt_secret: None, ..args.clone() }, "auth.jwt_secret"),
+        (ConfigArgs { database_url: None, ..args.clone() }, "database.url"),
+    ];
+    for (args, field) in test_datas {
+        match Config::load(&args) {
+            Err(ConfigError::MissingError { field: f }) => assert_eq!(f, field),
+            other => panic!("{}: {:?}", field, other),
+        }
+    }
+
+    // (args, expected field of invalid value)
+    let test_datas = [
+        (ConfigArgs { jwt_secret: Some("short_secret".to_owned()), ..args.clone() }, "auth.jwt_secret"),
+        (ConfigArgs { port: Some(0), ..args.clone() }, "server.port"),
+        (ConfigArgs { database_url: Some("mysql://localhost/todo".to_owned()), ..args.clone() }, "database.url"),
+        (ConfigArgs { db_min_connections: Some(10), db_max_connections: Some(5), ..args.clone() }, "database.min_connections"),
+    ];
+    for (args, field) in test_datas {
+        match Config::load(&args) {
+            Err(ConfigError::InvalidError { field: f, .. }) => assert_eq!(f, field),
+            other => panic!("{}: {:?}", field, other),
+        }
+    }
+
+    // Port out of range is rejected by flag and file.
+    assert!(Cli::try_parse_from(["server", "--port", "70000"]).is_err());
+    assert!(Cli::try_parse_from(["server", "--port", "http"]).is_err());
+    for (i, toml) in ["[server]\nport = 70000", "[server]\nport = -1", "[server]\nunknown = 1"].into_iter().enumerate() {
+        let path = config_file(&format!("invalid-{}", i), toml);
+        let args = ConfigArgs { config_path: Some(path.display().to_string()), ..args.clone() };
+        assert!(matches!(Config::load(&args), Err(ConfigError::ParseError { .. })), "{}", toml);
+        fs::remove_file(&path).unwrap();
+    }
+    let args = ConfigArgs { config_path: Some("/nonexistent/todo.toml".to_owned()), ..args.clone() };
+    assert!(matches!(Config::load(&args), Err(ConfigError::ReadError { .. })));
+
+    // Commands other than serve need database only.
+    let db_args = ConfigArgs { database_url: Some("postgres://localhost/todo".to_owned()), ..Default::default() };
+    assert!(Config::load(&db_args).is_err());
+    assert_eq!(Config::load_database(&db_args).unwrap().url, "postgres://localhost/todo");
+    assert!(matches!(
+        Config::load_database(&ConfigArgs::default()),
+        Err(ConfigError::MissingError { field: "database.url" })
+    ));
+}
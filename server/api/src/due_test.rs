@@ -0,0 +1,267 @@
+#![allow(dead_code)]
+
+use actix_web::{
+    App, web, test, http::header::ContentType,
+};
+use actix_web_httpauth::middleware::HttpAuthentication;
+use serde::{Serialize, Deserialize};
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate, Utc};
+use super::{user, auth, todo};
+use super::user_test::*;
+
+#[derive(Serialize, Clone)]
+struct TimeZoneSignUpForm {
+    username: &'static str,
+    password: &'static str,
+    email: &'static str,
+    time_zone: &'static str,
+}
+
+#[derive(Deserialize)]
+struct TimeZoneUserReturnForm {
+    id: i32,
+    time_zone: String,
+}
+
+#[derive(Serialize, Clone)]
+struct DueTodoForm {
+    name: String,
+    contents: Option<String>,
+    due_date: Option<NaiveDate>,
+    due_at: Option<DateTime<FixedOffset>>,
+    completed: bool,
+    recurrence: Option<String>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+struct DueTodoReturnForm {
+    id: i32,
+    name: String,
+    due_date: Option<NaiveDate>,
+    due_at: Option<DateTime<FixedOffset>>,
+    completed: bool,
+    series_id: Option<i32>,
+    created_at: DateTime<FixedOffset>,
+}
+
+#[derive(Deserialize)]
+struct DueTodoPageReturnForm {
+    items: Vec<DueTodoReturnForm>,
+    total: u64,
+}
+
+fn at(s: &str) -> DateTime<FixedOffset> {
+    DateTime::parse_from_rfc3339(s).unwrap()
+}
+
+#[actix_web::test]
+async fn test_due() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(db_connect().await.clone()))
+            .service(user::signup_user)
+            .service(user::login_user)
+            .service(
+                web::scope("/user/{user_id}")
+                    .wrap(HttpAuthentication::bearer(auth::jwt_validator))
+                    .service(user::modify_user)
+                    .service(user::withdrawal_user)
+                    .service(
+                        web::scope("/todo")
+                            .service(todo::fetch_todos)
+                            .service(todo::create_todo)
+                            .service(todo::modify_todo)
+                            .service(todo::remove_todo)
+                    )
+            )
+    ).await;
+
+    // SIGN UP WITH TIME ZONE //
+    // Invalid
+    let req = test::TestRequest::post().uri("/user/register")
+        .insert_header(ContentType::form_url_encoded())
+        .set_form(TimeZoneSignUpForm{ username: "due", password: "World123!!", email: "due@gmail.com", time_zone: "Mars/Olympus" })
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), 400);
+
+    // Kiritimati is UTC+14 without daylight saving
+    let req = test::TestRequest::post().uri("/user/register")
+        .insert_header(ContentType::form_url_encoded())
+        .set_form(TimeZoneSignUpForm{ username: "due", password: "World123!!", email: "due@gmail.com", time_zone: "Pacific/Kiritimati" })
+        .to_request();
+    let signup_resp: TimeZoneUserReturnForm = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(signup_resp.time_zone, "Pacific/Kiritimati");
+
+    // Default is UTC
+    let req = test::TestRequest::post().uri("/user/register")
+        .insert_header(ContentType::form_url_encoded())
+        .set_form(UserSignUpForm{ username: "due2", password: "World123!!", email: "due2@gmail.com" })
+        .to_request();
+    let signup_resp: TimeZoneUserReturnForm = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(signup_resp.time_zone, "UTC");
+
+    let mut login_resps = Vec::new();
+    for username in ["due", "due2"] {
+        let req = test::TestRequest::post().uri("/user/login")
+            .insert_header(ContentType::form_url_encoded())
+            .set_form(UserLoginForm{ username, password: "World123!!" })
+            .to_request();
+        let login_resp: UserLoginReturnForm = test::call_and_read_body_json(&app, req).await;
+        login_resps.push(login_resp);
+    }
+    let utc_resp = login_resps.pop().unwrap();
+    let login_resp = login_resps.pop().unwrap();
+
+    let form = |name: &str, due_date: Option<NaiveDate>, due_at: Option<DateTime<FixedOffset>>| DueTodoForm {
+        name: name.to_owned(),
+        contents: None,
+        due_date,
+        due_at,
+        completed: false,
+        recurrence: None,
+    };
+
+    // DUE AT //
+    // (user, due_at, expected due_date)
+    let test_datas = [
+        (&login_resp, "2026-10-18T15:00:00Z", "2026-10-19"),
+        (&utc_resp, "2026-10-18T15:00:00Z", "2026-10-18"),
+        (&utc_resp, "2026-10-19T03:00:00+09:00", "2026-10-18"),
+    ];
+    for (resp, due_at, due_date) in test_datas {
+        let req = test::TestRequest::post()
+            .uri(format!("/user/{}/todo/register", resp.user.id).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", resp.access_token)))
+            .set_json(form("Timed", None, Some(at(due_at))))
+            .to_request();
+        let todo_resp: DueTodoReturnForm = test::call_and_read_body_json(&app, req).await;
+
+        assert_eq!(todo_resp.due_at, Some(at(due_at)));
+        assert_eq!(todo_resp.due_date, Some(due_date.parse().unwrap()), "{}", due_at);
+    }
+
+    // due_date must be date of due_at in user's time zone
+    let req = test::TestRequest::post()
+        .uri(format!("/user/{}/todo/register", login_resp.user.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .set_json(form("Mismatch", Some("2026-10-18".parse().unwrap()), Some(at("2026-10-18T15:00:00Z"))))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), 400);
+
+    // OVERDUE AND DUE TODAY //
+    // Today in Kiritimati
+    let now = Utc::now();
+    let today = (now + Duration::hours(14)).date_naive();
+    let test_datas = [
+        ("Check timed past", None, Some(now - Duration::minutes(1))),
+        ("Check timed future", None, Some(now + Duration::minutes(1))),
+        ("Check all day yesterday", Some(today - Duration::days(1)), None),
+        ("Check all day today", Some(today), None),
+    ];
+    for (name, due_date, due_at) in test_datas {
+        let req = test::TestRequest::post()
+            .uri(format!("/user/{}/todo/register", login_resp.user.id).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+            .set_json(form(name, due_date, due_at.map(|t| t.fixed_offset())))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), 201);
+    }
+
+    // (query, expected todo names)
+    let test_datas = [
+        ("overdue=true&q=Check", vec!["Check all day yesterday", "Check timed past"]),
+        ("due_today=true&q=Check", vec!["Check all day today"]),
+    ];
+    for (query, names) in test_datas {
+        let req = test::TestRequest::get()
+            .uri(format!("/user/{}/todo?{}&sort=name&order=asc", login_resp.user.id, query).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+            .to_request();
+        let todo_resp: DueTodoPageReturnForm = test::call_and_read_body_json(&app, req).await;
+        let mut fetch_names: Vec<&str> = todo_resp.items.iter().map(|t| t.name.as_str()).collect();
+        // Timed todos are due today only if now is same day in Kiritimati.
+        fetch_names.retain(|n| !n.starts_with("Check timed") || query.starts_with("overdue"));
+
+        assert_eq!(fetch_names, names, "{}", query);
+    }
+
+    // MODIFY TIME ZONE //
+    // Invalid
+    let req = test::TestRequest::put()
+        .uri(format!("/user/{}", login_resp.user.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .insert_header(ContentType::form_url_encoded())
+        .set_form(TimeZoneSignUpForm{ username: "due", password: "World123!!", email: "due@gmail.com", time_zone: "KST" })
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), 400);
+
+    let req = test::TestRequest::put()
+        .uri(format!("/user/{}", login_resp.user.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .insert_header(ContentType::form_url_encoded())
+        .set_form(TimeZoneSignUpForm{ username: "due", password: "World123!!", email: "due@gmail.com", time_zone: "America/New_York" })
+        .to_request();
+    let user_resp: TimeZoneUserReturnForm = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(user_resp.time_zone, "America/New_York");
+
+    // Time zone is not changed without it
+    let req = test::TestRequest::put()
+        .uri(format!("/user/{}", login_resp.user.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .insert_header(ContentType::form_url_encoded())
+        .set_form(UserSignUpForm{ username: "due", password: "World123!!", email: "due@gmail.com" })
+        .to_request();
+    let user_resp: TimeZoneUserReturnForm = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(user_resp.time_zone, "America/New_York");
+
+    // RECURRING DUE AT //
+    // Daylight saving ends at 2026-11-01 in New York, next todo keeps 9am local time.
+    let req = test::TestRequest::post()
+        .uri(format!("/user/{}/todo/register", login_resp.user.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .set_json(DueTodoForm{
+            recurrence: Some("FREQ=DAILY".to_owned()),
+            ..form("Standup", None, Some(at("2026-10-31T09:00:00-04:00")))
+        })
+        .to_request();
+    let todo_resp: DueTodoReturnForm = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(todo_resp.due_date, Some("2026-10-31".parse().unwrap()));
+
+    let req = test::TestRequest::put()
+        .uri(format!("/user/{}/todo/{}", login_resp.user.id, todo_resp.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .set_json(DueTodoForm{ completed: true, ..form("Standup", None, todo_resp.due_at) })
+        .to_request();
+    test::call_service(&app, req).await;
+
+    let req = test::TestRequest::get()
+        .uri(format!("/user/{}/todo?series_id={}&completed=false", login_resp.user.id, todo_resp.series_id.unwrap()).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .to_request();
+    let todo_resp: DueTodoPageReturnForm = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(todo_resp.items[0].due_date, Some("2026-11-01".parse().unwrap()));
+    assert_eq!(todo_resp.items[0].due_at, Some(at("2026-11-01T09:00:00-05:00")));
+
+    // Delete test users
+    for resp in [login_resp, utc_resp] {
+        let req = test::TestRequest::delete()
+            .uri(format!("/user/{}", resp.user.id).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", resp.access_token)))
+            .to_request();
+        test::call_service(&app, req).await;
+    }
+}
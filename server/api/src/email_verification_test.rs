@@ -0,0 +1,169 @@
+#![allow(dead_code)]
+
+use std::fs;
+use actix_web::{
+    App, web, test, http::header::ContentType,
+};
+use actix_web_httpauth::middleware::HttpAuthentication;
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use super::{user, auth, access_token};
+use super::user_test::*;
+use super::password_reset_test::mail_tokens;
+
+const VERIFY_URL: &str = "https://todo.example.com/verify-email?token=";
+
+#[derive(Serialize)]
+struct VerifyEmailForm {
+    token: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct VerifiedUserReturnForm {
+    id: i32,
+    email: String,
+    email_verified_at: Option<DateTime<FixedOffset>>,
+    pending_email: Option<String>,
+}
+
+#[actix_web::test]
+async fn test_email_verification() {
+    let mail_dir = std::env::temp_dir().join(format!("todo-mail-{}", rand::random::<u32>()));
+    let mut config = test_config();
+    config.mail.dir = Some(mail_dir.to_string_lossy().into_owned());
+    config.auth.email_verification_url = Some(format!("{}{{token}}", VERIFY_URL));
+    config.auth.require_verified_email = true;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(app_state(config).await))
+            .service(user::signup_user)
+            .service(user::login_user)
+            .service(user::verify_email)
+            .service(
+                web::scope("/user/{user_id}")
+                    .wrap(HttpAuthentication::bearer(auth::jwt_validator))
+                    .service(user::modify_user)
+                    .service(user::withdrawal_user)
+                    .service(user::resend_verification_email)
+                    .service(
+                        web::scope("/token")
+                            .service(access_token::create_access_token)
+                    )
+            )
+    ).await;
+
+    let req = test::TestRequest::post().uri("/user/register")
+        .insert_header(ContentType::form_url_encoded())
+        .set_form(UserSignUpForm{ username: "verify", password: "World123!!", email: "verify@gmail.com" })
+        .to_request();
+    let signup_resp: VerifiedUserReturnForm = test::call_and_read_body_json(&app, req).await;
+    let user_id = signup_resp.id;
+
+    assert!(signup_resp.email_verified_at.is_none());
+    let tokens = mail_tokens(&mail_dir, VERIFY_URL);
+    assert_eq!(tokens.len(), 1);
+
+    let req = test::TestRequest::post().uri("/user/login")
+        .insert_header(ContentType::form_url_encoded())
+        .set_form(UserLoginForm{ username: "verify", password: "World123!!" })
+        .to_request();
+    let login_resp: UserLoginReturnForm = test::call_and_read_body_json(&app, req).await;
+
+    // REQUIRE VERIFIED EMAIL //
+    let req = test::TestRequest::post()
+        .uri(format!("/user/{}/token/register", user_id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .set_json(json!({ "name": "script", "scopes": "todo:read" }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 403);
+
+    // VERIFY SIGN UP EMAIL //
+    // (token, expected status)
+    let test_datas = [
+        ("not_a_verification_token".to_owned(), 400),
+        (tokens[0].clone(), 200),
+        // Single-use
+        (tokens[0].clone(), 400),
+    ];
+    for (token, status) in test_datas {
+        let req = test::TestRequest::post().uri("/user/email/verify")
+            .insert_header(ContentType::form_url_encoded())
+            .set_form(VerifyEmailForm{ token })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), status);
+        if resp.status() == 200 {
+            let verify_resp: VerifiedUserReturnForm = test::read_body_json(resp).await;
+            assert_eq!(verify_resp.email, "verify@gmail.com");
+            assert!(verify_resp.email_verified_at.is_some());
+        }
+    }
+
+    let req = test::TestRequest::post()
+        .uri(format!("/user/{}/token/register", user_id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .set_json(json!({ "name": "script", "scopes": "todo:read" }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 201);
+
+    // Nothing to verify
+    let req = test::TestRequest::post()
+        .uri(format!("/user/{}/email/resend", user_id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 409);
+
+    // CHANGE EMAIL //
+    let req = test::TestRequest::put()
+        .uri(format!("/user/{}", user_id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .insert_header(ContentType::form_url_encoded())
+        .set_form(UserSignUpForm{ username: "verify", password: "World123!!", email: "verify2@gmail.com" })
+        .to_request();
+    let modify_resp: VerifiedUserReturnForm = test::call_and_read_body_json(&app, req).await;
+
+    // Old email stays active until new one is verified.
+    assert_eq!(modify_resp.email, "verify@gmail.com");
+    assert!(modify_resp.email_verified_at.is_some());
+    assert_eq!(modify_resp.pending_email.as_deref(), Some("verify2@gmail.com"));
+
+    // Resent token replaces previous one.
+    let req = test::TestRequest::post()
+        .uri(format!("/user/{}/email/resend", user_id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 202);
+
+    let tokens = mail_tokens(&mail_dir, VERIFY_URL);
+    assert_eq!(tokens.len(), 3);
+
+    // (token, expected status)
+    let test_datas = [
+        (tokens[1].clone(), 400),
+        (tokens[2].clone(), 200),
+    ];
+    for (token, status) in test_datas {
+        let req = test::TestRequest::post().uri("/user/email/verify")
+            .insert_header(ContentType::form_url_encoded())
+            .set_form(VerifyEmailForm{ token })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), status);
+        if resp.status() == 200 {
+            let verify_resp: VerifiedUserReturnForm = test::read_body_json(resp).await;
+            assert_eq!(verify_resp.email, "verify2@gmail.com");
+        }
+    }
+
+    // Delete test user
+    let req = test::TestRequest::delete()
+        .uri(format!("/user/{}", user_id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .to_request();
+    test::call_service(&app, req).await;
+    fs::remove_dir_all(&mail_dir).unwrap();
+}
@@ -0,0 +1,193 @@
+#![allow(dead_code)]
+
+use std::time::{SystemTime, UNIX_EPOCH};
+use actix_web::{
+    App, web, test, body, dev::ServiceResponse,
+    http::{StatusCode, header::{self, ContentType}},
+};
+use actix_web_httpauth::middleware::HttpAuthentication;
+use serde_json::{json, Value};
+use myerror::ServerError;
+use super::{user, auth, todo};
+use super::user_test::*;
+
+// Status, content type and body of response, rejected token is error of middleware.
+async fn problem_of(resp: Result<ServiceResponse, actix_web::Error>) -> (StatusCode, String, Value) {
+    let resp = match resp {
+        Ok(resp) => resp.map_into_boxed_body().into_parts().1,
+        Err(e) => e.error_response(),
+    };
+    let status = resp.status();
+    let content_type = resp.headers().get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .to_owned();
+    let bytes = body::to_bytes(resp.into_body()).await.unwrap();
+    (status, content_type, serde_json::from_slice(&bytes).unwrap())
+}
+
+#[actix_web::test]
+async fn test_problem_detail() {
+    let state = db_connect().await;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state.clone()))
+            .service(user::signup_user)
+            .service(user::login_user)
+            .service(
+                web::scope("/user/{user_id}")
+                    .wrap(HttpAuthentication::bearer(auth::jwt_validator))
+                    .service(user::withdrawal_user)
+                    .service(
+                        web::scope("/todo")
+                            .service(todo::fetch_todos)
+                            .service(todo::remove_todo)
+                    )
+            )
+    ).await;
+
+    let req = test::TestRequest::post().uri("/user/register")
+        .insert_header(ContentType::form_url_encoded())
+        .set_form(UserSignUpForm{ username: "problem", password: "World123!!", email: "problem@gmail.com" })
+        .to_request();
+    let signup_resp: UserSignUpReturnForm = test::call_and_read_body_json(&app, req).await;
+    let user_id = signup_resp.id;
+
+    let req = test::TestRequest::post().uri("/user/login")
+        .insert_header(ContentType::form_url_encoded())
+        .set_form(UserLoginForm{ username: "problem", password: "World123!!" })
+        .to_request();
+    let login_resp: UserLoginReturnForm = test::call_and_read_body_json(&app, req).await;
+
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as usize;
+    let expired = state.keys.encode(&auth::JwtClaim {
+        user_id,
+        jti: "expired".to_owned(),
+        sid: 0,
+        role: "user".to_owned(),
+        iat: now - 120,
+        iat_ms: 0,
+        exp: now - 60,
+    }).unwrap();
+
+    // (method, uri, token, expected status, expected code)
+    let test_datas = [
+        ("DELETE", format!("/user/{}/todo/0", user_id), login_resp.access_token.clone(), 404, "TODO_NOT_FOUND"),
+        ("GET", format!("/user/{}/todo", user_id), expired, 401, "TOKEN_EXPIRED"),
+        ("GET", format!("/user/{}/todo", user_id), "not.a.token".to_owned(), 401, "TOKEN_INVALID"),
+        ("GET", format!("/user/{}/todo", user_id + 1), login_resp.access_token.clone(), 401, "TOKEN_USER_MISMATCH"),
+        ("GET", format!("/user/{}/todo?per_page=0", user_id), login_resp.access_token.clone(), 400, "INVALID_QUERY"),
+    ];
+    for (method, uri, token, status, code) in test_datas {
+        let req = match method {
+            "DELETE" => test::TestRequest::delete(),
+            _ => test::TestRequest::get(),
+        };
+        let req = req.uri(&uri)
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .to_request();
+        let (resp_status, content_type, problem) = problem_of(test::try_call_service(&app, req).await).await;
+
+        assert_eq!(resp_status, status, "{}", code);
+        assert_eq!(content_type, myerror::PROBLEM_JSON);
+        assert_eq!(problem["type"], "about:blank");
+        assert_eq!(problem["status"], status);
+        assert_eq!(problem["code"], code);
+        assert!(problem["title"].is_string());
+    }
+
+    // Cause of internal error is only logged.
+    let e = ServerError::InternalServerError { msg: "Database fetch error", detail: "relation \"todos\" does not exist".to_owned() };
+    let (status, _, problem) = problem_of(Err(e.into())).await;
+    assert_eq!(status, 500);
+    assert_eq!(problem["code"], myerror::INTERNAL_ERROR);
+    assert!(problem.get("detail").is_none());
+
+    // Delete test user
+    let req = test::TestRequest::delete()
+        .uri(format!("/user/{}", user_id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .to_request();
+    test::call_service(&app, req).await;
+}
+
+#[actix_web::test]
+async fn test_validation_error() {
+    let state = db_connect().await;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state.clone()))
+            .service(user::signup_user)
+            .service(user::login_user)
+            .service(
+                web::scope("/user/{user_id}")
+                    .wrap(HttpAuthentication::bearer(auth::jwt_validator))
+                    .service(user::withdrawal_user)
+                    .service(
+                        web::scope("/todo")
+                            .service(todo::create_todo)
+                    )
+            )
+    ).await;
+
+    // Every broken password rule is returned, with rules of other fields.
+    let req = test::TestRequest::post().uri("/user/register")
+        .insert_header(ContentType::form_url_encoded())
+        .set_form(UserSignUpForm{ username: "validation", password: "world 1", email: "validation" })
+        .to_request();
+    let (status, _, problem) = problem_of(test::try_call_service(&app, req).await).await;
+    assert_eq!(status, 400);
+    assert_eq!(problem["code"], myerror::VALIDATION_FAILED);
+    let codes = |field: &str| -> Vec<String> {
+        problem["errors"][field].as_array().unwrap().iter()
+            .map(|e| e["code"].as_str().unwrap().to_owned())
+            .collect()
+    };
+    assert_eq!(codes("password"), ["too_short", "missing_uppercase", "missing_special", "invalid_character"]);
+    assert_eq!(codes("email"), ["email"]);
+    assert!(problem["errors"].get("username").is_none());
+    assert_eq!(problem["errors"]["password"][0]["params"]["min"], 8);
+    assert!(problem["errors"]["password"][0]["message"].is_string());
+    // Value of request is not echoed.
+    assert!(problem["errors"]["email"][0]["params"].get("value").is_none());
+
+    let req = test::TestRequest::post().uri("/user/register")
+        .insert_header(ContentType::form_url_encoded())
+        .set_form(UserSignUpForm{ username: "validation", password: "World123!!", email: "validation@gmail.com" })
+        .to_request();
+    let signup_resp: UserSignUpReturnForm = test::call_and_read_body_json(&app, req).await;
+    let user_id = signup_resp.id;
+
+    let req = test::TestRequest::post().uri("/user/login")
+        .insert_header(ContentType::form_url_encoded())
+        .set_form(UserLoginForm{ username: "validation", password: "World123!!" })
+        .to_request();
+    let login_resp: UserLoginReturnForm = test::call_and_read_body_json(&app, req).await;
+
+    // (todo, expected field, expected code)
+    let test_datas = [
+        (json!({ "name": "", "completed": false }), "name", "length"),
+        (json!({ "name": "a".repeat(101), "completed": false }), "name", "length"),
+        (json!({ "name": "Due", "completed": false, "due_at": "2023-05-01T00:00:00Z", "due_date": "2023-05-02" }), "due_date", "due_date_mismatch"),
+        (json!({ "name": "Recurring", "completed": false, "recurrence": "FREQ=DAILY" }), "due_date", "required"),
+    ];
+    for (todo, field, code) in test_datas {
+        let req = test::TestRequest::post()
+            .uri(format!("/user/{}/todo/register", user_id).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+            .set_json(todo)
+            .to_request();
+        let (status, _, problem) = problem_of(test::try_call_service(&app, req).await).await;
+        assert_eq!(status, 400, "{}", code);
+        assert_eq!(problem["errors"][field][0]["code"], code);
+    }
+
+    // Delete test user
+    let req = test::TestRequest::delete()
+        .uri(format!("/user/{}", user_id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .to_request();
+    test::call_service(&app, req).await;
+}
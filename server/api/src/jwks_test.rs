@@ -0,0 +1,157 @@
+#![allow(dead_code)]
+
+use actix_web::{
+    App, web, test, http::header::ContentType,
+};
+use actix_web_httpauth::middleware::HttpAuthentication;
+use jsonwebtoken::{
+    decode, decode_header, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation, jwk::JwkSet
+};
+use myconfig::{Config, JwtAlgorithm, JwtPublicKey};
+use super::{user, auth, keys, todo, AppState};
+use super::user_test::*;
+use super::logout_test::status_of;
+
+// Config signing with the key, both keys are active.
+fn key_config(algorithm: JwtAlgorithm, kid: &str, private_key: &str) -> Config {
+    let mut config = test_config();
+    config.auth.jwt_algorithm = algorithm;
+    config.auth.jwt_key_id = Some(kid.to_owned());
+    config.auth.jwt_private_key = Some(private_key.to_owned());
+    config.auth.jwt_public_keys = vec![
+        JwtPublicKey { kid: "rsa-1".to_owned(), algorithm: JwtAlgorithm::RS256, path: "testdata/rs256.pub.pem".to_owned() },
+        JwtPublicKey { kid: "ed-1".to_owned(), algorithm: JwtAlgorithm::EdDSA, path: "testdata/ed25519.pub.pem".to_owned() },
+    ];
+    config
+}
+
+async fn login(state: &AppState) -> UserLoginReturnForm {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state.clone()))
+            .service(user::login_user)
+    ).await;
+    let req = test::TestRequest::post().uri("/user/login")
+        .insert_header(ContentType::form_url_encoded())
+        .set_form(UserLoginForm{ username: "jwks", password: "World123!!" })
+        .to_request();
+    test::call_and_read_body_json(&app, req).await
+}
+
+#[actix_web::test]
+async fn test_jwks() {
+    let rsa_state = app_state(key_config(JwtAlgorithm::RS256, "rsa-1", "testdata/rs256.pem")).await;
+    // Server rotated to next key
+    let ed_state = app_state(key_config(JwtAlgorithm::EdDSA, "ed-1", "testdata/ed25519.pem")).await;
+    // Server not migrated from HS256 yet
+    let hs_state = db_connect().await;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(rsa_state.clone()))
+            .service(user::signup_user)
+            .service(keys::fetch_jwks)
+            .service(
+                web::scope("/user/{user_id}")
+                    .wrap(HttpAuthentication::bearer(auth::jwt_validator))
+                    .service(user::withdrawal_user)
+                    .service(
+                        web::scope("/todo")
+                            .service(todo::fetch_todos)
+                    )
+            )
+    ).await;
+
+    let req = test::TestRequest::post().uri("/user/register")
+        .insert_header(ContentType::form_url_encoded())
+        .set_form(UserSignUpForm{ username: "jwks", password: "World123!!", email: "jwks@gmail.com" })
+        .to_request();
+    let signup_resp: UserSignUpReturnForm = test::call_and_read_body_json(&app, req).await;
+    let user_id = signup_resp.id;
+
+    let rsa_login = login(&rsa_state).await;
+    let ed_login = login(&ed_state).await;
+    let hs_login = login(&hs_state).await;
+
+    // (access token, expected alg, expected kid)
+    let test_datas = [
+        (&rsa_login.access_token, Algorithm::RS256, Some("rsa-1")),
+        (&ed_login.access_token, Algorithm::EdDSA, Some("ed-1")),
+        (&hs_login.access_token, Algorithm::HS256, None),
+    ];
+    for (token, alg, kid) in test_datas {
+        let header = decode_header(token).unwrap();
+        assert_eq!(header.alg, alg);
+        assert_eq!(header.kid.as_deref(), kid);
+    }
+
+    // VERIFY //
+    let claims = serde_json::json!({
+        "user_id": user_id,
+        "jti": "0".repeat(32),
+        "sid": 0,
+        "iat": 0,
+        "exp": usize::MAX,
+    });
+    let forged = |kid: &str, alg: Algorithm, key: EncodingKey| {
+        let mut header = Header::new(alg);
+        header.kid = Some(kid.to_owned());
+        encode(&header, &claims, &key).unwrap()
+    };
+    let ed_key = std::fs::read("testdata/ed25519.pem").unwrap();
+    let rsa_public_key = std::fs::read("testdata/rs256.pub.pem").unwrap();
+    // (access token, expected status)
+    let test_datas = [
+        (rsa_login.access_token.clone(), 200),
+        // Every active key is accepted while keys are rotated.
+        (ed_login.access_token.clone(), 200),
+        // Token without kid is verified by HS256 secret.
+        (hs_login.access_token.clone(), 200),
+        // Retired key
+        (forged("ed-0", Algorithm::EdDSA, EncodingKey::from_ed_pem(&ed_key).unwrap()), 401),
+        // Signed by other key of kid
+        (forged("rsa-1", Algorithm::EdDSA, EncodingKey::from_ed_pem(&ed_key).unwrap()), 401),
+        // Public key used as HMAC secret
+        (forged("rsa-1", Algorithm::HS256, EncodingKey::from_secret(&rsa_public_key)), 401),
+    ];
+    for (token, status) in test_datas {
+        let req = test::TestRequest::get()
+            .uri(format!("/user/{}/todo", user_id).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .to_request();
+        assert_eq!(status_of(test::try_call_service(&app, req).await), status);
+    }
+
+    // Server without secret does not accept HS256 token.
+    let mut config = key_config(JwtAlgorithm::RS256, "rsa-1", "testdata/rs256.pem");
+    config.auth.jwt_secret = None;
+    let no_secret_state = app_state(config).await;
+    assert!(auth::verify_token(&no_secret_state.keys, &hs_login.access_token).await.is_err());
+    assert!(auth::verify_token(&no_secret_state.keys, &rsa_login.access_token).await.is_ok());
+
+    // Signing key must match its public key.
+    let config = key_config(JwtAlgorithm::RS256, "rsa-1", "testdata/ed25519.pem");
+    assert!(keys::JwtKeys::load(&config.auth).is_err());
+
+    // JWKS //
+    let req = test::TestRequest::get().uri("/.well-known/jwks.json").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    let jwks: JwkSet = test::read_body_json(resp).await;
+
+    assert_eq!(jwks.keys.len(), 2);
+    // Other services verify tokens with published keys only.
+    for (token, kid) in [(&rsa_login.access_token, "rsa-1"), (&ed_login.access_token, "ed-1")] {
+        let jwk = jwks.find(kid).unwrap();
+        let alg = jwk.common.algorithm.unwrap();
+        let claims = decode::<serde_json::Value>(token, &DecodingKey::from_jwk(jwk).unwrap(), &Validation::new(alg)).unwrap().claims;
+        assert_eq!(claims["user_id"], user_id);
+    }
+
+    // Delete test user
+    let req = test::TestRequest::delete()
+        .uri(format!("/user/{}", user_id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", rsa_login.access_token)))
+        .to_request();
+    test::call_service(&app, req).await;
+}
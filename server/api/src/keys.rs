@@ -0,0 +1,184 @@
+use std::{collections::HashMap, fs};
+use actix_web::{get, HttpResponse, http::header, web};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use jsonwebtoken::{
+    encode, decode, decode_header, Algorithm, Header, Validation, EncodingKey, DecodingKey,
+    errors::ErrorKind,
+    jwk::{
+        Jwk, JwkSet, CommonParameters, PublicKeyUse, AlgorithmParameters, RSAKeyParameters,
+        RSAKeyType, OctetKeyPairParameters, OctetKeyPairType, EllipticCurve
+    }
+};
+use serde::{de::DeserializeOwned, Serialize};
+use simple_asn1::{from_der, ASN1Block};
+use myconfig::{AuthConfig, JwtAlgorithm};
+use myerror::ServerError;
+use super::AppState;
+
+/// Signing key and every active verification key.
+/// Tokens carry kid of signing key in header, so old keys keep verifying while keys are rotated.
+pub struct JwtKeys {
+    header: Header,
+    encoding: EncodingKey,
+    /// HS256 key of tokens without kid.
+    secret: Option<DecodingKey>,
+    /// Public keys by kid.
+    public_keys: HashMap<String, (Algorithm, DecodingKey)>,
+    jwks: JwkSet,
+}
+
+fn load_error(path: &str, detail: String) -> ServerError {
+    ServerError::InternalServerError { msg: "JWT key load error", detail: format!("{}: {}", path, detail) }
+}
+
+fn algorithm_of(algorithm: JwtAlgorithm) -> Algorithm {
+    match algorithm {
+        JwtAlgorithm::HS256 => Algorithm::HS256,
+        JwtAlgorithm::RS256 => Algorithm::RS256,
+        JwtAlgorithm::EdDSA => Algorithm::EdDSA,
+    }
+}
+
+/// Subject public key of SubjectPublicKeyInfo, or the block itself for PKCS#1 RSA public key.
+fn public_key_der(pem: &pem::Pem) -> Result<Vec<u8>, String> {
+    match pem.tag.as_str() {
+        "RSA PUBLIC KEY" => Ok(pem.contents.clone()),
+        "PUBLIC KEY" => match from_der(&pem.contents).map_err(|e| e.to_string())?.as_slice() {
+            [ASN1Block::Sequence(_, info)] => match info.as_slice() {
+                [_, ASN1Block::BitString(_, _, key)] => Ok(key.clone()),
+                _ => Err("Invalid SubjectPublicKeyInfo".to_owned()),
+            },
+            _ => Err("Invalid SubjectPublicKeyInfo".to_owned()),
+        },
+        tag => Err(format!("Unsupported PEM {}", tag)),
+    }
+}
+
+/// JWK of PEM public key.
+fn public_jwk(kid: &str, algorithm: Algorithm, pem: &pem::Pem) -> Result<Jwk, String> {
+    let key = public_key_der(pem)?;
+    let algorithm_parameters = match algorithm {
+        // RSAPublicKey ::= SEQUENCE { modulus INTEGER, publicExponent INTEGER }
+        Algorithm::RS256 => match from_der(&key).map_err(|e| e.to_string())?.as_slice() {
+            [ASN1Block::Sequence(_, rsa)] => match rsa.as_slice() {
+                [ASN1Block::Integer(_, n), ASN1Block::Integer(_, e)] => AlgorithmParameters::RSA(RSAKeyParameters {
+                    key_type: RSAKeyType::RSA,
+                    n: URL_SAFE_NO_PAD.encode(n.to_bytes_be().1),
+                    e: URL_SAFE_NO_PAD.encode(e.to_bytes_be().1),
+                }),
+                _ => return Err("Invalid RSA public key".to_owned()),
+            },
+            _ => return Err("Invalid RSA public key".to_owned()),
+        },
+        Algorithm::EdDSA if key.len() == 32 => AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+            key_type: OctetKeyPairType::OctetKeyPair,
+            curve: EllipticCurve::Ed25519,
+            x: URL_SAFE_NO_PAD.encode(key),
+        }),
+        _ => return Err("Invalid Ed25519 public key".to_owned()),
+    };
+    Ok(Jwk {
+        common: CommonParameters {
+            public_key_use: Some(PublicKeyUse::Signature),
+            algorithm: Some(algorithm),
+            key_id: Some(kid.to_owned()),
+            ..Default::default()
+        },
+        algorithm: algorithm_parameters,
+    })
+}
+
+impl JwtKeys {
+    /// Read PEM keys of config.
+    /// Fail if signing key and its public key do not match.
+    pub fn load(config: &AuthConfig) -> Result<JwtKeys, ServerError> {
+        let secret = config.jwt_secret.as_ref().map(|s| DecodingKey::from_secret(s.as_bytes()));
+
+        let mut public_keys = HashMap::new();
+        let mut jwks = JwkSet { keys: Vec::new() };
+        for key in &config.jwt_public_keys {
+            let algorithm = algorithm_of(key.algorithm);
+            let raw = fs::read(&key.path).map_err(|e| load_error(&key.path, e.to_string()))?;
+            let decoding = match algorithm {
+                Algorithm::RS256 => DecodingKey::from_rsa_pem(&raw),
+                _ => DecodingKey::from_ed_pem(&raw),
+            }
+            .map_err(|e| load_error(&key.path, e.to_string()))?;
+            let pem = pem::parse(&raw).map_err(|e| load_error(&key.path, e.to_string()))?;
+            jwks.keys.push(public_jwk(&key.kid, algorithm, &pem).map_err(|e| load_error(&key.path, e))?);
+            public_keys.insert(key.kid.clone(), (algorithm, decoding));
+        }
+
+        let algorithm = algorithm_of(config.jwt_algorithm);
+        let (header, encoding) = match (&config.jwt_private_key, &config.jwt_key_id) {
+            (Some(path), Some(kid)) if algorithm != Algorithm::HS256 => {
+                let raw = fs::read(path).map_err(|e| load_error(path, e.to_string()))?;
+                let encoding = match algorithm {
+                    Algorithm::RS256 => EncodingKey::from_rsa_pem(&raw),
+                    _ => EncodingKey::from_ed_pem(&raw),
+                }
+                .map_err(|e| load_error(path, e.to_string()))?;
+                let mut header = Header::new(algorithm);
+                header.kid = Some(kid.clone());
+                (header, encoding)
+            },
+            // HS256 token has no kid.
+            _ => {
+                let secret = config.jwt_secret.as_ref()
+                    .ok_or(load_error("auth.jwt_secret", "Missing HS256 secret".to_owned()))?;
+                (Header::default(), EncodingKey::from_secret(secret.as_bytes()))
+            },
+        };
+
+        let keys = JwtKeys { header, encoding, secret, public_keys, jwks };
+        // Probe signing key against its verification key.
+        let probe = keys.encode(&serde_json::json!({ "exp": usize::MAX }))?;
+        keys.decode::<serde_json::Value>(&probe)
+            .map_err(|_| load_error(config.jwt_private_key.as_deref().unwrap_or("auth.jwt_secret"), "Signing key does not match public key".to_owned()))?;
+        Ok(keys)
+    }
+
+    pub fn encode<T: Serialize>(&self, claims: &T) -> Result<String, ServerError> {
+        encode(&self.header, claims, &self.encoding)
+            .map_err(|e|
+                ServerError::InternalServerError { msg: "JWT generate error", detail: e.to_string() }
+            )
+    }
+
+    /// Verify token with key of its kid, or HS256 secret if it has no kid.
+    /// Algorithm of header must be one of the key.
+    pub fn decode<T: DeserializeOwned>(&self, token: &str) -> Result<T, ServerError> {
+        let verify_error = |detail: String| ServerError::UnauthorizedError { code: "TOKEN_INVALID", msg: "JWT verfy error", detail };
+
+        let header = decode_header(token).map_err(|e| verify_error(e.to_string()))?;
+        let (algorithm, key) = match &header.kid {
+            Some(kid) => {
+                let (algorithm, key) = self.public_keys.get(kid)
+                    .ok_or_else(|| verify_error(format!("Unknown kid {}", kid)))?;
+                (*algorithm, key)
+            },
+            None => (Algorithm::HS256, self.secret.as_ref().ok_or_else(|| verify_error("Missing kid".to_owned()))?),
+        };
+
+        let mut validation = Validation::new(algorithm);
+        validation.leeway = 0;
+        decode::<T>(token, key, &validation)
+            .map(|data| data.claims)
+            .map_err(|e| match e.kind() {
+                ErrorKind::ExpiredSignature => ServerError::UnauthorizedError {
+                    code: "TOKEN_EXPIRED", msg: "JWT verfy error", detail: "Token is expired".to_owned()
+                },
+                _ => verify_error(e.to_string()),
+            })
+    }
+}
+
+/// Public keys to verify access tokens, for other services.
+#[get("/.well-known/jwks.json")]
+async fn fetch_jwks(
+    state: web::Data<AppState>,
+) -> HttpResponse {
+    HttpResponse::Ok()
+        .insert_header((header::CACHE_CONTROL, "public, max-age=300"))
+        .json(&state.keys.jwks)
+}
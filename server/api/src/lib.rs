@@ -1,3 +1,4 @@
+use std::sync::Arc;
 use env_logger::Env;
 use actix_web::{
     middleware::{Logger},
@@ -5,55 +6,216 @@ use actix_web::{
 };
 use actix_web_httpauth::middleware::HttpAuthentication;
 use sea_orm::DatabaseConnection;
-use entity;
+use migration::{Migrator, MigratorTrait};
+use myconfig::Config;
+use notifier::{Mailer, Notifiers};
+use revocation::RevocationStore;
+use keys::JwtKeys;
+use throttle::LoginThrottle;
+use entity::password::HashPool;
 
 mod user;
 mod todo;
+mod tag;
+mod project;
+mod series;
+mod reminder;
+mod notification;
+mod session;
+mod access_token;
+mod mfa;
+mod admin;
 mod auth;
+pub mod keys;
+pub mod revocation;
+pub mod scheduler;
+pub mod throttle;
 
+#[cfg(test)]
+pub mod config_test;
 #[cfg(test)]
 pub mod user_test;
 #[cfg(test)]
 pub mod todo_test;
+#[cfg(test)]
+pub mod tag_test;
+#[cfg(test)]
+pub mod project_test;
+#[cfg(test)]
+pub mod subtask_test;
+#[cfg(test)]
+pub mod series_test;
+#[cfg(test)]
+pub mod recurrence_test;
+#[cfg(test)]
+pub mod due_test;
+#[cfg(test)]
+pub mod reminder_test;
+#[cfg(test)]
+pub mod logout_test;
+#[cfg(test)]
+pub mod session_test;
+#[cfg(test)]
+pub mod access_token_test;
+#[cfg(test)]
+pub mod jwks_test;
+#[cfg(test)]
+pub mod password_reset_test;
+#[cfg(test)]
+pub mod email_verification_test;
+#[cfg(test)]
+pub mod mfa_test;
+#[cfg(test)]
+pub mod throttle_test;
+#[cfg(test)]
+pub mod password_hash_test;
+#[cfg(test)]
+pub mod admin_test;
+#[cfg(test)]
+pub mod error_test;
 
 
 #[derive(Clone)]
 pub struct AppState {
-    pub conn: DatabaseConnection
+    pub conn: DatabaseConnection,
+    pub config: Config,
+    /// Delivery channels of reminders.
+    pub notifiers: Arc<Notifiers>,
+    /// Revoked access tokens cached in memory.
+    pub revocations: Arc<RevocationStore>,
+    /// Keys to sign and verify access tokens.
+    pub keys: Arc<JwtKeys>,
+    /// Sender of account mails.
+    pub mailer: Arc<dyn Mailer>,
+    /// Failed logins counted in memory.
+    pub throttle: Arc<LoginThrottle>,
+    /// Threads of password hashing.
+    pub hasher: Arc<HashPool>,
 }
 
 #[actix_web::main]
-async fn server_run() {
+async fn server_run(config: Config) -> std::io::Result<()> {
     env_logger::init_from_env(Env::default().default_filter_or("info"));
+    myerror::expose_internal_detail(config.server.expose_error_detail);
     let logger_format = r#"%a %t "%r" %s %b "%{Referer}i" "%{User-Agent}i" %Dms"#;
-    let conn = entity::database_connect().await.unwrap();
-    let state = AppState {conn};
+    let conn = entity::database_connect(&config.database).await
+        .map_err(|e| std::io::Error::other(format!("Database connect error: {}", e)))?;
+    if config.database.auto_migrate {
+        Migrator::up(&conn, None).await
+            .map_err(|e| std::io::Error::other(format!("Database migration error: {}", e)))?;
+    }
+    let notifiers = Notifiers::new(&config, conn.clone())
+        .map_err(|e| std::io::Error::other(format!("Notifier error: {}", e)))?;
+    let revocations = RevocationStore::load(&conn, &config.auth).await
+        .map_err(|e| std::io::Error::other(format!("Revocation load error: {:?}", e)))?;
+    let keys = JwtKeys::load(&config.auth)
+        .map_err(|e| std::io::Error::other(format!("JWT key error: {:?}", e)))?;
+    let mailer = notifier::new_mailer(&config)
+        .map_err(|e| std::io::Error::other(format!("Mailer error: {}", e)))?;
+    let throttle = LoginThrottle::new(&config.login);
+    let hasher = HashPool::new(&config.password);
+    let bind = (config.server.host.clone(), config.server.port);
+    let state = AppState {conn, config, notifiers: Arc::new(notifiers), revocations: Arc::new(revocations), keys: Arc::new(keys), mailer: Arc::from(mailer), throttle: Arc::new(throttle), hasher: Arc::new(hasher)};
+    revocation::spawn_sync(state.revocations.clone(), state.conn.clone(), state.config.auth.revocation_sync_interval);
+    if state.config.reminder.enabled {
+        scheduler::spawn(state.clone());
+    }
     HttpServer::new(move ||
         App::new()
             .wrap(Logger::new(logger_format))
             .app_data(web::Data::new(state.clone()))
             .service(user::signup_user)
             .service(user::login_user)
+            .service(user::login_mfa_user)
+            .service(user::refresh_user)
+            .service(user::forgot_password)
+            .service(user::reset_password)
+            .service(user::verify_email)
+            .service(keys::fetch_jwks)
+            .service(
+                web::scope("/admin")
+                    .wrap(HttpAuthentication::bearer(auth::admin_validator))
+                    .service(admin::fetch_users)
+                    .service(admin::fetch_user)
+                    .service(admin::disable_user)
+                    .service(admin::enable_user)
+                    .service(admin::force_password_reset)
+                    .service(admin::fetch_user_todos)
+                    .service(admin::fetch_audit_logs)
+            )
             .service(
                 web::scope("/user/{user_id}")
                     .wrap(HttpAuthentication::bearer(auth::jwt_validator))
                     .service(user::modify_user)
                     .service(user::withdrawal_user)
+                    .service(user::logout_user)
+                    .service(user::logout_all_user)
+                    .service(user::resend_verification_email)
                     .service(
                         web::scope("/todo")
                             .service(todo::fetch_todos)
+                            .service(todo::fetch_todo)
                             .service(todo::create_todo)
                             .service(todo::modify_todo)
+                            .service(todo::move_todo)
                             .service(todo::remove_todo)
+                            .service(reminder::fetch_reminders)
+                            .service(reminder::create_reminder)
+                            .service(reminder::remove_reminder)
+                    )
+                    .service(
+                        web::scope("/tag")
+                            .service(tag::fetch_tags)
+                            .service(tag::create_tag)
+                            .service(tag::modify_tag)
+                            .service(tag::remove_tag)
+                    )
+                    .service(
+                        web::scope("/project")
+                            .service(project::fetch_projects)
+                            .service(project::create_project)
+                            .service(project::modify_project)
+                            .service(project::remove_project)
+                            .service(project::fetch_project_todos)
+                            .service(project::create_project_todo)
+                    )
+                    .service(
+                        web::scope("/series")
+                            .service(series::fetch_series)
+                            .service(series::modify_series)
+                            .service(series::remove_series)
+                    )
+                    .service(
+                        web::scope("/notification")
+                            .service(notification::fetch_notifications)
+                            .service(notification::read_notification)
+                            .service(notification::remove_notification)
+                    )
+                    .service(
+                        web::scope("/session")
+                            .service(session::fetch_sessions)
+                            .service(session::remove_session)
+                    )
+                    .service(
+                        web::scope("/mfa")
+                            .service(mfa::enroll_totp)
+                            .service(mfa::confirm_totp)
+                            .service(mfa::disable_totp)
+                    )
+                    .service(
+                        web::scope("/token")
+                            .service(access_token::fetch_access_tokens)
+                            .service(access_token::create_access_token)
+                            .service(access_token::remove_access_token)
                     )
             )
 
     )
-    .bind(("0.0.0.0", 8080)).unwrap()
+    .bind(bind)?
     .run()
-    .await.unwrap();
+    .await
 }
 
-pub fn main() {
-    server_run();
+pub fn main(config: Config) -> std::io::Result<()> {
+    server_run(config)
 }
\ No newline at end of file
@@ -0,0 +1,202 @@
+#![allow(dead_code)]
+
+use actix_web::{
+    App, web, test, dev::ServiceResponse, http::{StatusCode, header::ContentType},
+};
+use actix_web_httpauth::middleware::HttpAuthentication;
+use serde::Serialize;
+use super::{user, auth, todo};
+use super::user_test::*;
+
+#[derive(Serialize)]
+struct LogoutForm {
+    refresh_token: String,
+}
+
+// Status of response, rejected token is error of middleware.
+pub fn status_of(resp: Result<ServiceResponse, actix_web::Error>) -> StatusCode {
+    resp.map(|r| r.status())
+        .unwrap_or_else(|e| e.as_response_error().status_code())
+}
+
+#[actix_web::test]
+async fn test_logout() {
+    let state = db_connect().await;
+    // Another server, it sees revocations after sync.
+    let other_state = db_connect().await;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state.clone()))
+            .service(user::signup_user)
+            .service(user::login_user)
+            .service(user::refresh_user)
+            .service(
+                web::scope("/user/{user_id}")
+                    .wrap(HttpAuthentication::bearer(auth::jwt_validator))
+                    .service(user::withdrawal_user)
+                    .service(user::logout_user)
+                    .service(user::logout_all_user)
+                    .service(
+                        web::scope("/todo")
+                            .service(todo::fetch_todos)
+                    )
+            )
+    ).await;
+    let other_app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(other_state.clone()))
+            .service(
+                web::scope("/user/{user_id}")
+                    .wrap(HttpAuthentication::bearer(auth::jwt_validator))
+                    .service(
+                        web::scope("/todo")
+                            .service(todo::fetch_todos)
+                    )
+            )
+    ).await;
+
+    let req = test::TestRequest::post().uri("/user/register")
+        .insert_header(ContentType::form_url_encoded())
+        .set_form(UserSignUpForm{ username: "logout", password: "World123!!", email: "logout@gmail.com" })
+        .to_request();
+    let signup_resp: UserSignUpReturnForm = test::call_and_read_body_json(&app, req).await;
+    let user_id = signup_resp.id;
+
+    // Two logins
+    let mut login_resps = Vec::new();
+    for _ in 0..2 {
+        let req = test::TestRequest::post().uri("/user/login")
+            .insert_header(ContentType::form_url_encoded())
+            .set_form(UserLoginForm{ username: "logout", password: "World123!!" })
+            .to_request();
+        let login_resp: UserLoginReturnForm = test::call_and_read_body_json(&app, req).await;
+        login_resps.push(login_resp);
+    }
+    let second = login_resps.pop().unwrap();
+    let first = login_resps.pop().unwrap();
+
+    // LOGOUT //
+    // Refresh token of other user's login is not accepted.
+    let req = test::TestRequest::post()
+        .uri(format!("/user/{}/logout", user_id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", first.access_token)))
+        .insert_header(ContentType::form_url_encoded())
+        .set_form(LogoutForm{ refresh_token: "not_a_refresh_token".to_owned() })
+        .to_request();
+    assert_eq!(status_of(test::try_call_service(&app, req).await), 401);
+
+    let req = test::TestRequest::post()
+        .uri(format!("/user/{}/logout", user_id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", first.access_token)))
+        .insert_header(ContentType::form_url_encoded())
+        .set_form(LogoutForm{ refresh_token: first.refresh_token.clone() })
+        .to_request();
+    assert_eq!(status_of(test::try_call_service(&app, req).await), 204);
+
+    // (access token, expected status)
+    let test_datas = [
+        (&first.access_token, 401),
+        (&second.access_token, 200),
+    ];
+    for (token, status) in test_datas {
+        let req = test::TestRequest::get()
+            .uri(format!("/user/{}/todo", user_id).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .to_request();
+        assert_eq!(status_of(test::try_call_service(&app, req).await), status);
+    }
+
+    // Logout without refresh token
+    let req = test::TestRequest::post()
+        .uri(format!("/user/{}/logout", user_id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", first.access_token)))
+        .to_request();
+    assert_eq!(status_of(test::try_call_service(&app, req).await), 401);
+
+    // (refresh token, expected status)
+    let test_datas = [
+        (&first.refresh_token, 401),
+        (&second.refresh_token, 200),
+    ];
+    let mut refresh_resps = Vec::new();
+    for (token, status) in test_datas {
+        let req = test::TestRequest::post().uri("/user/refresh")
+            .insert_header(ContentType::form_url_encoded())
+            .set_form(RefreshForm { refresh_token: token.clone() })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), status);
+        if resp.status() == 200 {
+            let refresh_resp: RefreshReturnForm = test::read_body_json(resp).await;
+            refresh_resps.push(refresh_resp);
+        }
+    }
+    let refreshed = refresh_resps.pop().unwrap();
+
+    // REVOCATION CACHE //
+    // Other server does not hit database on request, it knows revocation after sync.
+    let req = test::TestRequest::get()
+        .uri(format!("/user/{}/todo", user_id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", first.access_token)))
+        .to_request();
+    assert_eq!(status_of(test::try_call_service(&other_app, req).await), 200);
+
+    other_state.revocations.sync(&other_state.conn).await.unwrap();
+
+    let req = test::TestRequest::get()
+        .uri(format!("/user/{}/todo", user_id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", first.access_token)))
+        .to_request();
+    assert_eq!(status_of(test::try_call_service(&other_app, req).await), 401);
+
+    // LOGOUT ALL SESSIONS //
+    let req = test::TestRequest::post()
+        .uri(format!("/user/{}/logout/all", user_id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", refreshed.access_token)))
+        .to_request();
+    assert_eq!(status_of(test::try_call_service(&app, req).await), 204);
+    other_state.revocations.sync(&other_state.conn).await.unwrap();
+
+    for token in [&second.access_token, &refreshed.access_token] {
+        for app in [&app, &other_app] {
+            let req = test::TestRequest::get()
+                .uri(format!("/user/{}/todo", user_id).as_str())
+                .insert_header(("Authorization", format!("Bearer {}", token)))
+                .to_request();
+            assert_eq!(status_of(test::try_call_service(app, req).await), 401);
+        }
+    }
+
+    let req = test::TestRequest::post().uri("/user/refresh")
+        .insert_header(ContentType::form_url_encoded())
+        .set_form(RefreshForm { refresh_token: refreshed.refresh_token.clone() })
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), 401);
+
+    // Login right after logout is not revoked, on both servers.
+    let req = test::TestRequest::post().uri("/user/login")
+        .insert_header(ContentType::form_url_encoded())
+        .set_form(UserLoginForm{ username: "logout", password: "World123!!" })
+        .to_request();
+    let login_resp: UserLoginReturnForm = test::call_and_read_body_json(&app, req).await;
+
+    other_state.revocations.sync(&other_state.conn).await.unwrap();
+    for app in [&app, &other_app] {
+        let req = test::TestRequest::get()
+            .uri(format!("/user/{}/todo", user_id).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+            .to_request();
+        assert_eq!(status_of(test::try_call_service(app, req).await), 200);
+    }
+
+    // Delete test user
+    let req = test::TestRequest::delete()
+        .uri(format!("/user/{}", user_id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .to_request();
+    test::call_service(&app, req).await;
+}
@@ -0,0 +1,54 @@
+use actix_web::{
+    post, delete, HttpResponse, web
+};
+use serde::Deserialize;
+use serde_json::json;
+use entity::{
+    mutation::*,
+    totp::Totp,
+};
+use super::AppState;
+use myerror::ServerError;
+
+#[derive(Deserialize)]
+struct CodeForm {
+    /// TOTP code, or recovery code when disabling.
+    code: String
+}
+
+#[post("/totp")]
+async fn enroll_totp(
+    state: web::Data<AppState>,
+    user_id: web::Path<i32>,
+) -> Result<HttpResponse, ServerError> {
+    let user = Mutation::enroll_totp(&state.conn, user_id.into_inner()).await?;
+    let secret = user.totp_secret.unwrap_or_default();
+    // Authenticator apps read the URI from QR code.
+    let uri = Totp::uri(&secret, &state.config.auth.totp_issuer, &user.username);
+    Ok(HttpResponse::Ok().json(json!({
+        "secret" : secret,
+        "otpauth_uri" : uri
+    })))
+}
+
+#[post("/totp/confirm")]
+async fn confirm_totp(
+    state: web::Data<AppState>,
+    user_id: web::Path<i32>,
+    code_form: web::Form<CodeForm>,
+) -> Result<HttpResponse, ServerError> {
+    let codes = Mutation::confirm_totp(&state.conn, user_id.into_inner(), &code_form.code).await?;
+    Ok(HttpResponse::Ok().json(json!({
+        "recovery_codes" : codes
+    })))
+}
+
+#[delete("/totp")]
+async fn disable_totp(
+    state: web::Data<AppState>,
+    user_id: web::Path<i32>,
+    code_form: web::Form<CodeForm>,
+) -> Result<HttpResponse, ServerError> {
+    Mutation::disable_totp(&state.conn, user_id.into_inner(), &code_form.code).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
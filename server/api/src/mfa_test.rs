@@ -0,0 +1,216 @@
+#![allow(dead_code)]
+
+use actix_web::{
+    App, web, test, http::header::ContentType,
+};
+use actix_web_httpauth::middleware::HttpAuthentication;
+use chrono::{Duration, TimeZone, Utc};
+use entity::totp::Totp;
+use serde::{Deserialize, Serialize};
+use super::{user, auth, mfa};
+use super::user_test::*;
+use super::logout_test::status_of;
+
+#[derive(Serialize)]
+struct CodeForm {
+    code: String,
+}
+
+#[derive(Serialize)]
+struct MfaLoginForm {
+    mfa_token: String,
+    code: String,
+}
+
+#[derive(Deserialize)]
+struct EnrollReturnForm {
+    secret: String,
+    otpauth_uri: String,
+}
+
+#[derive(Deserialize)]
+struct ConfirmReturnForm {
+    recovery_codes: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct MfaRequiredReturnForm {
+    mfa_required: bool,
+    mfa_token: String,
+}
+
+#[actix_web::test]
+async fn test_mfa() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(db_connect().await.clone()))
+            .service(user::signup_user)
+            .service(user::login_user)
+            .service(user::login_mfa_user)
+            .service(
+                web::scope("/user/{user_id}")
+                    .wrap(HttpAuthentication::bearer(auth::jwt_validator))
+                    .service(user::withdrawal_user)
+                    .service(
+                        web::scope("/mfa")
+                            .service(mfa::enroll_totp)
+                            .service(mfa::confirm_totp)
+                            .service(mfa::disable_totp)
+                    )
+            )
+    ).await;
+
+    let req = test::TestRequest::post().uri("/user/register")
+        .insert_header(ContentType::form_url_encoded())
+        .set_form(UserSignUpForm{ username: "mfa", password: "World123!!", email: "mfa@gmail.com" })
+        .to_request();
+    let signup_resp: UserSignUpReturnForm = test::call_and_read_body_json(&app, req).await;
+    let user_id = signup_resp.id;
+
+    let req = test::TestRequest::post().uri("/user/login")
+        .insert_header(ContentType::form_url_encoded())
+        .set_form(UserLoginForm{ username: "mfa", password: "World123!!" })
+        .to_request();
+    let login_resp: UserLoginReturnForm = test::call_and_read_body_json(&app, req).await;
+
+    // ENROLL //
+    let req = test::TestRequest::post()
+        .uri(format!("/user/{}/mfa/totp", user_id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .to_request();
+    let enroll_resp: EnrollReturnForm = test::call_and_read_body_json(&app, req).await;
+
+    assert!(enroll_resp.otpauth_uri.starts_with(&format!("otpauth://totp/Todo:mfa?secret={}&", enroll_resp.secret)));
+    let totp = Totp::new(&enroll_resp.secret).unwrap();
+    let confirm_code = totp.code(Totp::step(Utc::now()));
+
+    // (code, expected status)
+    let test_datas = [
+        ("abcdef".to_owned(), 400),
+        (confirm_code.clone(), 200),
+        // Already enabled
+        (confirm_code.clone(), 409),
+    ];
+    let mut recovery_codes = Vec::new();
+    for (code, status) in test_datas {
+        let req = test::TestRequest::post()
+            .uri(format!("/user/{}/mfa/totp/confirm", user_id).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+            .insert_header(ContentType::form_url_encoded())
+            .set_form(CodeForm{ code })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), status);
+        if resp.status() == 200 {
+            let confirm_resp: ConfirmReturnForm = test::read_body_json(resp).await;
+            recovery_codes = confirm_resp.recovery_codes;
+        }
+    }
+    assert_eq!(recovery_codes.len(), 10);
+
+    // TWO-STEP LOGIN //
+    let req = test::TestRequest::post().uri("/user/login")
+        .insert_header(ContentType::form_url_encoded())
+        .set_form(UserLoginForm{ username: "mfa", password: "World123!!" })
+        .to_request();
+    let mfa_resp: MfaRequiredReturnForm = test::call_and_read_body_json(&app, req).await;
+    assert!(mfa_resp.mfa_required);
+
+    // MFA token is not access token.
+    let req = test::TestRequest::post()
+        .uri(format!("/user/{}/mfa/totp", user_id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", mfa_resp.mfa_token)))
+        .to_request();
+    assert_eq!(status_of(test::try_call_service(&app, req).await), 401);
+
+    // Next step is within allowed clock drift, and after the step used by confirm.
+    let next_code = totp.code(Totp::step(Utc::now()) + 1);
+    // (mfa token, code, expected status)
+    let test_datas = [
+        // Access token is not MFA token.
+        (login_resp.access_token.clone(), next_code.clone(), 401),
+        (mfa_resp.mfa_token.clone(), "abcdef".to_owned(), 401),
+        // Code of used step
+        (mfa_resp.mfa_token.clone(), confirm_code.clone(), 401),
+        (mfa_resp.mfa_token.clone(), next_code.clone(), 200),
+        (mfa_resp.mfa_token.clone(), next_code.clone(), 401),
+        // Recovery code ignores dashes and case.
+        (mfa_resp.mfa_token.clone(), recovery_codes[0].replace('-', "").to_uppercase(), 200),
+        (mfa_resp.mfa_token.clone(), recovery_codes[0].clone(), 401),
+    ];
+    let mut access_token = String::new();
+    for (mfa_token, code, status) in test_datas {
+        let req = test::TestRequest::post().uri("/user/login/mfa")
+            .insert_header(ContentType::form_url_encoded())
+            .set_form(MfaLoginForm{ mfa_token, code })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), status);
+        if resp.status() == 200 {
+            let login_resp: UserLoginReturnForm = test::read_body_json(resp).await;
+            assert_eq!(login_resp.user.id, user_id);
+            access_token = login_resp.access_token;
+        }
+    }
+
+    // DISABLE //
+    // (code, expected status)
+    let test_datas = [
+        (recovery_codes[0].clone(), 400),
+        (recovery_codes[1].clone(), 204),
+        // Not enabled
+        (recovery_codes[2].clone(), 400),
+    ];
+    for (code, status) in test_datas {
+        let req = test::TestRequest::delete()
+            .uri(format!("/user/{}/mfa/totp", user_id).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", access_token)))
+            .insert_header(ContentType::form_url_encoded())
+            .set_form(CodeForm{ code })
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), status);
+    }
+
+    // One step login again
+    let req = test::TestRequest::post().uri("/user/login")
+        .insert_header(ContentType::form_url_encoded())
+        .set_form(UserLoginForm{ username: "mfa", password: "World123!!" })
+        .to_request();
+    let login_resp: UserLoginReturnForm = test::call_and_read_body_json(&app, req).await;
+
+    // Delete test user
+    let req = test::TestRequest::delete()
+        .uri(format!("/user/{}", user_id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .to_request();
+    test::call_service(&app, req).await;
+}
+
+#[actix_web::test]
+async fn test_totp_rfc6238() {
+    // Test vectors of RFC 6238 Appendix B for SHA1, last 6 digits of 8 digits code.
+    // Secret is base32 of "12345678901234567890".
+    let totp = Totp::new("GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ").unwrap();
+    // (unix time, expected code)
+    let test_datas = [
+        (59, "287082"),
+        (1111111109, "081804"),
+        (1111111111, "050471"),
+        (1234567890, "005924"),
+        (2000000000, "279037"),
+        (20000000000, "353130"),
+    ];
+    for (time, code) in test_datas {
+        let now = Utc.timestamp_opt(time, 0).unwrap();
+        assert_eq!(totp.code(Totp::step(now)), code, "{}", time);
+        assert_eq!(totp.verify(code, now), Some(Totp::step(now)), "{}", time);
+    }
+
+    // One step of clock drift is accepted.
+    let now = Utc.timestamp_opt(1111111109, 0).unwrap();
+    assert!(totp.verify("081804", now + Duration::seconds(30)).is_some());
+    assert!(totp.verify("081804", now - Duration::seconds(30)).is_some());
+    assert!(totp.verify("081804", now + Duration::seconds(90)).is_none());
+}
@@ -0,0 +1,36 @@
+use actix_web::{
+    get, put, delete, HttpResponse, web
+};
+use super::AppState;
+use entity::mutation::*;
+use myerror::ServerError;
+
+#[get("")]
+async fn fetch_notifications(
+    state: web::Data<AppState>,
+    user_id: web::Path<i32>,
+    query: web::Query<NotificationQuery>,
+) -> Result<HttpResponse, ServerError> {
+    let models = Mutation::get_notifications(&state.conn, user_id.into_inner(), query.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(models))
+}
+
+#[put("/{notification_id}/read")]
+async fn read_notification(
+    state: web::Data<AppState>,
+    path_para: web::Path<(i32, i32)>,
+) -> Result<HttpResponse, ServerError> {
+    let (user_id, notification_id) = path_para.into_inner();
+    let model = Mutation::read_notification(&state.conn, user_id, notification_id).await?;
+    Ok(HttpResponse::Ok().json(model))
+}
+
+#[delete("/{notification_id}")]
+async fn remove_notification(
+    state: web::Data<AppState>,
+    path_para: web::Path<(i32, i32)>,
+) -> Result<HttpResponse, ServerError> {
+    let (user_id, notification_id) = path_para.into_inner();
+    Mutation::delete_notification(&state.conn, user_id, notification_id).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
@@ -0,0 +1,181 @@
+use std::time::{Duration, Instant};
+use actix_web::{
+    App, web, test, rt, http::header::ContentType,
+};
+use futures_util::future::{join, join_all};
+use actix_web_httpauth::middleware::HttpAuthentication;
+use sea_orm::{DbConn, EntityTrait};
+use entity::entities::users;
+use myconfig::PasswordAlgorithm;
+use super::{user, auth, keys};
+use super::user_test::*;
+
+async fn stored_hash(db: &DbConn, user_id: i32) -> String {
+    users::Entity::find_by_id(user_id).one(db).await.unwrap().unwrap().password
+}
+
+#[actix_web::test]
+async fn test_password_rehash() {
+    // Users of old bcrypt deployment.
+    let mut config = test_config();
+    config.password.algorithm = PasswordAlgorithm::Bcrypt;
+    config.password.bcrypt_cost = 4;
+    let bcrypt_state = app_state(config).await;
+    let bcrypt_app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(bcrypt_state.clone()))
+            .service(user::signup_user)
+            .service(user::login_user)
+    ).await;
+
+    let req = test::TestRequest::post().uri("/user/register")
+        .insert_header(ContentType::form_url_encoded())
+        .set_form(UserSignUpForm{ username: "rehash", password: "World123!!", email: "rehash@gmail.com" })
+        .to_request();
+    let signup_resp: UserSignUpReturnForm = test::call_and_read_body_json(&bcrypt_app, req).await;
+    let user_id = signup_resp.id;
+    assert!(stored_hash(&bcrypt_state.conn, user_id).await.starts_with("$2b$04$"));
+
+    // Same cost is not hashed again.
+    let req = test::TestRequest::post().uri("/user/login")
+        .insert_header(ContentType::form_url_encoded())
+        .set_form(UserLoginForm{ username: "rehash", password: "World123!!" })
+        .to_request();
+    let bcrypt_hash = stored_hash(&bcrypt_state.conn, user_id).await;
+    assert_eq!(test::call_service(&bcrypt_app, req).await.status(), 200);
+    assert_eq!(stored_hash(&bcrypt_state.conn, user_id).await, bcrypt_hash);
+
+    // Default Argon2id deployment.
+    let state = db_connect().await;
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state.clone()))
+            .service(user::login_user)
+            .service(
+                web::scope("/user/{user_id}")
+                    .wrap(HttpAuthentication::bearer(auth::jwt_validator))
+                    .service(user::withdrawal_user)
+            )
+    ).await;
+
+    // Wrong password does not change hash.
+    let req = test::TestRequest::post().uri("/user/login")
+        .insert_header(ContentType::form_url_encoded())
+        .set_form(UserLoginForm{ username: "rehash", password: "Wrong123!!" })
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 401);
+    assert_eq!(stored_hash(&state.conn, user_id).await, bcrypt_hash);
+
+    // Bcrypt hash is still verified, and upgraded to Argon2id.
+    let req = test::TestRequest::post().uri("/user/login")
+        .insert_header(ContentType::form_url_encoded())
+        .set_form(UserLoginForm{ username: "rehash", password: "World123!!" })
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 200);
+    let argon2_hash = stored_hash(&state.conn, user_id).await;
+    assert!(argon2_hash.starts_with("$argon2id$v=19$m=19456,t=2,p=1$"));
+
+    // Upgraded hash is used as is.
+    let req = test::TestRequest::post().uri("/user/login")
+        .insert_header(ContentType::form_url_encoded())
+        .set_form(UserLoginForm{ username: "rehash", password: "World123!!" })
+        .to_request();
+    let login_resp: UserLoginReturnForm = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(stored_hash(&state.conn, user_id).await, argon2_hash);
+
+    // Configured algorithm is followed in both directions.
+    let req = test::TestRequest::post().uri("/user/login")
+        .insert_header(ContentType::form_url_encoded())
+        .set_form(UserLoginForm{ username: "rehash", password: "World123!!" })
+        .to_request();
+    assert_eq!(test::call_service(&bcrypt_app, req).await.status(), 200);
+    assert!(stored_hash(&state.conn, user_id).await.starts_with("$2b$04$"));
+
+    // Outdated cost of same algorithm is upgraded too.
+    let mut config = test_config();
+    config.password.algorithm = PasswordAlgorithm::Bcrypt;
+    config.password.bcrypt_cost = 5;
+    let cost_app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(app_state(config).await))
+            .service(user::login_user)
+    ).await;
+    let req = test::TestRequest::post().uri("/user/login")
+        .insert_header(ContentType::form_url_encoded())
+        .set_form(UserLoginForm{ username: "rehash", password: "World123!!" })
+        .to_request();
+    assert_eq!(test::call_service(&cost_app, req).await.status(), 200);
+    assert!(stored_hash(&state.conn, user_id).await.starts_with("$2b$05$"));
+
+    // Delete test user
+    let req = test::TestRequest::delete()
+        .uri(format!("/user/{}", user_id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .to_request();
+    test::call_service(&app, req).await;
+}
+
+#[actix_web::test]
+async fn test_password_hash_pool() {
+    // One slow hashing thread with one waiting job.
+    let mut config = test_config();
+    config.password.argon2_iterations = 4;
+    config.password.hash_workers = 1;
+    config.password.hash_queue_size = 1;
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(app_state(config).await))
+            .service(user::signup_user)
+            .service(user::login_user)
+            .service(keys::fetch_jwks)
+            .service(
+                web::scope("/user/{user_id}")
+                    .wrap(HttpAuthentication::bearer(auth::jwt_validator))
+                    .service(user::withdrawal_user)
+            )
+    ).await;
+
+    let req = test::TestRequest::post().uri("/user/register")
+        .insert_header(ContentType::form_url_encoded())
+        .set_form(UserSignUpForm{ username: "hashpool", password: "World123!!", email: "hashpool@gmail.com" })
+        .to_request();
+    let signup_resp: UserSignUpReturnForm = test::call_and_read_body_json(&app, req).await;
+
+    // Concurrent logins, over the queue are rejected instead of waiting.
+    let logins = join_all((0..4).map(|_| async {
+        let req = test::TestRequest::post().uri("/user/login")
+            .insert_header(ContentType::form_url_encoded())
+            .set_form(UserLoginForm{ username: "hashpool", password: "World123!!" })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        (resp.status().as_u16(), Instant::now())
+    }));
+    // Hashing does not block other requests on same worker.
+    let jwks = async {
+        // Requested while hashing is running.
+        rt::time::sleep(Duration::from_millis(100)).await;
+        let req = test::TestRequest::get().uri("/.well-known/jwks.json").to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), 200);
+        Instant::now()
+    };
+    let (logins, jwks_done) = join(logins, jwks).await;
+
+    let mut statuses: Vec<_> = logins.iter().map(|(status, _)| *status).collect();
+    statuses.sort();
+    assert_eq!(statuses, [200, 200, 503, 503]);
+    assert!(logins.iter().filter(|(status, _)| *status == 200).all(|(_, done)| jwks_done < *done));
+
+    // Pool accepts jobs again after queue is drained.
+    let req = test::TestRequest::post().uri("/user/login")
+        .insert_header(ContentType::form_url_encoded())
+        .set_form(UserLoginForm{ username: "hashpool", password: "World123!!" })
+        .to_request();
+    let login_resp: UserLoginReturnForm = test::call_and_read_body_json(&app, req).await;
+
+    // Delete test user
+    let req = test::TestRequest::delete()
+        .uri(format!("/user/{}", signup_resp.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .to_request();
+    test::call_service(&app, req).await;
+}
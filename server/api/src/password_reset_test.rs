@@ -0,0 +1,187 @@
+#![allow(dead_code)]
+
+use std::{fs, path::Path, time::Duration};
+use actix_web::{
+    App, web, test, rt, http::header::ContentType,
+};
+use actix_web_httpauth::middleware::HttpAuthentication;
+use sea_orm::{EntityTrait, QueryFilter, ColumnTrait, sea_query::Expr};
+use serde::Serialize;
+use entity::entities::password_reset_tokens;
+use super::{user, auth, todo};
+use super::user_test::*;
+use super::logout_test::status_of;
+
+const RESET_URL: &str = "https://todo.example.com/reset-password?token=";
+
+#[derive(Serialize)]
+struct ForgotPasswordForm {
+    email: &'static str,
+}
+
+#[derive(Serialize)]
+struct ResetPasswordForm {
+    token: String,
+    password: &'static str,
+}
+
+// Tokens in links of saved mails, oldest first.
+pub fn mail_tokens(dir: &Path, url: &str) -> Vec<String> {
+    let mut paths: Vec<_> = match fs::read_dir(dir) {
+        Ok(entries) => entries.map(|e| e.unwrap().path()).collect(),
+        Err(_) => return Vec::new(),
+    };
+    paths.sort();
+    paths.iter()
+        .filter_map(|path| {
+            let mail = fs::read_to_string(path).unwrap();
+            mail.lines()
+                .find_map(|line| line.strip_prefix(url))
+                .map(|token| token.to_owned())
+        })
+        .collect()
+}
+
+#[actix_web::test]
+async fn test_password_reset() {
+    let mail_dir = std::env::temp_dir().join(format!("todo-mail-{}", rand::random::<u32>()));
+    let mut config = test_config();
+    config.mail.dir = Some(mail_dir.to_string_lossy().into_owned());
+    config.auth.password_reset_url = Some(format!("{}{{token}}", RESET_URL));
+    let state = app_state(config).await;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state.clone()))
+            .service(user::signup_user)
+            .service(user::login_user)
+            .service(user::refresh_user)
+            .service(user::forgot_password)
+            .service(user::reset_password)
+            .service(
+                web::scope("/user/{user_id}")
+                    .wrap(HttpAuthentication::bearer(auth::jwt_validator))
+                    .service(user::withdrawal_user)
+                    .service(
+                        web::scope("/todo")
+                            .service(todo::fetch_todos)
+                    )
+            )
+    ).await;
+
+    let req = test::TestRequest::post().uri("/user/register")
+        .insert_header(ContentType::form_url_encoded())
+        .set_form(UserSignUpForm{ username: "reset", password: "World123!!", email: "reset@gmail.com" })
+        .to_request();
+    let signup_resp: UserSignUpReturnForm = test::call_and_read_body_json(&app, req).await;
+    let user_id = signup_resp.id;
+
+    let req = test::TestRequest::post().uri("/user/login")
+        .insert_header(ContentType::form_url_encoded())
+        .set_form(UserLoginForm{ username: "reset", password: "World123!!" })
+        .to_request();
+    let login_resp: UserLoginReturnForm = test::call_and_read_body_json(&app, req).await;
+
+    // FORGOT PASSWORD //
+    // Unknown email has same response, but no mail.
+    let req = test::TestRequest::post().uri("/user/password/forgot")
+        .insert_header(ContentType::form_url_encoded())
+        .set_form(ForgotPasswordForm{ email: "nobody@gmail.com" })
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 202);
+    assert!(mail_tokens(&mail_dir, RESET_URL).is_empty());
+
+    for _ in 0..2 {
+        let req = test::TestRequest::post().uri("/user/password/forgot")
+            .insert_header(ContentType::form_url_encoded())
+            .set_form(ForgotPasswordForm{ email: "reset@gmail.com" })
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), 202);
+        // Mail file name is in microseconds.
+        rt::time::sleep(Duration::from_millis(5)).await;
+    }
+    let tokens = mail_tokens(&mail_dir, RESET_URL);
+    assert_eq!(tokens.len(), 2);
+
+    // RESET PASSWORD //
+    // (token, password, expected status)
+    let test_datas = [
+        ("not_a_reset_token".to_owned(), "Hello123!!", 400),
+        // Same rule as sign up, token is not consumed.
+        (tokens[0].clone(), "weak", 400),
+        (tokens[0].clone(), "Hello123!!", 204),
+        // Single-use
+        (tokens[0].clone(), "Again123!!", 400),
+        // Other tokens of the user are consumed too.
+        (tokens[1].clone(), "Again123!!", 400),
+    ];
+    for (token, password, status) in test_datas {
+        let req = test::TestRequest::post().uri("/user/password/reset")
+            .insert_header(ContentType::form_url_encoded())
+            .set_form(ResetPasswordForm{ token, password })
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), status);
+    }
+
+    // Existing sessions are invalidated.
+    let req = test::TestRequest::get()
+        .uri(format!("/user/{}/todo", user_id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .to_request();
+    assert_eq!(status_of(test::try_call_service(&app, req).await), 401);
+
+    let req = test::TestRequest::post().uri("/user/refresh")
+        .insert_header(ContentType::form_url_encoded())
+        .set_form(RefreshForm { refresh_token: login_resp.refresh_token.clone() })
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 401);
+
+    // Login right after reset is not revoked.
+    // (password, expected status)
+    let test_datas = [
+        ("World123!!", 401),
+        ("Hello123!!", 200),
+    ];
+    let mut access_token = String::new();
+    for (password, status) in test_datas {
+        let req = test::TestRequest::post().uri("/user/login")
+            .insert_header(ContentType::form_url_encoded())
+            .set_form(UserLoginForm{ username: "reset", password })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), status);
+        if resp.status() == 200 {
+            let login_resp: UserLoginReturnForm = test::read_body_json(resp).await;
+            access_token = login_resp.access_token;
+        }
+    }
+
+    // Expired token
+    let req = test::TestRequest::post().uri("/user/password/forgot")
+        .insert_header(ContentType::form_url_encoded())
+        .set_form(ForgotPasswordForm{ email: "reset@gmail.com" })
+        .to_request();
+    test::call_service(&app, req).await;
+    let token = mail_tokens(&mail_dir, RESET_URL).pop().unwrap();
+    password_reset_tokens::Entity::update_many()
+        .col_expr(password_reset_tokens::Column::ExpiresAt, Expr::cust("now() - interval '1 minute'"))
+        .filter(password_reset_tokens::Column::UserId.eq(user_id))
+        .filter(password_reset_tokens::Column::UsedAt.is_null())
+        .exec(&state.conn).await
+        .unwrap();
+
+    let req = test::TestRequest::post().uri("/user/password/reset")
+        .insert_header(ContentType::form_url_encoded())
+        .set_form(ResetPasswordForm{ token, password: "Again123!!" })
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 400);
+
+    // Delete test user
+    let req = test::TestRequest::delete()
+        .uri(format!("/user/{}", user_id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", access_token)))
+        .to_request();
+    test::call_service(&app, req).await;
+    fs::remove_dir_all(&mail_dir).unwrap();
+}
@@ -0,0 +1,81 @@
+use actix_web::{
+    get, post, put, delete, HttpResponse, web
+};
+use super::AppState;
+use entity::{
+    entities::projects,
+    mutation::*
+};
+use myerror::ServerError;
+
+#[get("")]
+async fn fetch_projects(
+    state: web::Data<AppState>,
+    user_id: web::Path<i32>,
+    query: web::Query<ProjectQuery>,
+) -> Result<HttpResponse, ServerError> {
+    let models = Mutation::get_projects(&state.conn, user_id.into_inner(), query.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(models))
+}
+
+#[post("/register")]
+async fn create_project(
+    state: web::Data<AppState>,
+    user_id: web::Path<i32>,
+    project_data: web::Json<projects::Model>,
+) -> Result<HttpResponse, ServerError> {
+    let mut project_data = project_data.into_inner();
+    project_data.user_id = user_id.into_inner();
+    let model = Mutation::create_project(&state.conn, project_data).await?;
+    Ok(HttpResponse::Created().json(model))
+}
+
+#[put("/{project_id}")]
+async fn modify_project(
+    state: web::Data<AppState>,
+    path_para: web::Path<(i32, i32)>,
+    project_data: web::Json<projects::Model>,
+) -> Result<HttpResponse, ServerError> {
+    let (user_id, project_id) = path_para.into_inner();
+    let mut project_data = project_data.into_inner();
+    project_data.id = project_id;
+    project_data.user_id = user_id;
+    let model = Mutation::update_project(&state.conn, project_data).await?;
+    Ok(HttpResponse::Ok().json(model))
+}
+
+#[delete("/{project_id}")]
+async fn remove_project(
+    state: web::Data<AppState>,
+    path_para: web::Path<(i32, i32)>,
+    query: web::Query<DeleteProjectQuery>,
+) -> Result<HttpResponse, ServerError> {
+    let (user_id, project_id) = path_para.into_inner();
+    Mutation::delete_project(&state.conn, user_id, project_id, query.todos).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[get("/{project_id}/todo")]
+async fn fetch_project_todos(
+    state: web::Data<AppState>,
+    path_para: web::Path<(i32, i32)>,
+    query: web::Query<TodoQuery>,
+) -> Result<HttpResponse, ServerError> {
+    let (user_id, project_id) = path_para.into_inner();
+    let todo_page = Mutation::get_project_todo(&state.conn, user_id, project_id, query.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(todo_page))
+}
+
+#[post("/{project_id}/todo/register")]
+async fn create_project_todo(
+    state: web::Data<AppState>,
+    path_para: web::Path<(i32, i32)>,
+    todo_data: web::Json<TodoForm>,
+) -> Result<HttpResponse, ServerError> {
+    let (user_id, project_id) = path_para.into_inner();
+    let mut todo_data = todo_data.into_inner();
+    todo_data.todo.user_id = user_id;
+    todo_data.todo.project_id = Some(project_id);
+    let model = Mutation::create_todo(&state.conn, &state.config.todo, todo_data).await?;
+    Ok(HttpResponse::Created().json(model))
+}
@@ -0,0 +1,400 @@
+#![allow(dead_code)]
+
+use actix_web::{
+    App, web, test, http::header::ContentType,
+};
+use actix_web_httpauth::middleware::HttpAuthentication;
+use serde::{Serialize, Deserialize};
+use chrono::NaiveDateTime;
+use super::{user, auth, todo, project};
+use super::user_test::*;
+
+#[derive(Serialize, Clone)]
+struct ProjectForm {
+    name: String,
+    color: Option<String>,
+    sort_order: i32,
+    archived: bool,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+struct ProjectReturnForm {
+    id: i32,
+    user_id: i32,
+    name: String,
+    color: Option<String>,
+    sort_order: i32,
+    archived: bool,
+    created_at: NaiveDateTime,
+}
+
+#[derive(Serialize, Clone)]
+struct ProjectTodoForm {
+    name: String,
+    contents: Option<String>,
+    completed: bool,
+}
+
+#[derive(Serialize)]
+struct MoveTodoForm {
+    project_id: Option<i32>,
+}
+
+#[derive(Deserialize)]
+struct ProjectTodoReturnForm {
+    id: i32,
+    name: String,
+    project_id: Option<i32>,
+}
+
+#[derive(Deserialize)]
+struct ProjectTodoPageReturnForm {
+    items: Vec<ProjectTodoReturnForm>,
+    total: u64,
+}
+
+#[actix_web::test]
+async fn test_project() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(db_connect().await.clone()))
+            .service(user::signup_user)
+            .service(user::login_user)
+            .service(
+                web::scope("/user/{user_id}")
+                    .wrap(HttpAuthentication::bearer(auth::jwt_validator))
+                    .service(user::modify_user)
+                    .service(user::withdrawal_user)
+                    .service(
+                        web::scope("/todo")
+                            .service(todo::fetch_todos)
+                            .service(todo::create_todo)
+                            .service(todo::modify_todo)
+                            .service(todo::move_todo)
+                            .service(todo::remove_todo)
+                    )
+                    .service(
+                        web::scope("/project")
+                            .service(project::fetch_projects)
+                            .service(project::create_project)
+                            .service(project::modify_project)
+                            .service(project::remove_project)
+                            .service(project::fetch_project_todos)
+                            .service(project::create_project_todo)
+                    )
+            )
+    ).await;
+
+    // Create user
+    let mut login_resps = Vec::new();
+    for (username, email) in [("project", "project@gmail.com"), ("project2", "project2@gmail.com")] {
+        let req = test::TestRequest::post().uri("/user/register")
+            .insert_header(ContentType::form_url_encoded())
+            .set_form(UserSignUpForm{ username, password: "World123!!", email })
+            .to_request();
+        let _: UserSignUpReturnForm = test::call_and_read_body_json(&app, req).await;
+
+        let req = test::TestRequest::post().uri("/user/login")
+            .insert_header(ContentType::form_url_encoded())
+            .set_form(UserLoginForm{ username, password: "World123!!" })
+            .to_request();
+        let login_resp: UserLoginReturnForm = test::call_and_read_body_json(&app, req).await;
+        login_resps.push(login_resp);
+    }
+    let other_resp = login_resps.pop().unwrap();
+    let login_resp = login_resps.pop().unwrap();
+
+    // CREATE PROJECT //
+    // Valid
+    // (name, color, sort_order)
+    let test_datas = [
+        ("Work", Some("#1a2B3c"), 2),
+        ("Home", None, 1),
+        ("Study", Some("#ffffff"), 2),
+    ];
+    let mut projects = Vec::new();
+    for (name, color, sort_order) in test_datas {
+        let req = test::TestRequest::post()
+            .uri(format!("/user/{}/project/register", login_resp.user.id).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+            .set_json(ProjectForm{ name: name.to_owned(), color: color.map(str::to_owned), sort_order, archived: false })
+            .to_request();
+        let project_resp: ProjectReturnForm = test::call_and_read_body_json(&app, req).await;
+
+        assert_eq!(project_resp.name, name);
+        assert_eq!(project_resp.user_id, login_resp.user.id);
+        assert_eq!(project_resp.color.as_deref(), color);
+        projects.push(project_resp);
+    }
+    let (work, home, study) = (projects[0].clone(), projects[1].clone(), projects[2].clone());
+
+    // Other user's project
+    let req = test::TestRequest::post()
+        .uri(format!("/user/{}/project/register", other_resp.user.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", other_resp.access_token)))
+        .set_json(ProjectForm{ name: "Work".to_owned(), color: None, sort_order: 0, archived: false })
+        .to_request();
+    let other_project: ProjectReturnForm = test::call_and_read_body_json(&app, req).await;
+
+    // Invalid
+    let test_datas = [
+        // No name
+        ProjectForm{ name: "".to_owned(), color: None, sort_order: 0, archived: false },
+        // Name over 100
+        ProjectForm{ name: "a".repeat(101), color: None, sort_order: 0, archived: false },
+        // Color not hex
+        ProjectForm{ name: "Color".to_owned(), color: Some("#gggggg".to_owned()), sort_order: 0, archived: false },
+        // Color without #
+        ProjectForm{ name: "Color".to_owned(), color: Some("1a2b3c".to_owned()), sort_order: 0, archived: false },
+        // Color short
+        ProjectForm{ name: "Color".to_owned(), color: Some("#fff".to_owned()), sort_order: 0, archived: false },
+    ];
+    for data in test_datas {
+        let req = test::TestRequest::post()
+            .uri(format!("/user/{}/project/register", login_resp.user.id).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+            .set_json(data)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), 400);
+    }
+
+    // FETCH PROJECT //
+    // Sorted by sort_order then id
+    let req = test::TestRequest::get()
+        .uri(format!("/user/{}/project", login_resp.user.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .to_request();
+    let project_resp: Vec<ProjectReturnForm> = test::call_and_read_body_json(&app, req).await;
+    let names: Vec<&str> = project_resp.iter().map(|p| p.name.as_str()).collect();
+
+    assert_eq!(names, vec!["Home", "Work", "Study"]);
+
+    // MODIFY PROJECT //
+    let req = test::TestRequest::put()
+        .uri(format!("/user/{}/project/{}", login_resp.user.id, study.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .set_json(ProjectForm{ name: "Study".to_owned(), color: None, sort_order: 2, archived: true })
+        .to_request();
+    let project_resp: ProjectReturnForm = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(project_resp.id, study.id);
+    assert!(project_resp.archived);
+    assert_eq!(project_resp.color, None);
+
+    // Archived filter
+    // (query, expected project names)
+    let test_datas = [
+        ("archived=true", vec!["Study"]),
+        ("archived=false", vec!["Home", "Work"]),
+    ];
+    for (query, names) in test_datas {
+        let req = test::TestRequest::get()
+            .uri(format!("/user/{}/project?{}", login_resp.user.id, query).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+            .to_request();
+        let project_resp: Vec<ProjectReturnForm> = test::call_and_read_body_json(&app, req).await;
+        let fetch_names: Vec<&str> = project_resp.iter().map(|p| p.name.as_str()).collect();
+
+        assert_eq!(fetch_names, names, "{}", query);
+    }
+
+    // Invalid color
+    let req = test::TestRequest::put()
+        .uri(format!("/user/{}/project/{}", login_resp.user.id, work.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .set_json(ProjectForm{ name: "Work".to_owned(), color: Some("red".to_owned()), sort_order: 0, archived: false })
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), 400);
+
+    // Other user's project can not be modified
+    let req = test::TestRequest::put()
+        .uri(format!("/user/{}/project/{}", login_resp.user.id, other_project.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .set_json(ProjectForm{ name: "Stolen".to_owned(), color: None, sort_order: 0, archived: false })
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), 404);
+
+    // PROJECT TODO //
+    // (project, todo name)
+    let test_datas = [
+        (Some(&work), "Write report"),
+        (Some(&work), "Meeting"),
+        (Some(&home), "Clean room"),
+        (None, "Call mom"),
+    ];
+    let mut todos = Vec::new();
+    for (project, name) in test_datas {
+        let uri = match project {
+            Some(project) => format!("/user/{}/project/{}/todo/register", login_resp.user.id, project.id),
+            None => format!("/user/{}/todo/register", login_resp.user.id),
+        };
+        let req = test::TestRequest::post()
+            .uri(uri.as_str())
+            .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+            .set_json(ProjectTodoForm{ name: name.to_owned(), contents: None, completed: false })
+            .to_request();
+        let todo_resp: ProjectTodoReturnForm = test::call_and_read_body_json(&app, req).await;
+
+        assert_eq!(todo_resp.project_id, project.map(|p| p.id));
+        todos.push(todo_resp);
+    }
+
+    // Other user's project
+    let req = test::TestRequest::post()
+        .uri(format!("/user/{}/project/{}/todo/register", login_resp.user.id, other_project.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .set_json(ProjectTodoForm{ name: "Invalid project".to_owned(), contents: None, completed: false })
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), 404);
+
+    // Fetch project todo
+    let req = test::TestRequest::get()
+        .uri(format!("/user/{}/project/{}/todo?sort=name&order=asc", login_resp.user.id, work.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .to_request();
+    let todo_resp: ProjectTodoPageReturnForm = test::call_and_read_body_json(&app, req).await;
+    let names: Vec<&str> = todo_resp.items.iter().map(|t| t.name.as_str()).collect();
+
+    assert_eq!(names, vec!["Meeting", "Write report"]);
+    assert_eq!(todo_resp.total, 2);
+
+    let req = test::TestRequest::get()
+        .uri(format!("/user/{}/project/{}/todo", login_resp.user.id, other_project.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), 404);
+
+    // FILTER BY PROJECT //
+    // (query, expected todo names)
+    let test_datas = [
+        (format!("project_id={}", home.id), vec!["Clean room"]),
+        ("inbox=true".to_owned(), vec!["Call mom"]),
+        ("inbox=false".to_owned(), vec!["Clean room", "Meeting", "Write report"]),
+    ];
+    for (query, names) in test_datas {
+        let req = test::TestRequest::get()
+            .uri(format!("/user/{}/todo?{}&sort=name&order=asc", login_resp.user.id, query).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+            .to_request();
+        let todo_resp: ProjectTodoPageReturnForm = test::call_and_read_body_json(&app, req).await;
+        let fetch_names: Vec<&str> = todo_resp.items.iter().map(|t| t.name.as_str()).collect();
+
+        assert_eq!(fetch_names, names, "{}", query);
+    }
+
+    // MOVE TODO //
+    // (todo, target project)
+    let test_datas = [
+        // Inbox to project
+        (todos[3].id, Some(home.id)),
+        // Project to other project
+        (todos[1].id, Some(home.id)),
+        // Project to inbox
+        (todos[2].id, None),
+    ];
+    for (todo_id, project_id) in test_datas {
+        let req = test::TestRequest::put()
+            .uri(format!("/user/{}/todo/{}/project", login_resp.user.id, todo_id).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+            .set_json(MoveTodoForm{ project_id })
+            .to_request();
+        let todo_resp: ProjectTodoReturnForm = test::call_and_read_body_json(&app, req).await;
+
+        assert_eq!(todo_resp.id, todo_id);
+        assert_eq!(todo_resp.project_id, project_id);
+    }
+
+    // Invalid move
+    // (todo, target project)
+    let test_datas = [
+        // Other user's project
+        (todos[0].id, Some(other_project.id)),
+        // Not exist todo
+        (0, None),
+    ];
+    for (todo_id, project_id) in test_datas {
+        let req = test::TestRequest::put()
+            .uri(format!("/user/{}/todo/{}/project", login_resp.user.id, todo_id).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+            .set_json(MoveTodoForm{ project_id })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), 404);
+    }
+
+    // REMOVE PROJECT //
+    // Todos move to inbox (default)
+    let req = test::TestRequest::delete()
+        .uri(format!("/user/{}/project/{}", login_resp.user.id, home.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), 204);
+
+    let req = test::TestRequest::get()
+        .uri(format!("/user/{}/todo?inbox=true&sort=name&order=asc", login_resp.user.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .to_request();
+    let todo_resp: ProjectTodoPageReturnForm = test::call_and_read_body_json(&app, req).await;
+    let names: Vec<&str> = todo_resp.items.iter().map(|t| t.name.as_str()).collect();
+
+    assert_eq!(names, vec!["Call mom", "Clean room", "Meeting"]);
+
+    // Todos are deleted with project
+    let req = test::TestRequest::delete()
+        .uri(format!("/user/{}/project/{}?todos=cascade", login_resp.user.id, work.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), 204);
+
+    let req = test::TestRequest::get()
+        .uri(format!("/user/{}/todo?sort=name&order=asc", login_resp.user.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .to_request();
+    let todo_resp: ProjectTodoPageReturnForm = test::call_and_read_body_json(&app, req).await;
+    let names: Vec<&str> = todo_resp.items.iter().map(|t| t.name.as_str()).collect();
+
+    assert_eq!(names, vec!["Call mom", "Clean room", "Meeting"]);
+
+    // Invalid todos option
+    let req = test::TestRequest::delete()
+        .uri(format!("/user/{}/project/{}?todos=archive", login_resp.user.id, study.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), 400);
+
+    // Already removed
+    let req = test::TestRequest::delete()
+        .uri(format!("/user/{}/project/{}", login_resp.user.id, work.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), 404);
+
+    // Delete test users
+    // Projects and todos also remove all cause by on_delete_cascade
+    for resp in [login_resp, other_resp] {
+        let req = test::TestRequest::delete()
+            .uri(format!("/user/{}", resp.user.id).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", resp.access_token)))
+            .to_request();
+        test::call_service(&app, req).await;
+    }
+}
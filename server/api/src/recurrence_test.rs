@@ -0,0 +1,147 @@
+use chrono::NaiveDate;
+use entity::recurrence::RecurrenceRule;
+
+fn date(s: &str) -> NaiveDate {
+    s.parse().unwrap()
+}
+
+fn rule(s: &str) -> RecurrenceRule {
+    s.parse().unwrap()
+}
+
+// Occurrences after dtstart in order, until the series ends or limit.
+fn occurrences(rrule: &str, dtstart: &str, limit: usize) -> Vec<NaiveDate> {
+    let rule = rule(rrule);
+    let dtstart = date(dtstart);
+    let mut dates = Vec::new();
+    let mut after = dtstart;
+    while dates.len() < limit {
+        match rule.next_after(dtstart, after) {
+            Some(next) => {
+                dates.push(next);
+                after = next;
+            },
+            None => break,
+        }
+    }
+    dates
+}
+
+#[test]
+fn test_recurrence_parse() {
+    // (rule, expected canonical rule)
+    let test_datas = [
+        ("RRULE:freq=monthly;byday=-1fr", "FREQ=MONTHLY;BYDAY=-1FR"),
+        ("FREQ=WEEKLY;INTERVAL=1;BYDAY=MO,TH", "FREQ=WEEKLY;BYDAY=MO,TH"),
+        ("FREQ=DAILY;UNTIL=20261231T235959Z", "FREQ=DAILY;UNTIL=20261231"),
+        ("FREQ=MONTHLY;COUNT=3;BYMONTHDAY=1,-1", "FREQ=MONTHLY;COUNT=3;BYMONTHDAY=1,-1"),
+    ];
+    for (rrule, expected) in test_datas {
+        assert_eq!(rule(rrule).to_string(), expected, "{}", rrule);
+    }
+
+    let test_datas = [
+        "FREQ=DAILY;COUNT=0",
+        "FREQ=DAILY;COUNT=-1",
+        "FREQ=MONTHLY;BYDAY=0MO",
+        "FREQ=MONTHLY;BYDAY=6MO",
+        "FREQ=MONTHLY;BYDAY=-6FR",
+        "FREQ=MONTHLY;BYDAY=+MO",
+        "FREQ=WEEKLY;BYDAY=2MO",
+        "FREQ=WEEKLY;BYDAY=aéb",
+        "FREQ=WEEKLY;BYDAY=é",
+        "FREQ=MONTHLY;BYDAY=1éMO",
+        "FREQ=MONTHLY;BYMONTHDAY=0",
+        "FREQ=MONTHLY;BYMONTHDAY=-32",
+        "FREQ=HOURLY",
+        "FREQ=SECONDLY;COUNT=1",
+        "FREQ=DAILY;UNTIL=2026",
+        "FREQ=DAILY;UNTIL=2026é1231",
+        "FREQ=DAILY;FREQ",
+        "FREQ=DAILY;INTERVAL=1001",
+        "FREQ=WEEKLY;INTERVAL=521",
+        "FREQ=MONTHLY;INTERVAL=121",
+        "INTERVAL=101;FREQ=YEARLY",
+        "FREQ=DAILY;INTERVAL=4294967295",
+        "COUNT=1",
+        "",
+    ];
+    for rrule in test_datas {
+        assert!(rrule.parse::<RecurrenceRule>().is_err(), "{}", rrule);
+    }
+}
+
+#[test]
+fn test_recurrence_monthly() {
+    // (rule, dtstart, expected occurrences)
+    let test_datas = [
+        // Month without 31th is skipped.
+        ("FREQ=MONTHLY;BYMONTHDAY=31", "2026-01-31", vec!["2026-03-31", "2026-05-31", "2026-07-31", "2026-08-31"]),
+        // Last day of month, including leap year.
+        ("FREQ=MONTHLY;BYMONTHDAY=-1", "2026-01-31", vec!["2026-02-28", "2026-03-31", "2026-04-30"]),
+        ("FREQ=MONTHLY;BYMONTHDAY=-1", "2028-01-31", vec!["2028-02-29", "2028-03-31"]),
+        // Same day of month as dtstart.
+        ("FREQ=MONTHLY", "2026-01-31", vec!["2026-03-31", "2026-05-31"]),
+        ("FREQ=MONTHLY;INTERVAL=2;BYMONTHDAY=1,15", "2026-01-01", vec!["2026-01-15", "2026-03-01", "2026-03-15"]),
+        // Last Friday
+        ("FREQ=MONTHLY;BYDAY=-1FR", "2026-01-30", vec!["2026-02-27", "2026-03-27", "2026-04-24"]),
+        // Second Monday
+        ("FREQ=MONTHLY;BYDAY=2MO", "2026-01-12", vec!["2026-02-09", "2026-03-09", "2026-04-13"]),
+        // Fifth Friday exists only in some months.
+        ("FREQ=MONTHLY;BYDAY=5FR", "2026-01-30", vec!["2026-05-29", "2026-07-31"]),
+    ];
+    for (rrule, dtstart, expected) in test_datas {
+        let expected: Vec<NaiveDate> = expected.into_iter().map(date).collect();
+        assert_eq!(occurrences(rrule, dtstart, expected.len()), expected, "{}", rrule);
+    }
+}
+
+#[test]
+fn test_recurrence_end() {
+    // (rule, dtstart, expected occurrences after dtstart)
+    let test_datas = [
+        // dtstart is the first of COUNT.
+        ("FREQ=DAILY;COUNT=3", "2026-01-01", vec!["2026-01-02", "2026-01-03"]),
+        ("FREQ=DAILY;COUNT=1", "2026-01-01", vec![]),
+        // dtstart is counted even if it does not match the rule. (2026-01-01 is Thursday)
+        ("FREQ=WEEKLY;BYDAY=MO;COUNT=2", "2026-01-01", vec!["2026-01-05"]),
+        ("FREQ=WEEKLY;BYDAY=MO,TH;COUNT=4", "2026-01-01", vec!["2026-01-05", "2026-01-08", "2026-01-12"]),
+        // UNTIL is inclusive.
+        ("FREQ=DAILY;UNTIL=20260103", "2026-01-01", vec!["2026-01-02", "2026-01-03"]),
+        ("FREQ=WEEKLY;UNTIL=20260114", "2026-01-01", vec!["2026-01-08"]),
+        ("FREQ=DAILY;UNTIL=20260101", "2026-01-01", vec![]),
+        // Skipped month is not counted.
+        ("FREQ=MONTHLY;BYMONTHDAY=31;COUNT=2", "2026-01-31", vec!["2026-03-31"]),
+    ];
+    for (rrule, dtstart, expected) in test_datas {
+        let expected: Vec<NaiveDate> = expected.into_iter().map(date).collect();
+        assert_eq!(occurrences(rrule, dtstart, 10), expected, "{}", rrule);
+    }
+
+    let rule = rule("FREQ=DAILY;COUNT=3");
+    // dtstart is next of any date before it.
+    assert_eq!(rule.next_after(date("2026-01-01"), date("2025-12-01")), Some(date("2026-01-01")));
+    // Next of date between occurrences.
+    assert_eq!(rule.next_after(date("2026-01-01"), date("2026-01-02")), Some(date("2026-01-03")));
+    assert_eq!(rule.next_after(date("2026-01-01"), date("2026-02-01")), None);
+}
+
+#[test]
+fn test_recurrence_large_interval() {
+    let test_datas = [
+        ("FREQ=DAILY;INTERVAL=1000", "2026-01-01", "2028-09-27"),
+        ("FREQ=WEEKLY;INTERVAL=520", "2026-01-01", "2035-12-20"),
+        ("FREQ=MONTHLY;INTERVAL=120", "2026-01-01", "2036-01-01"),
+        ("FREQ=YEARLY;INTERVAL=100", "2026-01-01", "2126-01-01"),
+    ];
+    for (rrule, dtstart, expected) in test_datas {
+        assert_eq!(occurrences(rrule, dtstart, 1), vec![date(expected)], "{}", rrule);
+    }
+
+    // No next occurrence after the last date, instead of overflow.
+    let last = NaiveDate::MAX;
+    for rrule in ["FREQ=DAILY;INTERVAL=1000", "FREQ=WEEKLY;INTERVAL=520;BYDAY=MO,SU",
+        "FREQ=MONTHLY;INTERVAL=120;BYMONTHDAY=-1", "FREQ=MONTHLY;BYDAY=-1FR", "FREQ=YEARLY;INTERVAL=100"] {
+        assert_eq!(rule(rrule).next_after(last, last), None, "{}", rrule);
+    }
+}
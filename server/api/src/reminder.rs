@@ -0,0 +1,55 @@
+use actix_web::{
+    get, post, delete, HttpResponse, web
+};
+use super::{auth, AppState};
+use entity::{
+    entities::reminders,
+    mutation::*
+};
+use myerror::ServerError;
+
+#[get("/{todo_id}/reminder")]
+async fn fetch_reminders(
+    state: web::Data<AppState>,
+    path_para: web::Path<(i32, i32)>,
+) -> Result<HttpResponse, ServerError> {
+    let (user_id, todo_id) = path_para.into_inner();
+    let models = Mutation::get_reminders(&state.conn, user_id, todo_id).await?;
+    Ok(HttpResponse::Ok().json(models))
+}
+
+#[post("/{todo_id}/reminder/register")]
+async fn create_reminder(
+    state: web::Data<AppState>,
+    path_para: web::Path<(i32, i32)>,
+    reminder_data: web::Json<reminders::Model>,
+) -> Result<HttpResponse, ServerError> {
+    let (user_id, todo_id) = path_para.into_inner();
+    let mut reminder_data = reminder_data.into_inner();
+    reminder_data.user_id = user_id;
+    reminder_data.todo_id = todo_id;
+    if state.notifiers.get(&reminder_data.channel).is_none() {
+        return Err(ServerError::UnprocessableEntityError {
+            code: "CHANNEL_UNAVAILABLE",
+            msg: "Unavailable channel",
+            detail: format!("{} channel is not available", reminder_data.channel),
+            field: Some("channel".to_owned()),
+        })
+    }
+    // Reminder mails are sent only to verified email.
+    if reminder_data.channel == "email" {
+        auth::require_verified_email(&state, user_id).await?;
+    }
+    let model = Mutation::create_reminder(&state.conn, reminder_data).await?;
+    Ok(HttpResponse::Created().json(model))
+}
+
+#[delete("/{todo_id}/reminder/{reminder_id}")]
+async fn remove_reminder(
+    state: web::Data<AppState>,
+    path_para: web::Path<(i32, i32, i32)>,
+) -> Result<HttpResponse, ServerError> {
+    let (user_id, todo_id, reminder_id) = path_para.into_inner();
+    Mutation::delete_reminder(&state.conn, user_id, todo_id, reminder_id).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
@@ -0,0 +1,479 @@
+#![allow(dead_code)]
+
+use std::sync::{Arc, Mutex, atomic::{AtomicBool, Ordering}};
+use actix_web::{
+    App, HttpServer, HttpResponse, web, test, rt, http::header::ContentType,
+};
+use actix_web_httpauth::middleware::HttpAuthentication;
+use serde::{Serialize, Deserialize};
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate, Utc};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use myconfig::{SmtpConfig, SmtpTls};
+use notifier::Notifiers;
+use super::{user, auth, todo, reminder, notification, scheduler};
+use super::user_test::*;
+
+#[derive(Serialize, Clone)]
+struct ReminderSignUpForm {
+    username: &'static str,
+    password: &'static str,
+    email: &'static str,
+    time_zone: &'static str,
+}
+
+#[derive(Serialize, Clone)]
+struct ReminderTodoForm {
+    name: &'static str,
+    due_date: Option<NaiveDate>,
+    due_at: Option<DateTime<FixedOffset>>,
+    completed: bool,
+    recurrence: Option<&'static str>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+struct ReminderTodoReturnForm {
+    id: i32,
+    due_at: Option<DateTime<FixedOffset>>,
+    series_id: Option<i32>,
+}
+
+#[derive(Deserialize)]
+struct ReminderTodoPageReturnForm {
+    items: Vec<ReminderTodoReturnForm>,
+}
+
+#[derive(Serialize, Clone)]
+struct ReminderForm {
+    remind_at: Option<DateTime<FixedOffset>>,
+    offset_minutes: Option<i32>,
+    channel: &'static str,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+struct ReminderReturnForm {
+    id: i32,
+    todo_id: i32,
+    channel: String,
+    fire_at: Option<DateTime<FixedOffset>>,
+    sent_at: Option<DateTime<FixedOffset>>,
+    attempts: i32,
+    last_error: Option<String>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+struct NotificationReturnForm {
+    id: i32,
+    todo_id: Option<i32>,
+    title: String,
+    body: String,
+    read: bool,
+}
+
+/// Local webhook receiver. Responds 500 while fail is set.
+async fn webhook_stand_in() -> (String, Arc<Mutex<Vec<serde_json::Value>>>, Arc<AtomicBool>) {
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let fail = Arc::new(AtomicBool::new(false));
+    let (data, fail_data) = (web::Data::from(received.clone()), web::Data::from(fail.clone()));
+    let server = HttpServer::new(move ||
+        App::new()
+            .app_data(data.clone())
+            .app_data(fail_data.clone())
+            .route("/hook", web::post().to(|
+                received: web::Data<Mutex<Vec<serde_json::Value>>>,
+                fail: web::Data<AtomicBool>,
+                body: web::Json<serde_json::Value>,
+            | async move {
+                if fail.load(Ordering::SeqCst) {
+                    return HttpResponse::InternalServerError().finish()
+                }
+                received.lock().unwrap().push(body.into_inner());
+                HttpResponse::Ok().finish()
+            }))
+    )
+    .workers(1)
+    .bind(("127.0.0.1", 0)).unwrap();
+    let addr = server.addrs()[0];
+    rt::spawn(server.run());
+    (format!("http://{}/hook", addr), received, fail)
+}
+
+/// Local SMTP server which accepts every mail without auth.
+async fn smtp_stand_in() -> (u16, Arc<Mutex<Vec<String>>>) {
+    let mails = Arc::new(Mutex::new(Vec::new()));
+    let listener = rt::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let received = mails.clone();
+    rt::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let received = received.clone();
+            rt::spawn(async move {
+                let (reader, mut writer) = stream.into_split();
+                let mut lines = BufReader::new(reader).lines();
+                writer.write_all(b"220 localhost ESMTP\r\n").await.unwrap();
+                let mut data: Option<String> = None;
+                while let Ok(Some(line)) = lines.next_line().await {
+                    if let Some(mail) = data.as_mut() {
+                        if line == "." {
+                            received.lock().unwrap().push(data.take().unwrap());
+                            writer.write_all(b"250 OK\r\n").await.unwrap();
+                        } else {
+                            mail.push_str(&line);
+                            mail.push('\n');
+                        }
+                        continue
+                    }
+                    let command = line.to_ascii_uppercase();
+                    if command.starts_with("QUIT") {
+                        writer.write_all(b"221 Bye\r\n").await.unwrap();
+                        break
+                    }
+                    let reply: &[u8] = if command.starts_with("EHLO") || command.starts_with("HELO") {
+                        b"250 localhost\r\n"
+                    } else if command.starts_with("DATA") {
+                        data = Some(String::new());
+                        b"354 End data with <CR><LF>.<CR><LF>\r\n"
+                    } else {
+                        b"250 OK\r\n"
+                    };
+                    writer.write_all(reply).await.unwrap();
+                }
+            });
+        }
+    });
+    (port, mails)
+}
+
+#[actix_web::test]
+async fn test_reminder() {
+    let (webhook_url, hooks, hook_fail) = webhook_stand_in().await;
+    let (smtp_port, mails) = smtp_stand_in().await;
+
+    let mut state = db_connect().await;
+    state.config.reminder.webhook_url = Some(webhook_url);
+    state.config.smtp = Some(SmtpConfig {
+        host: "127.0.0.1".to_owned(),
+        port: smtp_port,
+        tls: SmtpTls::None,
+        username: None,
+        password: None,
+        from: "Todo <noreply@todo.local>".to_owned(),
+    });
+    state.notifiers = Arc::new(Notifiers::new(&state.config, state.conn.clone()).unwrap());
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state.clone()))
+            .service(user::signup_user)
+            .service(user::login_user)
+            .service(
+                web::scope("/user/{user_id}")
+                    .wrap(HttpAuthentication::bearer(auth::jwt_validator))
+                    .service(user::modify_user)
+                    .service(user::withdrawal_user)
+                    .service(
+                        web::scope("/todo")
+                            .service(todo::fetch_todos)
+                            .service(todo::create_todo)
+                            .service(todo::modify_todo)
+                            .service(reminder::fetch_reminders)
+                            .service(reminder::create_reminder)
+                            .service(reminder::remove_reminder)
+                    )
+                    .service(
+                        web::scope("/notification")
+                            .service(notification::fetch_notifications)
+                            .service(notification::read_notification)
+                            .service(notification::remove_notification)
+                    )
+            )
+    ).await;
+
+    // Sign up and login
+    let mut login_resps = Vec::new();
+    for (username, email) in [("reminder", "reminder@gmail.com"), ("reminder2", "reminder2@gmail.com")] {
+        let req = test::TestRequest::post().uri("/user/register")
+            .insert_header(ContentType::form_url_encoded())
+            .set_form(ReminderSignUpForm{ username, password: "World123!!", email, time_zone: "Asia/Seoul" })
+            .to_request();
+        test::call_service(&app, req).await;
+
+        let req = test::TestRequest::post().uri("/user/login")
+            .insert_header(ContentType::form_url_encoded())
+            .set_form(UserLoginForm{ username, password: "World123!!" })
+            .to_request();
+        let login_resp: UserLoginReturnForm = test::call_and_read_body_json(&app, req).await;
+        login_resps.push(login_resp);
+    }
+    let other_resp = login_resps.pop().unwrap();
+    let login_resp = login_resps.pop().unwrap();
+
+    // Whole seconds, database keeps microseconds only.
+    let now = DateTime::from_timestamp(Utc::now().timestamp(), 0).unwrap();
+    let meeting_at = (now + Duration::hours(2)).fixed_offset();
+    let all_day = (now + Duration::hours(9)).date_naive() + Duration::days(3);
+
+    let mut todos = Vec::new();
+    let test_datas = [
+        ("Meeting", None, Some(meeting_at), false),
+        ("All day", Some(all_day), None, false),
+        ("No due", None, None, false),
+        ("Done", None, None, true),
+    ];
+    for (name, due_date, due_at, completed) in test_datas {
+        let req = test::TestRequest::post()
+            .uri(format!("/user/{}/todo/register", login_resp.user.id).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+            .set_json(ReminderTodoForm{ name, due_date, due_at, completed, recurrence: None })
+            .to_request();
+        let todo_resp: ReminderTodoReturnForm = test::call_and_read_body_json(&app, req).await;
+        todos.push(todo_resp);
+    }
+    let (meeting, all_day_todo, no_due, done) = (&todos[0], &todos[1], &todos[2], &todos[3]);
+
+    // CREATE REMINDER //
+    let past = (now - Duration::minutes(1)).fixed_offset();
+    // (user, todo id, form, expected status)
+    let test_datas = [
+        (&login_resp, meeting.id, ReminderForm{ remind_at: Some(past), offset_minutes: Some(10), channel: "inbox" }, 400),
+        (&login_resp, meeting.id, ReminderForm{ remind_at: None, offset_minutes: None, channel: "inbox" }, 400),
+        (&login_resp, meeting.id, ReminderForm{ remind_at: None, offset_minutes: Some(-10), channel: "inbox" }, 400),
+        (&login_resp, meeting.id, ReminderForm{ remind_at: None, offset_minutes: Some(10), channel: "sms" }, 422),
+        (&login_resp, 0, ReminderForm{ remind_at: None, offset_minutes: Some(10), channel: "inbox" }, 404),
+        // Other user's todo
+        (&other_resp, meeting.id, ReminderForm{ remind_at: None, offset_minutes: Some(10), channel: "inbox" }, 404),
+    ];
+    for (resp, todo_id, form, status) in test_datas {
+        let req = test::TestRequest::post()
+            .uri(format!("/user/{}/todo/{}/reminder/register", resp.user.id, todo_id).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", resp.access_token)))
+            .set_json(form.clone())
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), status, "{:?} {:?}", form.offset_minutes, form.channel);
+    }
+
+    // (todo, form, expected fire_at)
+    let all_day_start = all_day.and_hms_opt(0, 0, 0).unwrap().and_local_timezone(FixedOffset::east_opt(9 * 3600).unwrap()).unwrap();
+    let test_datas = [
+        // Missed while server was down
+        (meeting, ReminderForm{ remind_at: Some(past), offset_minutes: None, channel: "inbox" }, Some(past)),
+        (meeting, ReminderForm{ remind_at: None, offset_minutes: Some(30), channel: "webhook" }, Some(meeting_at - Duration::minutes(30))),
+        (all_day_todo, ReminderForm{ remind_at: None, offset_minutes: Some(60), channel: "email" }, Some(all_day_start - Duration::minutes(60))),
+        (no_due, ReminderForm{ remind_at: None, offset_minutes: Some(10), channel: "inbox" }, None),
+        (done, ReminderForm{ remind_at: Some(past), offset_minutes: None, channel: "inbox" }, Some(past)),
+    ];
+    let mut reminders = Vec::new();
+    for (todo, form, fire_at) in test_datas {
+        let req = test::TestRequest::post()
+            .uri(format!("/user/{}/todo/{}/reminder/register", login_resp.user.id, todo.id).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+            .set_json(form)
+            .to_request();
+        let reminder_resp: ReminderReturnForm = test::call_and_read_body_json(&app, req).await;
+
+        assert_eq!(reminder_resp.fire_at, fire_at);
+        reminders.push(reminder_resp);
+    }
+
+    // FETCH AND DELETE REMINDER //
+    let req = test::TestRequest::get()
+        .uri(format!("/user/{}/todo/{}/reminder", login_resp.user.id, meeting.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .to_request();
+    let reminder_resp: Vec<ReminderReturnForm> = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(reminder_resp.iter().map(|r| r.channel.as_str()).collect::<Vec<_>>(), vec!["inbox", "webhook"]);
+
+    // (reminder id, expected status)
+    let test_datas = [
+        (reminders[3].id, 204),
+        (reminders[3].id, 404),
+    ];
+    for (reminder_id, status) in test_datas {
+        let req = test::TestRequest::delete()
+            .uri(format!("/user/{}/todo/{}/reminder/{}", login_resp.user.id, no_due.id, reminder_id).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), status);
+    }
+
+    // RESCHEDULE //
+    // Relative reminder follows due of todo.
+    let meeting_at = meeting_at + Duration::hours(1);
+    let req = test::TestRequest::put()
+        .uri(format!("/user/{}/todo/{}", login_resp.user.id, meeting.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .set_json(ReminderTodoForm{ name: "Meeting", due_date: None, due_at: Some(meeting_at), completed: false, recurrence: None })
+        .to_request();
+    test::call_service(&app, req).await;
+
+    // All day todo is due at midnight of user's new time zone.
+    let req = test::TestRequest::put()
+        .uri(format!("/user/{}", login_resp.user.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .insert_header(ContentType::form_url_encoded())
+        .set_form(ReminderSignUpForm{ username: "reminder", password: "World123!!", email: "reminder@gmail.com", time_zone: "UTC" })
+        .to_request();
+    test::call_service(&app, req).await;
+    let all_day_start = all_day.and_hms_opt(0, 0, 0).unwrap().and_utc().fixed_offset();
+
+    // (todo id, expected fire_at of last reminder)
+    let test_datas = [
+        (meeting.id, meeting_at - Duration::minutes(30)),
+        (all_day_todo.id, all_day_start - Duration::minutes(60)),
+    ];
+    for (todo_id, fire_at) in test_datas {
+        let req = test::TestRequest::get()
+            .uri(format!("/user/{}/todo/{}/reminder", login_resp.user.id, todo_id).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+            .to_request();
+        let reminder_resp: Vec<ReminderReturnForm> = test::call_and_read_body_json(&app, req).await;
+
+        assert_eq!(reminder_resp.last().unwrap().fire_at, Some(fire_at));
+    }
+
+    // SCHEDULER //
+    // Inbox reminder missed before now is fired once, completed todo's reminder is skipped.
+    assert_eq!(scheduler::run_once(&state, now).await.unwrap(), 1);
+    assert_eq!(scheduler::run_once(&state, now).await.unwrap(), 0);
+
+    // Webhook failure is retried later.
+    let webhook_at = (meeting_at - Duration::minutes(30)).with_timezone(&Utc);
+    hook_fail.store(true, Ordering::SeqCst);
+    assert_eq!(scheduler::run_once(&state, webhook_at).await.unwrap(), 0);
+
+    let req = test::TestRequest::get()
+        .uri(format!("/user/{}/todo/{}/reminder", login_resp.user.id, meeting.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .to_request();
+    let reminder_resp: Vec<ReminderReturnForm> = test::call_and_read_body_json(&app, req).await;
+    let webhook_reminder = &reminder_resp[1];
+
+    assert_eq!(webhook_reminder.attempts, 1);
+    assert!(webhook_reminder.last_error.is_some());
+    assert_eq!(webhook_reminder.fire_at, Some((webhook_at + Duration::seconds(60)).fixed_offset()));
+
+    hook_fail.store(false, Ordering::SeqCst);
+    assert_eq!(scheduler::run_once(&state, webhook_at + Duration::seconds(30)).await.unwrap(), 0);
+    assert_eq!(scheduler::run_once(&state, webhook_at + Duration::seconds(60)).await.unwrap(), 1);
+    {
+        let hooks = hooks.lock().unwrap();
+        assert_eq!(hooks.len(), 1);
+        assert_eq!(hooks[0]["todo_id"], meeting.id);
+        assert_eq!(hooks[0]["title"], "Meeting");
+        assert!(hooks[0].get("email").is_none());
+    }
+
+    // Email is sent to user.
+    let email_at = (all_day_start - Duration::minutes(60)).with_timezone(&Utc);
+    assert_eq!(scheduler::run_once(&state, email_at).await.unwrap(), 1);
+    {
+        let mails = mails.lock().unwrap();
+        assert_eq!(mails.len(), 1);
+        assert!(mails[0].contains("To: reminder@gmail.com"));
+        assert!(mails[0].contains("[Todo] All day"));
+        assert!(mails[0].contains(&format!("Due on {}", all_day)));
+    }
+
+    // RECURRING //
+    // Relative reminder is copied to next occurrence.
+    let req = test::TestRequest::post()
+        .uri(format!("/user/{}/todo/register", login_resp.user.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .set_json(ReminderTodoForm{ name: "Daily", due_date: None, due_at: Some(meeting_at), completed: false, recurrence: Some("FREQ=DAILY") })
+        .to_request();
+    let daily: ReminderTodoReturnForm = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::post()
+        .uri(format!("/user/{}/todo/{}/reminder/register", login_resp.user.id, daily.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .set_json(ReminderForm{ remind_at: None, offset_minutes: Some(10), channel: "inbox" })
+        .to_request();
+    test::call_service(&app, req).await;
+
+    let req = test::TestRequest::put()
+        .uri(format!("/user/{}/todo/{}", login_resp.user.id, daily.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .set_json(ReminderTodoForm{ name: "Daily", due_date: None, due_at: Some(meeting_at), completed: true, recurrence: None })
+        .to_request();
+    test::call_service(&app, req).await;
+
+    let req = test::TestRequest::get()
+        .uri(format!("/user/{}/todo?series_id={}&completed=false", login_resp.user.id, daily.series_id.unwrap()).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .to_request();
+    let todo_resp: ReminderTodoPageReturnForm = test::call_and_read_body_json(&app, req).await;
+    let next = &todo_resp.items[0];
+
+    let req = test::TestRequest::get()
+        .uri(format!("/user/{}/todo/{}/reminder", login_resp.user.id, next.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .to_request();
+    let reminder_resp: Vec<ReminderReturnForm> = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(reminder_resp.len(), 1);
+    assert_eq!(reminder_resp[0].fire_at, Some(next.due_at.unwrap() - Duration::minutes(10)));
+
+    // INBOX //
+    let req = test::TestRequest::get()
+        .uri(format!("/user/{}/notification?read=false", login_resp.user.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .to_request();
+    let notification_resp: Vec<NotificationReturnForm> = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(notification_resp.len(), 1);
+    assert_eq!(notification_resp[0].todo_id, Some(meeting.id));
+    assert_eq!(notification_resp[0].title, "Meeting");
+    let notification_id = notification_resp[0].id;
+
+    let req = test::TestRequest::put()
+        .uri(format!("/user/{}/notification/{}/read", login_resp.user.id, notification_id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .to_request();
+    let notification_resp: NotificationReturnForm = test::call_and_read_body_json(&app, req).await;
+
+    assert!(notification_resp.read);
+
+    // (query, expected count)
+    let test_datas = [
+        ("read=false", 0),
+        ("read=true", 1),
+    ];
+    for (query, count) in test_datas {
+        let req = test::TestRequest::get()
+            .uri(format!("/user/{}/notification?{}", login_resp.user.id, query).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+            .to_request();
+        let notification_resp: Vec<NotificationReturnForm> = test::call_and_read_body_json(&app, req).await;
+
+        assert_eq!(notification_resp.len(), count, "{}", query);
+    }
+
+    // (user, expected status)
+    let test_datas = [
+        (&other_resp, 404),
+        (&login_resp, 204),
+        (&login_resp, 404),
+    ];
+    for (resp, status) in test_datas {
+        let req = test::TestRequest::delete()
+            .uri(format!("/user/{}/notification/{}", resp.user.id, notification_id).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", resp.access_token)))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), status);
+    }
+
+    // Delete test users
+    for resp in [login_resp, other_resp] {
+        let req = test::TestRequest::delete()
+            .uri(format!("/user/{}", resp.user.id).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", resp.access_token)))
+            .to_request();
+        test::call_service(&app, req).await;
+    }
+}
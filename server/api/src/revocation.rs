@@ -0,0 +1,99 @@
+use std::{collections::HashMap, sync::{Arc, RwLock}};
+use actix_web::rt;
+use chrono::{DateTime, Duration, Utc};
+use sea_orm::DbConn;
+use entity::mutation::Mutation;
+use myerror::ServerError;
+use myconfig::AuthConfig;
+use super::auth::JwtClaim;
+
+/// Revocations are loaded again from this long before last sync,
+/// so rows committed late by other servers are not missed.
+const SYNC_OVERLAP_SECS: i64 = 60;
+
+#[derive(Default)]
+struct RevocationCache {
+    /// jti and exp of revoked tokens.
+    tokens: HashMap<String, i64>,
+    /// Tokens of the user issued before this unix time in milliseconds are revoked.
+    valid_after: HashMap<i32, i64>,
+    /// Revoked session and revoked unix time.
+    sessions: HashMap<i32, i64>,
+    synced_at: Option<DateTime<Utc>>,
+}
+
+/// Revoked access tokens in memory, so validator does not hit database on every request.
+/// Database is the source of truth, revocations of other servers are loaded by sync.
+pub struct RevocationStore {
+    config: AuthConfig,
+    cache: RwLock<RevocationCache>,
+}
+
+impl RevocationStore {
+    /// Load every unexpired revocation.
+    pub async fn load(db: &DbConn, config: &AuthConfig) -> Result<RevocationStore, ServerError> {
+        let store = RevocationStore { config: config.clone(), cache: RwLock::default() };
+        store.sync(db).await?;
+        Ok(store)
+    }
+
+    /// Load revocations created since last sync and drop expired ones.
+    pub async fn sync(&self, db: &DbConn) -> Result<(), ServerError> {
+        let now = Utc::now();
+        let since = self.cache.read().unwrap().synced_at
+            .map(|t| t - Duration::seconds(SYNC_OVERLAP_SECS));
+        let revocations = Mutation::get_revocations(db, &self.config, since).await?;
+
+        let mut cache = self.cache.write().unwrap();
+        for token in revocations.tokens {
+            cache.tokens.insert(token.jti, token.expires_at.timestamp());
+        }
+        for (user_id, valid_after) in revocations.users {
+            let valid_after = valid_after.timestamp_millis();
+            let entry = cache.valid_after.entry(user_id).or_insert(valid_after);
+            *entry = (*entry).max(valid_after);
+        }
+        for (session_id, revoked_at) in revocations.sessions {
+            cache.sessions.insert(session_id, revoked_at.timestamp());
+        }
+        cache.tokens.retain(|_, exp| *exp >= now.timestamp());
+        // Every access token of the session is expired.
+        let sessions_since = (now - Duration::minutes(self.config.access_token_exp_min as i64)).timestamp();
+        cache.sessions.retain(|_, revoked_at| *revoked_at >= sessions_since);
+        cache.synced_at = Some(now);
+        Ok(())
+    }
+
+    pub fn revoke(&self, claim: &JwtClaim) {
+        self.cache.write().unwrap().tokens.insert(claim.jti.clone(), claim.exp as i64);
+    }
+
+    pub fn revoke_all(&self, user_id: i32, valid_after: DateTime<Utc>) {
+        self.cache.write().unwrap().valid_after.insert(user_id, valid_after.timestamp_millis());
+    }
+
+    pub fn revoke_session(&self, session_id: i32, revoked_at: DateTime<Utc>) {
+        self.cache.write().unwrap().sessions.insert(session_id, revoked_at.timestamp());
+    }
+
+    /// Issue time is compared in milliseconds, so new login right after log out all sessions is not revoked.
+    pub fn is_revoked(&self, claim: &JwtClaim) -> bool {
+        let cache = self.cache.read().unwrap();
+        cache.tokens.contains_key(&claim.jti)
+            || cache.sessions.contains_key(&claim.sid)
+            || cache.valid_after.get(&claim.user_id).is_some_and(|after| claim.issued_at_ms() <= *after)
+    }
+}
+
+/// Sync revocation store every interval in background.
+pub fn spawn_sync(store: Arc<RevocationStore>, db: DbConn, interval: std::time::Duration) {
+    rt::spawn(async move {
+        let mut interval = rt::time::interval(interval);
+        loop {
+            interval.tick().await;
+            if let Err(e) = store.sync(&db).await {
+                log::error!("Revocation sync error: {:?}", e);
+            }
+        }
+    });
+}
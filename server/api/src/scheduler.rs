@@ -0,0 +1,64 @@
+use std::time::Duration;
+use actix_web::rt;
+use chrono::{DateTime, Utc};
+use sea_orm::TransactionTrait;
+use entity::{db_error::db_error, mutation::Mutation};
+use myerror::ServerError;
+use notifier::Notification;
+use super::AppState;
+
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Fire due reminders every poll interval in background.
+/// Every state is in database, so reminders missed while server was down are fired on next poll.
+pub fn spawn(state: AppState) {
+    rt::spawn(async move {
+        let mut interval = rt::time::interval(state.config.reminder.poll_interval);
+        loop {
+            interval.tick().await;
+            if let Err(e) = run_once(&state, Utc::now()).await {
+                log::error!("Reminder scheduler error: {:?}", e);
+            }
+        }
+    });
+}
+
+/// Fire reminders due by now in one transaction, returns number of delivered reminders.
+/// Failed delivery is retried after retry_delay, doubled on every attempt up to a day.
+/// Delivery is at least once, reminder may be sent again if commit fails.
+pub async fn run_once(state: &AppState, now: DateTime<Utc>) -> Result<usize, ServerError> {
+    let config = &state.config.reminder;
+    let txn = state.conn.begin().await
+        .map_err(|e|
+            db_error("Database transaction error", e)
+        )?;
+
+    let mut sent = 0;
+    for due in Mutation::lock_due_reminders(&txn, now, config.batch_size, config.max_attempts).await? {
+        let result = match state.notifiers.get(&due.reminder.channel) {
+            Some(notifier) => notifier.notify(&Notification::from(&due)).await
+                .map_err(|e| e.to_string()),
+            None => Err(format!("{} channel is not configured", due.reminder.channel)),
+        };
+
+        match result {
+            Ok(()) => {
+                Mutation::mark_reminder_sent(&txn, due.reminder, now).await?;
+                sent += 1;
+            },
+            Err(e) => {
+                log::warn!("Reminder {} failed: {}", due.reminder.id, e);
+                let delay = config.retry_delay.saturating_mul(2u32.pow(due.reminder.attempts.clamp(0, 16) as u32))
+                    .min(MAX_RETRY_DELAY);
+                let retry_at = now + chrono::Duration::from_std(delay).unwrap_or(chrono::Duration::days(1));
+                Mutation::mark_reminder_failed(&txn, due.reminder, &e, retry_at).await?;
+            },
+        }
+    }
+
+    txn.commit().await
+        .map_err(|e|
+            db_error("Database transaction error", e)
+        )?;
+    Ok(sent)
+}
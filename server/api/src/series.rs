@@ -0,0 +1,42 @@
+use actix_web::{
+    get, put, delete, HttpResponse, web
+};
+use super::AppState;
+use entity::{
+    entities::series,
+    mutation::*
+};
+use myerror::ServerError;
+
+#[get("")]
+async fn fetch_series(
+    state: web::Data<AppState>,
+    user_id: web::Path<i32>,
+) -> Result<HttpResponse, ServerError> {
+    let models = Mutation::get_series(&state.conn, user_id.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(models))
+}
+
+#[put("/{series_id}")]
+async fn modify_series(
+    state: web::Data<AppState>,
+    path_para: web::Path<(i32, i32)>,
+    series_data: web::Json<series::Model>,
+) -> Result<HttpResponse, ServerError> {
+    let (user_id, series_id) = path_para.into_inner();
+    let mut series_data = series_data.into_inner();
+    series_data.id = series_id;
+    series_data.user_id = user_id;
+    let model = Mutation::update_series(&state.conn, series_data).await?;
+    Ok(HttpResponse::Ok().json(model))
+}
+
+#[delete("/{series_id}")]
+async fn remove_series(
+    state: web::Data<AppState>,
+    path_para: web::Path<(i32, i32)>,
+) -> Result<HttpResponse, ServerError> {
+    let (user_id, series_id) = path_para.into_inner();
+    Mutation::delete_series(&state.conn, user_id, series_id).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
@@ -0,0 +1,371 @@
+#![allow(dead_code)]
+
+use actix_web::{
+    App, web, test, http::header::ContentType,
+};
+use actix_web_httpauth::middleware::HttpAuthentication;
+use serde::{Serialize, Deserialize};
+use chrono::{NaiveDate, NaiveDateTime};
+use super::{user, auth, todo, tag, series};
+use super::user_test::*;
+
+#[derive(Serialize, Clone)]
+struct RecurringTodoForm {
+    name: String,
+    contents: Option<String>,
+    due_date: Option<NaiveDate>,
+    completed: bool,
+    tags: Option<Vec<i32>>,
+    recurrence: Option<String>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+struct SeriesTagReturnForm {
+    id: i32,
+    name: String,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+struct RecurringTodoReturnForm {
+    id: i32,
+    name: String,
+    due_date: Option<NaiveDate>,
+    completed: bool,
+    series_id: Option<i32>,
+    tags: Vec<SeriesTagReturnForm>,
+}
+
+#[derive(Deserialize)]
+struct RecurringTodoPageReturnForm {
+    items: Vec<RecurringTodoReturnForm>,
+    total: u64,
+}
+
+#[derive(Serialize)]
+struct SeriesForm {
+    rrule: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct SeriesReturnForm {
+    id: i32,
+    user_id: i32,
+    rrule: String,
+    dtstart: NaiveDate,
+    created_at: NaiveDateTime,
+}
+
+fn date(s: &str) -> NaiveDate {
+    s.parse().unwrap()
+}
+
+#[actix_web::test]
+async fn test_series() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(db_connect().await.clone()))
+            .service(user::signup_user)
+            .service(user::login_user)
+            .service(
+                web::scope("/user/{user_id}")
+                    .wrap(HttpAuthentication::bearer(auth::jwt_validator))
+                    .service(user::modify_user)
+                    .service(user::withdrawal_user)
+                    .service(
+                        web::scope("/todo")
+                            .service(todo::fetch_todos)
+                            .service(todo::create_todo)
+                            .service(todo::modify_todo)
+                            .service(todo::remove_todo)
+                    )
+                    .service(
+                        web::scope("/tag")
+                            .service(tag::create_tag)
+                    )
+                    .service(
+                        web::scope("/series")
+                            .service(series::fetch_series)
+                            .service(series::modify_series)
+                            .service(series::remove_series)
+                    )
+            )
+    ).await;
+
+    // Create user
+    let mut login_resps = Vec::new();
+    for (username, email) in [("series", "series@gmail.com"), ("series2", "series2@gmail.com")] {
+        let req = test::TestRequest::post().uri("/user/register")
+            .insert_header(ContentType::form_url_encoded())
+            .set_form(UserSignUpForm{ username, password: "World123!!", email })
+            .to_request();
+        let _: UserSignUpReturnForm = test::call_and_read_body_json(&app, req).await;
+
+        let req = test::TestRequest::post().uri("/user/login")
+            .insert_header(ContentType::form_url_encoded())
+            .set_form(UserLoginForm{ username, password: "World123!!" })
+            .to_request();
+        let login_resp: UserLoginReturnForm = test::call_and_read_body_json(&app, req).await;
+        login_resps.push(login_resp);
+    }
+    let other_resp = login_resps.pop().unwrap();
+    let login_resp = login_resps.pop().unwrap();
+
+    let req = test::TestRequest::post()
+        .uri(format!("/user/{}/tag/register", login_resp.user.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .set_json(serde_json::json!({ "name": "chore" }))
+        .to_request();
+    let chore: SeriesTagReturnForm = test::call_and_read_body_json(&app, req).await;
+
+    let form = |name: &str, due_date: Option<&str>, completed: bool, recurrence: Option<&str>| RecurringTodoForm {
+        name: name.to_owned(),
+        contents: None,
+        due_date: due_date.map(date),
+        completed,
+        tags: Some(vec![chore.id]),
+        recurrence: recurrence.map(str::to_owned),
+    };
+
+    // CREATE RECURRING TODO //
+    // 2026-10-19 is Monday
+    let req = test::TestRequest::post()
+        .uri(format!("/user/{}/todo/register", login_resp.user.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .set_json(form("Clean room", Some("2026-10-19"), false, Some("RRULE:FREQ=WEEKLY;BYDAY=MO,TH")))
+        .to_request();
+    let first: RecurringTodoReturnForm = test::call_and_read_body_json(&app, req).await;
+    let series_id = first.series_id.unwrap();
+
+    // Invalid
+    let test_datas = [
+        // No due_date
+        (None, "FREQ=DAILY"),
+        // Unsupported freq
+        (Some("2026-10-19"), "FREQ=HOURLY"),
+        // No freq
+        (Some("2026-10-19"), "INTERVAL=2"),
+        // Invalid weekday
+        (Some("2026-10-19"), "FREQ=WEEKLY;BYDAY=XX"),
+        // Non-ASCII weekday
+        (Some("2026-10-19"), "FREQ=WEEKLY;BYDAY=aéb"),
+        // Zero interval
+        (Some("2026-10-19"), "FREQ=DAILY;INTERVAL=0"),
+        // COUNT with UNTIL
+        (Some("2026-10-19"), "FREQ=DAILY;COUNT=2;UNTIL=20270101"),
+        // BYMONTHDAY without monthly
+        (Some("2026-10-19"), "FREQ=YEARLY;BYMONTHDAY=1"),
+        // Unsupported part
+        (Some("2026-10-19"), "FREQ=DAILY;BYHOUR=9"),
+    ];
+    for (due_date, rrule) in test_datas {
+        let req = test::TestRequest::post()
+            .uri(format!("/user/{}/todo/register", login_resp.user.id).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+            .set_json(form("Invalid", due_date, false, Some(rrule)))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), 400, "{}", rrule);
+    }
+
+    // FETCH SERIES //
+    let req = test::TestRequest::get()
+        .uri(format!("/user/{}/series", login_resp.user.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .to_request();
+    let series_resp: Vec<SeriesReturnForm> = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(series_resp.len(), 1);
+    assert_eq!(series_resp[0].id, series_id);
+    assert_eq!(series_resp[0].rrule, "FREQ=WEEKLY;BYDAY=MO,TH");
+    assert_eq!(series_resp[0].dtstart, date("2026-10-19"));
+
+    // COMPLETE RECURRING TODO //
+    // (todo due_date, completed, expected due_dates of series)
+    let test_datas = [
+        // Next occurrence is created
+        ("2026-10-19", true, vec!["2026-10-19", "2026-10-22"]),
+        // Already completed
+        ("2026-10-19", true, vec!["2026-10-19", "2026-10-22"]),
+        // Completed again, next occurrence already exists
+        ("2026-10-19", false, vec!["2026-10-19", "2026-10-22"]),
+        ("2026-10-19", true, vec!["2026-10-19", "2026-10-22"]),
+        ("2026-10-22", true, vec!["2026-10-19", "2026-10-22", "2026-10-26"]),
+    ];
+    for (due_date, completed, expected) in test_datas {
+        let req = test::TestRequest::get()
+            .uri(format!("/user/{}/todo?series_id={}&sort=due_date&order=asc", login_resp.user.id, series_id).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+            .to_request();
+        let todo_resp: RecurringTodoPageReturnForm = test::call_and_read_body_json(&app, req).await;
+        let todo = todo_resp.items.iter().find(|t| t.due_date == Some(date(due_date))).unwrap();
+
+        let req = test::TestRequest::put()
+            .uri(format!("/user/{}/todo/{}", login_resp.user.id, todo.id).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+            .set_json(RecurringTodoForm{ tags: None, ..form("Clean room", Some(due_date), completed, None) })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert!(resp.status().is_success());
+
+        let req = test::TestRequest::get()
+            .uri(format!("/user/{}/todo?series_id={}&sort=due_date&order=asc", login_resp.user.id, series_id).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+            .to_request();
+        let todo_resp: RecurringTodoPageReturnForm = test::call_and_read_body_json(&app, req).await;
+        let due_dates: Vec<NaiveDate> = todo_resp.items.iter().filter_map(|t| t.due_date).collect();
+        let expected: Vec<NaiveDate> = expected.into_iter().map(date).collect();
+
+        assert_eq!(due_dates, expected, "{} -> {}", due_date, completed);
+
+        // Next occurrence keeps name and tags
+        let last = todo_resp.items.last().unwrap();
+        assert_eq!(last.name, "Clean room");
+        assert_eq!(last.tags.iter().map(|t| t.id).collect::<Vec<i32>>(), vec![chore.id]);
+    }
+
+    // MODIFY SERIES //
+    // Last day of every month
+    let req = test::TestRequest::put()
+        .uri(format!("/user/{}/series/{}", login_resp.user.id, series_id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .set_json(SeriesForm{ rrule: "freq=monthly;bymonthday=-1".to_owned() })
+        .to_request();
+    let series_resp: SeriesReturnForm = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(series_resp.rrule, "FREQ=MONTHLY;BYMONTHDAY=-1");
+
+    let req = test::TestRequest::get()
+        .uri(format!("/user/{}/todo?series_id={}&completed=false", login_resp.user.id, series_id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .to_request();
+    let todo_resp: RecurringTodoPageReturnForm = test::call_and_read_body_json(&app, req).await;
+    let open = todo_resp.items[0].clone();
+
+    let req = test::TestRequest::put()
+        .uri(format!("/user/{}/todo/{}", login_resp.user.id, open.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .set_json(form("Clean room", Some("2026-10-26"), true, None))
+        .to_request();
+    test::call_service(&app, req).await;
+
+    let req = test::TestRequest::get()
+        .uri(format!("/user/{}/todo?series_id={}&completed=false", login_resp.user.id, series_id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .to_request();
+    let todo_resp: RecurringTodoPageReturnForm = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(todo_resp.total, 1);
+    assert_eq!(todo_resp.items[0].due_date, Some(date("2026-10-31")));
+
+    // Invalid rule
+    let req = test::TestRequest::put()
+        .uri(format!("/user/{}/series/{}", login_resp.user.id, series_id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .set_json(SeriesForm{ rrule: "FREQ=MONTHLY;BYMONTHDAY=32".to_owned() })
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), 400);
+
+    // Other user's series
+    let req = test::TestRequest::put()
+        .uri(format!("/user/{}/series/{}", other_resp.user.id, series_id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", other_resp.access_token)))
+        .set_json(SeriesForm{ rrule: "FREQ=DAILY".to_owned() })
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), 404);
+
+    // SERIES END //
+    // COUNT=2 ends after second todo
+    let req = test::TestRequest::post()
+        .uri(format!("/user/{}/todo/register", login_resp.user.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .set_json(form("Take pill", Some("2026-11-01"), false, Some("FREQ=DAILY;COUNT=2")))
+        .to_request();
+    let todo_resp: RecurringTodoReturnForm = test::call_and_read_body_json(&app, req).await;
+    let count_series_id = todo_resp.series_id.unwrap();
+
+    for due_date in ["2026-11-01", "2026-11-02"] {
+        let req = test::TestRequest::get()
+            .uri(format!("/user/{}/todo?series_id={}&completed=false", login_resp.user.id, count_series_id).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+            .to_request();
+        let todo_resp: RecurringTodoPageReturnForm = test::call_and_read_body_json(&app, req).await;
+
+        assert_eq!(todo_resp.items[0].due_date, Some(date(due_date)));
+
+        let req = test::TestRequest::put()
+            .uri(format!("/user/{}/todo/{}", login_resp.user.id, todo_resp.items[0].id).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+            .set_json(form("Take pill", Some(due_date), true, None))
+            .to_request();
+        test::call_service(&app, req).await;
+    }
+
+    let req = test::TestRequest::get()
+        .uri(format!("/user/{}/todo?series_id={}", login_resp.user.id, count_series_id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .to_request();
+    let todo_resp: RecurringTodoPageReturnForm = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(todo_resp.total, 2);
+
+    // REMOVE SERIES //
+    let req = test::TestRequest::delete()
+        .uri(format!("/user/{}/series/{}", login_resp.user.id, series_id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), 204);
+
+    // Todos are kept without series
+    let req = test::TestRequest::get()
+        .uri(format!("/user/{}/todo?q=Clean%20room&completed=false", login_resp.user.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .to_request();
+    let todo_resp: RecurringTodoPageReturnForm = test::call_and_read_body_json(&app, req).await;
+    let open = todo_resp.items[0].clone();
+
+    assert_eq!(todo_resp.total, 1);
+    assert_eq!(open.series_id, None);
+
+    // Completing stopped series todo creates nothing
+    let req = test::TestRequest::put()
+        .uri(format!("/user/{}/todo/{}", login_resp.user.id, open.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .set_json(form("Clean room", Some("2026-10-31"), true, None))
+        .to_request();
+    test::call_service(&app, req).await;
+
+    let req = test::TestRequest::get()
+        .uri(format!("/user/{}/todo?q=Clean%20room", login_resp.user.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .to_request();
+    let todo_resp: RecurringTodoPageReturnForm = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(todo_resp.total, 4);
+
+    // Already removed
+    let req = test::TestRequest::delete()
+        .uri(format!("/user/{}/series/{}", login_resp.user.id, series_id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), 404);
+
+    // Delete test users
+    // Series and todos also remove all cause by on_delete_cascade
+    for resp in [login_resp, other_resp] {
+        let req = test::TestRequest::delete()
+            .uri(format!("/user/{}", resp.user.id).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", resp.access_token)))
+            .to_request();
+        test::call_service(&app, req).await;
+    }
+}
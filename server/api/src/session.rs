@@ -0,0 +1,43 @@
+use actix_web::{
+    get, delete, HttpResponse, web
+};
+use serde::Serialize;
+use super::{auth, AppState};
+use entity::{
+    entities::sessions,
+    mutation::*
+};
+use myerror::ServerError;
+
+#[derive(Serialize)]
+struct SessionReturnForm {
+    #[serde(flatten)]
+    session: sessions::Model,
+    /// Session of the requesting token, always false for personal access token.
+    current: bool,
+}
+
+#[get("")]
+async fn fetch_sessions(
+    state: web::Data<AppState>,
+    user_id: web::Path<i32>,
+    req_data: Option<web::ReqData<auth::JwtClaim>>,
+) -> Result<HttpResponse, ServerError> {
+    let models = Mutation::get_sessions(&state.conn, &state.config.auth, user_id.into_inner()).await?;
+    let sid = req_data.map(|claim| claim.sid);
+    let resp: Vec<SessionReturnForm> = models.into_iter()
+        .map(|session| SessionReturnForm { current: Some(session.id) == sid, session })
+        .collect();
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+#[delete("/{session_id}")]
+async fn remove_session(
+    state: web::Data<AppState>,
+    path_para: web::Path<(i32, i32)>,
+) -> Result<HttpResponse, ServerError> {
+    let (user_id, session_id) = path_para.into_inner();
+    let revoked_at = Mutation::revoke_session(&state.conn, user_id, session_id).await?;
+    state.revocations.revoke_session(session_id, revoked_at);
+    Ok(HttpResponse::NoContent().finish())
+}
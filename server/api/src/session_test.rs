@@ -0,0 +1,177 @@
+#![allow(dead_code)]
+
+use actix_web::{
+    App, web, test, http::header::{ContentType, USER_AGENT},
+};
+use actix_web_httpauth::middleware::HttpAuthentication;
+use serde::Deserialize;
+use super::{user, auth, session};
+use super::user_test::*;
+use super::logout_test::status_of;
+
+#[derive(Deserialize, Debug)]
+struct SessionReturnForm {
+    id: i32,
+    user_id: i32,
+    user_agent: Option<String>,
+    ip: Option<String>,
+    current: bool,
+}
+
+#[actix_web::test]
+async fn test_session() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(db_connect().await.clone()))
+            .service(user::signup_user)
+            .service(user::login_user)
+            .service(user::refresh_user)
+            .service(
+                web::scope("/user/{user_id}")
+                    .wrap(HttpAuthentication::bearer(auth::jwt_validator))
+                    .service(user::withdrawal_user)
+                    .service(
+                        web::scope("/session")
+                            .service(session::fetch_sessions)
+                            .service(session::remove_session)
+                    )
+            )
+    ).await;
+
+    let mut user_ids = Vec::new();
+    for (username, email) in [("session", "session@gmail.com"), ("session2", "session2@gmail.com")] {
+        let req = test::TestRequest::post().uri("/user/register")
+            .insert_header(ContentType::form_url_encoded())
+            .set_form(UserSignUpForm{ username, password: "World123!!", email })
+            .to_request();
+        let signup_resp: UserSignUpReturnForm = test::call_and_read_body_json(&app, req).await;
+        user_ids.push(signup_resp.id);
+    }
+    let user_id = user_ids[0];
+
+    // Login on two devices
+    // (user agent, peer address)
+    let test_datas = [
+        ("Mozilla/5.0 (Macintosh)", "10.0.0.1:4000"),
+        ("TodoApp/1.0 (iPhone)", "10.0.0.2:5000"),
+    ];
+    let mut logins = Vec::new();
+    for (user_agent, addr) in test_datas {
+        let req = test::TestRequest::post().uri("/user/login")
+            .insert_header((USER_AGENT, user_agent))
+            .peer_addr(addr.parse().unwrap())
+            .insert_header(ContentType::form_url_encoded())
+            .set_form(UserLoginForm{ username: "session", password: "World123!!" })
+            .to_request();
+        let login_resp: UserLoginReturnForm = test::call_and_read_body_json(&app, req).await;
+        logins.push(login_resp);
+    }
+    let other_login = {
+        let req = test::TestRequest::post().uri("/user/login")
+            .insert_header(ContentType::form_url_encoded())
+            .set_form(UserLoginForm{ username: "session2", password: "World123!!" })
+            .to_request();
+        let login_resp: UserLoginReturnForm = test::call_and_read_body_json(&app, req).await;
+        login_resp
+    };
+
+    // FETCH SESSIONS //
+    let req = test::TestRequest::get()
+        .uri(format!("/user/{}/session", user_id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", logins[0].access_token)))
+        .to_request();
+    let sessions: Vec<SessionReturnForm> = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(sessions.len(), 2);
+    // Recently seen first
+    assert_eq!(sessions[0].user_agent.as_deref(), Some("TodoApp/1.0 (iPhone)"));
+    assert_eq!(sessions[0].ip.as_deref(), Some("10.0.0.2"));
+    assert!(!sessions[0].current);
+    assert_eq!(sessions[1].user_agent.as_deref(), Some("Mozilla/5.0 (Macintosh)"));
+    assert_eq!(sessions[1].ip.as_deref(), Some("10.0.0.1"));
+    assert!(sessions[1].current);
+    assert!(sessions.iter().all(|s| s.user_id == user_id));
+    let (mobile_id, desktop_id) = (sessions[0].id, sessions[1].id);
+
+    // Refreshed token stays in same session.
+    let req = test::TestRequest::post().uri("/user/refresh")
+        .insert_header(ContentType::form_url_encoded())
+        .set_form(RefreshForm { refresh_token: logins[0].refresh_token.clone() })
+        .to_request();
+    let refreshed: RefreshReturnForm = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::get()
+        .uri(format!("/user/{}/session", user_id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", refreshed.access_token)))
+        .to_request();
+    let sessions: Vec<SessionReturnForm> = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(sessions.len(), 2);
+    assert_eq!(sessions[0].id, desktop_id);
+    assert!(sessions[0].current);
+
+    // REVOKE SESSION //
+    let req = test::TestRequest::get()
+        .uri(format!("/user/{}/session", user_ids[1]).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", other_login.access_token)))
+        .to_request();
+    let other_sessions: Vec<SessionReturnForm> = test::call_and_read_body_json(&app, req).await;
+
+    // (user_id, session_id, access token, expected status)
+    let test_datas = [
+        // Session of other user
+        (user_id, other_sessions[0].id, &refreshed.access_token, 404),
+        // Not exist
+        (user_id, 0, &refreshed.access_token, 404),
+        (user_id, mobile_id, &refreshed.access_token, 204),
+        // Already revoked
+        (user_id, mobile_id, &refreshed.access_token, 404),
+    ];
+    for (user_id, session_id, token, status) in test_datas {
+        let req = test::TestRequest::delete()
+            .uri(format!("/user/{}/session/{}", user_id, session_id).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .to_request();
+        assert_eq!(status_of(test::try_call_service(&app, req).await), status);
+    }
+
+    // Tokens of revoked session are not accepted, other session is kept.
+    // (access token, expected status)
+    let test_datas = [
+        (&logins[1].access_token, 401),
+        (&refreshed.access_token, 200),
+    ];
+    for (token, status) in test_datas {
+        let req = test::TestRequest::get()
+            .uri(format!("/user/{}/session", user_id).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .to_request();
+        assert_eq!(status_of(test::try_call_service(&app, req).await), status);
+    }
+
+    let req = test::TestRequest::post().uri("/user/refresh")
+        .insert_header(ContentType::form_url_encoded())
+        .set_form(RefreshForm { refresh_token: logins[1].refresh_token.clone() })
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), 401);
+
+    let req = test::TestRequest::get()
+        .uri(format!("/user/{}/session", user_id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", refreshed.access_token)))
+        .to_request();
+    let sessions: Vec<SessionReturnForm> = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(sessions.len(), 1);
+    assert_eq!(sessions[0].id, desktop_id);
+
+    // Delete test users
+    for (user_id, token) in [(user_ids[0], &refreshed.access_token), (user_ids[1], &other_login.access_token)] {
+        let req = test::TestRequest::delete()
+            .uri(format!("/user/{}", user_id).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .to_request();
+        test::call_service(&app, req).await;
+    }
+}
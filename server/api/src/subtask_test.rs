@@ -0,0 +1,317 @@
+#![allow(dead_code)]
+
+use actix_web::{
+    App, web, test, http::header::ContentType,
+};
+use actix_web_httpauth::middleware::HttpAuthentication;
+use serde::{Serialize, Deserialize};
+use super::{user, auth, todo};
+use super::user_test::*;
+
+#[derive(Serialize, Clone)]
+struct SubtaskForm {
+    name: String,
+    contents: Option<String>,
+    completed: bool,
+    parent_id: Option<i32>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+struct SubtaskReturnForm {
+    id: i32,
+    name: String,
+    completed: bool,
+    parent_id: Option<i32>,
+}
+
+#[derive(Deserialize, Debug)]
+struct TodoTreeReturnForm {
+    id: i32,
+    name: String,
+    completed: bool,
+    parent_id: Option<i32>,
+    subtasks: Vec<TodoTreeReturnForm>,
+}
+
+#[derive(Deserialize)]
+struct SubtaskPageReturnForm {
+    items: Vec<SubtaskReturnForm>,
+    total: u64,
+}
+
+impl TodoTreeReturnForm {
+    /// (name, completed) of every todo in tree, preorder.
+    fn flatten(&self) -> Vec<(String, bool)> {
+        let mut result = vec![(self.name.clone(), self.completed)];
+        for subtask in &self.subtasks {
+            result.extend(subtask.flatten());
+        }
+        result
+    }
+}
+
+#[actix_web::test]
+async fn test_subtask() {
+    let mut state = db_connect().await;
+    state.config.todo.max_depth = 3;
+    state.config.todo.completion_roll_up = true;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(user::signup_user)
+            .service(user::login_user)
+            .service(
+                web::scope("/user/{user_id}")
+                    .wrap(HttpAuthentication::bearer(auth::jwt_validator))
+                    .service(user::modify_user)
+                    .service(user::withdrawal_user)
+                    .service(
+                        web::scope("/todo")
+                            .service(todo::fetch_todos)
+                            .service(todo::fetch_todo)
+                            .service(todo::create_todo)
+                            .service(todo::modify_todo)
+                            .service(todo::remove_todo)
+                    )
+            )
+    ).await;
+
+    // Create user
+    let mut login_resps = Vec::new();
+    for (username, email) in [("subtask", "subtask@gmail.com"), ("subtask2", "subtask2@gmail.com")] {
+        let req = test::TestRequest::post().uri("/user/register")
+            .insert_header(ContentType::form_url_encoded())
+            .set_form(UserSignUpForm{ username, password: "World123!!", email })
+            .to_request();
+        let _: UserSignUpReturnForm = test::call_and_read_body_json(&app, req).await;
+
+        let req = test::TestRequest::post().uri("/user/login")
+            .insert_header(ContentType::form_url_encoded())
+            .set_form(UserLoginForm{ username, password: "World123!!" })
+            .to_request();
+        let login_resp: UserLoginReturnForm = test::call_and_read_body_json(&app, req).await;
+        login_resps.push(login_resp);
+    }
+    let other_resp = login_resps.pop().unwrap();
+    let login_resp = login_resps.pop().unwrap();
+
+    let form = |name: &str, completed: bool, parent_id: Option<i32>| SubtaskForm {
+        name: name.to_owned(),
+        contents: None,
+        completed,
+        parent_id,
+    };
+
+    // CREATE SUBTASK //
+    // A - B - C
+    // E - F
+    let mut todos: Vec<SubtaskReturnForm> = Vec::new();
+    for (name, parent) in [("A", None), ("B", Some(0)), ("C", Some(1)), ("E", None), ("F", Some(3))] {
+        let req = test::TestRequest::post()
+            .uri(format!("/user/{}/todo/register", login_resp.user.id).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+            .set_json(form(name, false, parent.map(|i: usize| todos[i].id)))
+            .to_request();
+        let todo_resp: SubtaskReturnForm = test::call_and_read_body_json(&app, req).await;
+
+        assert_eq!(todo_resp.parent_id, parent.map(|i| todos[i].id));
+        todos.push(todo_resp);
+    }
+    let (a, b, c, e, f) = (todos[0].clone(), todos[1].clone(), todos[2].clone(), todos[3].clone(), todos[4].clone());
+
+    // Other user's todo
+    let req = test::TestRequest::post()
+        .uri(format!("/user/{}/todo/register", other_resp.user.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", other_resp.access_token)))
+        .set_json(form("Other", false, None))
+        .to_request();
+    let other_todo: SubtaskReturnForm = test::call_and_read_body_json(&app, req).await;
+
+    // Invalid parent
+    let test_datas = [
+        // Deeper than max_depth
+        c.id,
+        // Other user's todo
+        other_todo.id,
+        // Not exist
+        0,
+    ];
+    for parent_id in test_datas {
+        let req = test::TestRequest::post()
+            .uri(format!("/user/{}/todo/register", login_resp.user.id).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+            .set_json(form("D", false, Some(parent_id)))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), 422);
+    }
+
+    // MODIFY PARENT //
+    // Invalid
+    // (todo, parent)
+    let test_datas = [
+        // Itself
+        (&a, a.id),
+        // Cycle
+        (&a, b.id),
+        (&a, c.id),
+        (&b, c.id),
+        // E - F under B is deeper than max_depth
+        (&e, b.id),
+    ];
+    for (todo, parent_id) in test_datas {
+        let req = test::TestRequest::put()
+            .uri(format!("/user/{}/todo/{}", login_resp.user.id, todo.id).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+            .set_json(form(&todo.name, false, Some(parent_id)))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), 422, "{} -> {}", todo.name, parent_id);
+    }
+
+    // Valid, A - (B - C, E - F)
+    let req = test::TestRequest::put()
+        .uri(format!("/user/{}/todo/{}", login_resp.user.id, e.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .set_json(form("E", false, Some(a.id)))
+        .to_request();
+    let todo_resp: SubtaskReturnForm = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(todo_resp.parent_id, Some(a.id));
+
+    // FETCH SUBTREE //
+    let req = test::TestRequest::get()
+        .uri(format!("/user/{}/todo/{}", login_resp.user.id, a.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .to_request();
+    let tree: TodoTreeReturnForm = test::call_and_read_body_json(&app, req).await;
+    let names: Vec<String> = tree.flatten().into_iter().map(|(name, _)| name).collect();
+
+    assert_eq!(names, vec!["A", "B", "C", "E", "F"]);
+    assert_eq!(tree.subtasks[1].subtasks[0].parent_id, Some(e.id));
+
+    // Other user's todo
+    let req = test::TestRequest::get()
+        .uri(format!("/user/{}/todo/{}", login_resp.user.id, other_todo.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), 404);
+
+    // FILTER BY PARENT //
+    // (query, expected todo names)
+    let test_datas = [
+        (format!("parent_id={}", a.id), vec!["B", "E"]),
+        ("root=true".to_owned(), vec!["A"]),
+        ("root=false".to_owned(), vec!["B", "C", "E", "F"]),
+    ];
+    for (query, names) in test_datas {
+        let req = test::TestRequest::get()
+            .uri(format!("/user/{}/todo?{}&sort=name&order=asc", login_resp.user.id, query).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+            .to_request();
+        let todo_resp: SubtaskPageReturnForm = test::call_and_read_body_json(&app, req).await;
+        let fetch_names: Vec<&str> = todo_resp.items.iter().map(|t| t.name.as_str()).collect();
+
+        assert_eq!(fetch_names, names, "{}", query);
+        assert_eq!(todo_resp.total, names.len() as u64, "{}", query);
+    }
+
+    // COMPLETION ROLL UP //
+    // (todo, completed, expected (name, completed) of tree)
+    let test_datas = [
+        // Every subtask of B is completed, A still has E
+        (&c, true, vec![("A", false), ("B", true), ("C", true), ("E", false), ("F", false)]),
+        // Completing E completes F, then A
+        (&e, true, vec![("A", true), ("B", true), ("C", true), ("E", true), ("F", true)]),
+        // Uncompleting F uncompletes E and A
+        (&f, false, vec![("A", false), ("B", true), ("C", true), ("E", false), ("F", false)]),
+        // Completing A completes every subtask
+        (&a, true, vec![("A", true), ("B", true), ("C", true), ("E", true), ("F", true)]),
+    ];
+    for (todo, completed, expected) in test_datas {
+        let req = test::TestRequest::put()
+            .uri(format!("/user/{}/todo/{}", login_resp.user.id, todo.id).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+            .set_json(form(&todo.name, completed, if todo.id == e.id { Some(a.id) } else { todo.parent_id }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert!(resp.status().is_success());
+
+        let req = test::TestRequest::get()
+            .uri(format!("/user/{}/todo/{}", login_resp.user.id, a.id).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+            .to_request();
+        let tree: TodoTreeReturnForm = test::call_and_read_body_json(&app, req).await;
+        let expected: Vec<(String, bool)> = expected.into_iter().map(|(n, c)| (n.to_owned(), c)).collect();
+
+        assert_eq!(tree.flatten(), expected, "{} -> {}", todo.name, completed);
+    }
+
+    // New incomplete subtask uncompletes parent
+    let req = test::TestRequest::post()
+        .uri(format!("/user/{}/todo/register", login_resp.user.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .set_json(form("G", false, Some(a.id)))
+        .to_request();
+    let g: SubtaskReturnForm = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::get()
+        .uri(format!("/user/{}/todo/{}", login_resp.user.id, a.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .to_request();
+    let tree: TodoTreeReturnForm = test::call_and_read_body_json(&app, req).await;
+
+    assert!(!tree.completed);
+
+    // Removing it completes parent again
+    let req = test::TestRequest::delete()
+        .uri(format!("/user/{}/todo/{}", login_resp.user.id, g.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), 204);
+
+    let req = test::TestRequest::get()
+        .uri(format!("/user/{}/todo/{}", login_resp.user.id, a.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .to_request();
+    let tree: TodoTreeReturnForm = test::call_and_read_body_json(&app, req).await;
+
+    assert!(tree.completed);
+
+    // REMOVE TODO //
+    // Subtasks are removed with parent
+    let req = test::TestRequest::delete()
+        .uri(format!("/user/{}/todo/{}", login_resp.user.id, a.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), 204);
+
+    for todo in [&b, &c, &e, &f] {
+        let req = test::TestRequest::get()
+            .uri(format!("/user/{}/todo/{}", login_resp.user.id, todo.id).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), 404);
+    }
+
+    // Delete test users
+    for resp in [login_resp, other_resp] {
+        let req = test::TestRequest::delete()
+            .uri(format!("/user/{}", resp.user.id).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", resp.access_token)))
+            .to_request();
+        test::call_service(&app, req).await;
+    }
+}
@@ -0,0 +1,54 @@
+use actix_web::{
+    get, post, put, delete, HttpResponse, web
+};
+use super::AppState;
+use entity::{
+    entities::tags,
+    mutation::*
+};
+use myerror::ServerError;
+
+#[get("")]
+async fn fetch_tags(
+    state: web::Data<AppState>,
+    user_id: web::Path<i32>,
+) -> Result<HttpResponse, ServerError> {
+    let models = Mutation::get_tags(&state.conn, user_id.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(models))
+}
+
+#[post("/register")]
+async fn create_tag(
+    state: web::Data<AppState>,
+    user_id: web::Path<i32>,
+    tag_data: web::Json<tags::Model>,
+) -> Result<HttpResponse, ServerError> {
+    let mut tag_data = tag_data.into_inner();
+    tag_data.user_id = user_id.into_inner();
+    let model = Mutation::create_tag(&state.conn, tag_data).await?;
+    Ok(HttpResponse::Created().json(model))
+}
+
+#[put("/{tag_id}")]
+async fn modify_tag(
+    state: web::Data<AppState>,
+    path_para: web::Path<(i32, i32)>,
+    tag_data: web::Json<tags::Model>,
+) -> Result<HttpResponse, ServerError> {
+    let (user_id, tag_id) = path_para.into_inner();
+    let mut tag_data = tag_data.into_inner();
+    tag_data.id = tag_id;
+    tag_data.user_id = user_id;
+    let model = Mutation::update_tag(&state.conn, tag_data).await?;
+    Ok(HttpResponse::Ok().json(model))
+}
+
+#[delete("/{tag_id}")]
+async fn remove_tag(
+    state: web::Data<AppState>,
+    path_para: web::Path<(i32, i32)>,
+) -> Result<HttpResponse, ServerError> {
+    let (user_id, tag_id) = path_para.into_inner();
+    Mutation::delete_tag(&state.conn, user_id, tag_id).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
@@ -0,0 +1,325 @@
+#![allow(dead_code)]
+
+use actix_web::{
+    App, web, test, http::header::ContentType,
+};
+use actix_web_httpauth::middleware::HttpAuthentication;
+use serde::{Serialize, Deserialize};
+use chrono::NaiveDateTime;
+use super::{user, auth, todo, tag};
+use super::user_test::*;
+
+#[derive(Serialize, Clone)]
+struct TagForm {
+    name: String,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+struct TagReturnForm {
+    id: i32,
+    user_id: i32,
+    name: String,
+    created_at: NaiveDateTime,
+}
+
+#[derive(Serialize, Clone)]
+struct TaggedTodoForm {
+    name: String,
+    contents: Option<String>,
+    completed: bool,
+    tags: Option<Vec<i32>>,
+}
+
+#[derive(Deserialize)]
+struct TaggedTodoReturnForm {
+    id: i32,
+    name: String,
+    tags: Vec<TagReturnForm>,
+}
+
+#[derive(Deserialize)]
+struct TaggedTodoPageReturnForm {
+    items: Vec<TaggedTodoReturnForm>,
+    total: u64,
+}
+
+#[actix_web::test]
+async fn test_tag() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(db_connect().await.clone()))
+            .service(user::signup_user)
+            .service(user::login_user)
+            .service(
+                web::scope("/user/{user_id}")
+                    .wrap(HttpAuthentication::bearer(auth::jwt_validator))
+                    .service(user::modify_user)
+                    .service(user::withdrawal_user)
+                    .service(
+                        web::scope("/todo")
+                            .service(todo::fetch_todos)
+                            .service(todo::create_todo)
+                            .service(todo::modify_todo)
+                            .service(todo::remove_todo)
+                    )
+                    .service(
+                        web::scope("/tag")
+                            .service(tag::fetch_tags)
+                            .service(tag::create_tag)
+                            .service(tag::modify_tag)
+                            .service(tag::remove_tag)
+                    )
+            )
+    ).await;
+
+    // Create user
+    let mut login_resps = Vec::new();
+    for (username, email) in [("tag", "tag@gmail.com"), ("tag2", "tag2@gmail.com")] {
+        let req = test::TestRequest::post().uri("/user/register")
+            .insert_header(ContentType::form_url_encoded())
+            .set_form(UserSignUpForm{ username, password: "World123!!", email })
+            .to_request();
+        let _: UserSignUpReturnForm = test::call_and_read_body_json(&app, req).await;
+
+        let req = test::TestRequest::post().uri("/user/login")
+            .insert_header(ContentType::form_url_encoded())
+            .set_form(UserLoginForm{ username, password: "World123!!" })
+            .to_request();
+        let login_resp: UserLoginReturnForm = test::call_and_read_body_json(&app, req).await;
+        login_resps.push(login_resp);
+    }
+    let other_resp = login_resps.pop().unwrap();
+    let login_resp = login_resps.pop().unwrap();
+
+    // CREATE TAG //
+    // Valid
+    let mut tags = Vec::new();
+    for name in ["work", "home", "urgent"] {
+        let req = test::TestRequest::post()
+            .uri(format!("/user/{}/tag/register", login_resp.user.id).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+            .set_json(TagForm{ name: name.to_owned() })
+            .to_request();
+        let tag_resp: TagReturnForm = test::call_and_read_body_json(&app, req).await;
+
+        assert_eq!(tag_resp.name, name);
+        assert_eq!(tag_resp.user_id, login_resp.user.id);
+        tags.push(tag_resp);
+    }
+    let (work, home, urgent) = (tags[0].clone(), tags[1].clone(), tags[2].clone());
+
+    // Other user's tag
+    let req = test::TestRequest::post()
+        .uri(format!("/user/{}/tag/register", other_resp.user.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", other_resp.access_token)))
+        .set_json(TagForm{ name: "work".to_owned() })
+        .to_request();
+    let other_tag: TagReturnForm = test::call_and_read_body_json(&app, req).await;
+
+    // Same name
+    let req = test::TestRequest::post()
+        .uri(format!("/user/{}/tag/register", login_resp.user.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .set_json(TagForm{ name: "work".to_owned() })
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 409);
+    let problem: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!((problem["code"].as_str(), problem["field"].as_str()), (Some("TAG_NAME_TAKEN"), Some("name")));
+
+    // Invalid
+    let test_datas = [
+        // No name
+        TagForm{ name: "".to_owned() },
+        // Name over 50
+        TagForm{ name: "a".repeat(51) },
+    ];
+    for data in test_datas {
+        let req = test::TestRequest::post()
+            .uri(format!("/user/{}/tag/register", login_resp.user.id).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+            .set_json(data)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), 400);
+    }
+
+    // FETCH TAG //
+    let req = test::TestRequest::get()
+        .uri(format!("/user/{}/tag", login_resp.user.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .to_request();
+    let tag_resp: Vec<TagReturnForm> = test::call_and_read_body_json(&app, req).await;
+    let names: Vec<&str> = tag_resp.iter().map(|t| t.name.as_str()).collect();
+
+    assert_eq!(names, vec!["home", "urgent", "work"]);
+
+    // MODIFY TAG //
+    let req = test::TestRequest::put()
+        .uri(format!("/user/{}/tag/{}", login_resp.user.id, home.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .set_json(TagForm{ name: "house".to_owned() })
+        .to_request();
+    let tag_resp: TagReturnForm = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(tag_resp.id, home.id);
+    assert_eq!(tag_resp.name, "house");
+
+    // Other user's tag can not be modified
+    let req = test::TestRequest::put()
+        .uri(format!("/user/{}/tag/{}", login_resp.user.id, other_tag.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .set_json(TagForm{ name: "stolen".to_owned() })
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), 404);
+
+    // TAG TODO //
+    // (name, tags)
+    let test_datas = [
+        ("Write report", vec![work.id, urgent.id]),
+        ("Clean room", vec![home.id]),
+        ("Meeting", vec![work.id]),
+        ("Nothing", vec![]),
+    ];
+    let mut todos = Vec::new();
+    for (name, tag_ids) in test_datas {
+        let req = test::TestRequest::post()
+            .uri(format!("/user/{}/todo/register", login_resp.user.id).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+            .set_json(TaggedTodoForm{
+                name: name.to_owned(),
+                contents: None,
+                completed: false,
+                tags: Some(tag_ids.clone()),
+            })
+            .to_request();
+        let todo_resp: TaggedTodoReturnForm = test::call_and_read_body_json(&app, req).await;
+        let mut resp_ids: Vec<i32> = todo_resp.tags.iter().map(|t| t.id).collect();
+        resp_ids.sort();
+        let mut tag_ids = tag_ids;
+        tag_ids.sort();
+
+        assert_eq!(resp_ids, tag_ids);
+        todos.push(todo_resp);
+    }
+
+    // Invalid tag
+    for tag_id in [other_tag.id, 0] {
+        let req = test::TestRequest::post()
+            .uri(format!("/user/{}/todo/register", login_resp.user.id).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+            .set_json(TaggedTodoForm{
+                name: "Invalid tag".to_owned(),
+                contents: None,
+                completed: false,
+                tags: Some(vec![tag_id]),
+            })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), 422);
+    }
+
+    // Modify without tags keeps tags
+    let req = test::TestRequest::put()
+        .uri(format!("/user/{}/todo/{}", login_resp.user.id, todos[0].id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .set_json(TaggedTodoForm{
+            name: "Write final report".to_owned(),
+            contents: None,
+            completed: false,
+            tags: None,
+        })
+        .to_request();
+    let todo_resp: TaggedTodoReturnForm = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(todo_resp.name, "Write final report");
+    assert_eq!(todo_resp.tags.len(), 2);
+
+    // Modify with tags replaces tags
+    let req = test::TestRequest::put()
+        .uri(format!("/user/{}/todo/{}", login_resp.user.id, todos[3].id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .set_json(TaggedTodoForm{
+            name: "Nothing".to_owned(),
+            contents: None,
+            completed: false,
+            tags: Some(vec![urgent.id]),
+        })
+        .to_request();
+    let todo_resp: TaggedTodoReturnForm = test::call_and_read_body_json(&app, req).await;
+    let names: Vec<&str> = todo_resp.tags.iter().map(|t| t.name.as_str()).collect();
+
+    assert_eq!(names, vec!["urgent"]);
+
+    // FILTER BY TAG //
+    // (query, expected todo names)
+    let test_datas = [
+        (format!("tags={}", work.id), vec!["Meeting", "Write final report"]),
+        (format!("tags={},{}", work.id, home.id), vec!["Clean room", "Meeting", "Write final report"]),
+        (format!("tags={},{}&tag_mode=any", home.id, urgent.id), vec!["Clean room", "Nothing", "Write final report"]),
+        (format!("tags={},{}&tag_mode=all", work.id, urgent.id), vec!["Write final report"]),
+        (format!("tags={},{}&tag_mode=all", work.id, home.id), vec![]),
+    ];
+    for (query, names) in test_datas {
+        let req = test::TestRequest::get()
+            .uri(format!("/user/{}/todo?{}&sort=name&order=asc", login_resp.user.id, query).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+            .to_request();
+        let todo_resp: TaggedTodoPageReturnForm = test::call_and_read_body_json(&app, req).await;
+        let fetch_names: Vec<&str> = todo_resp.items.iter().map(|t| t.name.as_str()).collect();
+
+        assert_eq!(fetch_names, names, "{}", query);
+        assert_eq!(todo_resp.total, names.len() as u64, "{}", query);
+    }
+
+    // Invalid tag query
+    let req = test::TestRequest::get()
+        .uri(format!("/user/{}/todo?tags=work", login_resp.user.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), 400);
+
+    // REMOVE TAG //
+    let req = test::TestRequest::delete()
+        .uri(format!("/user/{}/tag/{}", login_resp.user.id, work.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), 204);
+
+    // Removed from todos
+    let req = test::TestRequest::get()
+        .uri(format!("/user/{}/todo?sort=name&order=asc", login_resp.user.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .to_request();
+    let todo_resp: TaggedTodoPageReturnForm = test::call_and_read_body_json(&app, req).await;
+    let meeting = todo_resp.items.iter().find(|t| t.name == "Meeting").unwrap();
+
+    assert!(meeting.tags.is_empty());
+
+    // Already removed
+    let req = test::TestRequest::delete()
+        .uri(format!("/user/{}/tag/{}", login_resp.user.id, work.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), 404);
+
+    // Delete test users
+    // Tags and todos also remove all cause by on_delete_cascade
+    for resp in [login_resp, other_resp] {
+        let req = test::TestRequest::delete()
+            .uri(format!("/user/{}", resp.user.id).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", resp.access_token)))
+            .to_request();
+        test::call_service(&app, req).await;
+    }
+}
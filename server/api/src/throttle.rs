@@ -0,0 +1,97 @@
+use std::{borrow::Borrow, collections::HashMap, hash::Hash, net::IpAddr, sync::Mutex, time::{Duration, Instant}};
+use myerror::ServerError;
+use myconfig::LoginConfig;
+
+struct Failures {
+    count: u32,
+    last_at: Instant,
+    locked_until: Option<Instant>,
+}
+
+/// Failures by key, forgotten when lockout passes after last failure or end of lock.
+struct FailureMap<K> {
+    entries: HashMap<K, Failures>,
+}
+
+impl<K: Eq + Hash> FailureMap<K> {
+    fn new() -> Self {
+        Self { entries: HashMap::new() }
+    }
+
+    fn locked_for<Q: Eq + Hash + ?Sized>(&self, key: &Q, now: Instant) -> Option<Duration>
+    where K: Borrow<Q> {
+        self.entries.get(key)
+            .and_then(|f| f.locked_until)
+            .filter(|until| *until > now)
+            .map(|until| until - now)
+    }
+
+    /// Count failure, locked time is given by number of failures.
+    fn fail(&mut self, key: K, now: Instant, lockout: Duration, lock: impl Fn(u32) -> Option<Duration>) {
+        self.entries.retain(|_, f| f.locked_until.unwrap_or(f.last_at) + lockout > now);
+        let failures = self.entries.entry(key)
+            .or_insert(Failures { count: 0, last_at: now, locked_until: None });
+        failures.count += 1;
+        failures.last_at = now;
+        failures.locked_until = lock(failures.count).map(|duration| now + duration);
+    }
+}
+
+/// Failed logins in memory, per username and per client IP.
+/// Each server counts failures of its own requests.
+pub struct LoginThrottle {
+    config: LoginConfig,
+    users: Mutex<FailureMap<String>>,
+    ips: Mutex<FailureMap<IpAddr>>,
+}
+
+impl LoginThrottle {
+    pub fn new(config: &LoginConfig) -> Self {
+        LoginThrottle { config: config.clone(), users: Mutex::new(FailureMap::new()), ips: Mutex::new(FailureMap::new()) }
+    }
+
+    /// 429 error if username or IP is locked.
+    /// Username which does not exist is locked same way, so lock does not tell it exists.
+    pub fn check(&self, username: &str, ip: Option<IpAddr>) -> Result<(), ServerError> {
+        let now = Instant::now();
+        let user_lock = self.users.lock().unwrap().locked_for(username, now);
+        let ip_lock = ip.and_then(|ip| self.ips.lock().unwrap().locked_for(&ip, now));
+        match user_lock.max(ip_lock) {
+            None => Ok(()),
+            Some(duration) => Err(ServerError::TooManyRequestsError {
+                code: "TOO_MANY_LOGIN_ATTEMPTS",
+                msg: "Too many login attempts",
+                detail: "Login is temporarily locked after failures".to_owned(),
+                // Seconds, rounded up.
+                retry_after: duration.as_secs() + u64::from(duration.subsec_nanos() > 0),
+            }),
+        }
+    }
+
+    /// Username is delayed exponentially after free attempts, then locked out.
+    /// IP is only locked out, many users can share it.
+    pub fn fail(&self, username: &str, ip: Option<IpAddr>) {
+        let now = Instant::now();
+        let config = &self.config;
+        self.users.lock().unwrap().fail(username.to_owned(), now, config.lockout, |count| {
+            if count >= config.max_failures {
+                Some(config.lockout)
+            } else if count >= config.free_attempts {
+                Some(config.backoff_base.saturating_mul(1 << (count - config.free_attempts).min(31)).min(config.lockout))
+            } else {
+                None
+            }
+        });
+        if let Some(ip) = ip {
+            self.ips.lock().unwrap().fail(ip, now, config.lockout, |count| {
+                (count >= config.ip_max_failures).then_some(config.lockout)
+            });
+        }
+    }
+
+    /// Failures of username are cleared by successful login.
+    /// Failures of IP are kept, so one known account does not reset guessing of others.
+    pub fn succeed(&self, username: &str) {
+        self.users.lock().unwrap().entries.remove(username);
+    }
+}
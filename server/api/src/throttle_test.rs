@@ -0,0 +1,120 @@
+#![allow(dead_code)]
+
+use std::time::Duration;
+use actix_web::{
+    App, web, test, rt, http::header::{self, ContentType},
+};
+use actix_web_httpauth::middleware::HttpAuthentication;
+use serde_json::Value;
+use super::{user, auth};
+use super::user_test::*;
+
+#[actix_web::test]
+async fn test_throttle() {
+    let mut config = test_config();
+    config.login.free_attempts = 2;
+    config.login.backoff_base = Duration::from_secs(1);
+    config.login.max_failures = 4;
+    config.login.ip_max_failures = 3;
+    config.login.lockout = Duration::from_secs(3);
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(app_state(config).await))
+            .service(user::signup_user)
+            .service(user::login_user)
+            .service(
+                web::scope("/user/{user_id}")
+                    .wrap(HttpAuthentication::bearer(auth::jwt_validator))
+                    .service(user::withdrawal_user)
+            )
+    ).await;
+
+    let req = test::TestRequest::post().uri("/user/register")
+        .insert_header(ContentType::form_url_encoded())
+        .set_form(UserSignUpForm{ username: "throttle", password: "World123!!", email: "throttle@gmail.com" })
+        .to_request();
+    let signup_resp: UserSignUpReturnForm = test::call_and_read_body_json(&app, req).await;
+    let user_id = signup_resp.id;
+
+    // GENERIC ERROR //
+    let mut bodies = Vec::new();
+    for (username, password) in [("throttle_nobody", "World123!!"), ("throttle", "World123!")] {
+        let req = test::TestRequest::post().uri("/user/login")
+            .insert_header(ContentType::form_url_encoded())
+            .set_form(UserLoginForm{ username, password })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), 401);
+        let body: Value = test::read_body_json(resp).await;
+        bodies.push(body);
+    }
+    assert_eq!(bodies[0], bodies[1]);
+
+    // USERNAME BACKOFF //
+    // Second failure of free attempts locks for backoff_base, doubled on every failure.
+    // (sleep before, password, expected status, expected Retry-After)
+    let test_datas = [
+        (0, "World123!", 401, None),
+        (0, "World123!!", 429, Some("1")),
+        (1100, "World123!", 401, None),
+        (0, "World123!!", 429, Some("2")),
+        // max_failures, locked out
+        (2100, "World123!", 401, None),
+        (0, "World123!!", 429, Some("3")),
+        // Success clears failures.
+        (3100, "World123!!", 200, None),
+        (0, "World123!", 401, None),
+        (0, "World123!!", 200, None),
+    ];
+    for (sleep, password, status, retry_after) in test_datas {
+        rt::time::sleep(Duration::from_millis(sleep)).await;
+        let req = test::TestRequest::post().uri("/user/login")
+            .insert_header(ContentType::form_url_encoded())
+            .set_form(UserLoginForm{ username: "throttle", password })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), status);
+        assert_eq!(resp.headers().get(header::RETRY_AFTER).map(|v| v.to_str().unwrap()), retry_after);
+    }
+
+    // IP LOCKOUT //
+    for username in ["throttle_ip1", "throttle_ip2", "throttle_ip3"] {
+        let req = test::TestRequest::post().uri("/user/login")
+            .peer_addr("10.0.0.1:50000".parse().unwrap())
+            .insert_header(ContentType::form_url_encoded())
+            .set_form(UserLoginForm{ username, password: "World123!!" })
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), 401);
+    }
+
+    // (client address, expected status)
+    let test_datas = [
+        ("10.0.0.1:50001", 429),
+        ("10.0.0.2:50000", 200),
+    ];
+    let mut access_token = String::new();
+    for (addr, status) in test_datas {
+        let req = test::TestRequest::post().uri("/user/login")
+            .peer_addr(addr.parse().unwrap())
+            .insert_header(ContentType::form_url_encoded())
+            .set_form(UserLoginForm{ username: "throttle", password: "World123!!" })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), status);
+        if resp.status() == 200 {
+            let login_resp: UserLoginReturnForm = test::read_body_json(resp).await;
+            access_token = login_resp.access_token;
+        }
+    }
+
+    // Delete test user
+    let req = test::TestRequest::delete()
+        .uri(format!("/user/{}", user_id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", access_token)))
+        .to_request();
+    test::call_service(&app, req).await;
+}
@@ -1,40 +1,39 @@
 use actix_web::{
     get, post, put, delete, HttpResponse, web
 };
-use serde::Deserialize;
-use super::{
-    AppState,
-    entity::{
-        entities::todos,
-        mutation::*
-    },
-};
+use super::AppState;
+use entity::mutation::*;
 use myerror::ServerError;
 
-#[derive(Deserialize)]
-struct Page {
-    page: u64
-}
-
 #[get("")]
 async fn fetch_todos(
     state: web::Data<AppState>,
     user_id: web::Path<i32>,
-    page: web::Query<Page>
+    query: web::Query<TodoQuery>
 ) -> Result<HttpResponse, ServerError> {
-    let models = Mutation::get_todo(&state.conn, user_id.into_inner(), page.page).await?;
-    Ok(HttpResponse::Ok().json(models))
+    let todo_page = Mutation::get_todo(&state.conn, user_id.into_inner(), query.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(todo_page))
+}
+
+#[get("/{todo_id}")]
+async fn fetch_todo(
+    state: web::Data<AppState>,
+    path_para: web::Path<(i32, i32)>,
+) -> Result<HttpResponse, ServerError> {
+    let (user_id, todo_id) = path_para.into_inner();
+    let todo_tree = Mutation::get_todo_tree(&state.conn, user_id, todo_id).await?;
+    Ok(HttpResponse::Ok().json(todo_tree))
 }
 
 #[post("/register")]
 async fn create_todo(
     state: web::Data<AppState>,
     user_id: web::Path<i32>,
-    todo_data: web::Json<todos::Model>,
+    todo_data: web::Json<TodoForm>,
 ) -> Result<HttpResponse, ServerError> {
     let mut todo_data = todo_data.into_inner();
-    todo_data.user_id = user_id.into_inner();
-    let model = Mutation::create_todo(&state.conn, todo_data).await?;
+    todo_data.todo.user_id = user_id.into_inner();
+    let model = Mutation::create_todo(&state.conn, &state.config.todo, todo_data).await?;
     Ok(HttpResponse::Created().json(model))
 }
 
@@ -42,22 +41,33 @@ async fn create_todo(
 async fn modify_todo(
     state: web::Data<AppState>,
     path_para: web::Path<(i32, i32)>,
-    todo_data: web::Json<todos::Model>,
+    todo_data: web::Json<TodoForm>,
 ) -> Result<HttpResponse, ServerError> {
     let (user_id, todo_id) = path_para.into_inner();
     let mut todo_data = todo_data.into_inner();
-    todo_data.id = todo_id;
-    todo_data.user_id = user_id;
-    let model = Mutation::update_todo(&state.conn, todo_data).await?;
+    todo_data.todo.id = todo_id;
+    todo_data.todo.user_id = user_id;
+    let model = Mutation::update_todo(&state.conn, &state.config.todo, todo_data).await?;
     Ok(HttpResponse::Created().json(model))
 }
 
+#[put("/{todo_id}/project")]
+async fn move_todo(
+    state: web::Data<AppState>,
+    path_para: web::Path<(i32, i32)>,
+    move_data: web::Json<MoveTodoForm>,
+) -> Result<HttpResponse, ServerError> {
+    let (user_id, todo_id) = path_para.into_inner();
+    let model = Mutation::move_todo(&state.conn, user_id, todo_id, move_data.project_id).await?;
+    Ok(HttpResponse::Ok().json(model))
+}
+
 #[delete("/{todo_id}")]
 async fn remove_todo(
     state: web::Data<AppState>,
     path_para: web::Path<(i32, i32)>,
 ) -> Result<HttpResponse, ServerError> {
     let (user_id, todo_id) = path_para.into_inner();
-    Mutation::delete_todo(&state.conn, user_id, todo_id).await?;
+    Mutation::delete_todo(&state.conn, &state.config.todo, user_id, todo_id).await?;
     Ok(HttpResponse::NoContent().finish())
 }
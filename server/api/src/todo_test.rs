@@ -5,7 +5,7 @@ use actix_web::{
 };
 use actix_web_httpauth::middleware::HttpAuthentication;
 use serde::{Serialize, Deserialize};
-use chrono::{NaiveDate, NaiveDateTime};
+use chrono::{DateTime, FixedOffset, NaiveDate};
 use super::{user, auth, todo};
 use super::user_test::*;
 
@@ -25,7 +25,18 @@ struct TodoReturnForm {
     contents: Option<String>,
     due_date: Option<NaiveDate>,
     completed: bool,
-    created_at: NaiveDateTime,
+    created_at: DateTime<FixedOffset>,
+}
+
+#[derive(Deserialize)]
+struct TodoPageReturnForm {
+    items: Vec<TodoReturnForm>,
+    total: u64,
+    page: u64,
+    per_page: u64,
+    total_pages: u64,
+    next_cursor: Option<String>,
+    prev_cursor: Option<String>,
 }
 
 #[actix_web::test]
@@ -195,8 +206,12 @@ async fn test_todo() {
             .uri(format!("/user/{}/todo?page={}", login_resp.user.id, i).as_str())
             .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
             .to_request();
-        let todo_resp: Vec<TodoReturnForm> = test::call_and_read_body_json(&app, req).await;
-        fetch_datas.extend(todo_resp);
+        let todo_resp: TodoPageReturnForm = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(todo_resp.total, 20);
+        assert_eq!(todo_resp.total_pages, 4);
+        assert_eq!(todo_resp.page, i);
+        assert_eq!(todo_resp.per_page, 5);
+        fetch_datas.extend(todo_resp.items);
     }
 
     // Check
@@ -210,6 +225,197 @@ async fn test_todo() {
         assert_eq!(a.due_date, b.due_date);
     }
 
+    // CURSOR PAGINATION //
+    // First page
+    let req = test::TestRequest::get()
+        .uri(format!("/user/{}/todo?cursor=", login_resp.user.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .to_request();
+    let first_page: TodoPageReturnForm = test::call_and_read_body_json(&app, req).await;
+
+    assert!(first_page.prev_cursor.is_none());
+    assert!(first_page.next_cursor.is_some());
+
+    // Todo inserted while paging does not shift next pages
+    let req = test::TestRequest::post()
+        .uri(format!("/user/{}/todo/register", login_resp.user.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .set_json(TodoForm{
+            name: "Inserted while paging".to_owned(),
+            contents: None,
+            due_date: None,
+            completed: true,
+        })
+        .to_request();
+    let inserted: TodoReturnForm = test::call_and_read_body_json(&app, req).await;
+
+    // Walk forward
+    let mut cursor_pages = vec![first_page.items.iter().map(|t| t.id).collect::<Vec<i32>>()];
+    let mut next_cursor = first_page.next_cursor;
+    let mut last_prev_cursor = None;
+    while let Some(cursor) = next_cursor {
+        let req = test::TestRequest::get()
+            .uri(format!("/user/{}/todo?cursor={}", login_resp.user.id, cursor).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+            .to_request();
+        let todo_resp: TodoPageReturnForm = test::call_and_read_body_json(&app, req).await;
+        cursor_pages.push(todo_resp.items.iter().map(|t| t.id).collect());
+        next_cursor = todo_resp.next_cursor;
+        last_prev_cursor = todo_resp.prev_cursor;
+    }
+    let cursor_ids: Vec<i32> = cursor_pages.concat();
+    let mock_ids: Vec<i32> = mock_datas.iter().rev().map(|t| t.id).collect();
+
+    assert_eq!(cursor_ids, mock_ids);
+    assert_eq!(cursor_pages.len(), 4);
+
+    // Walk backward from last page
+    let req = test::TestRequest::get()
+        .uri(format!("/user/{}/todo?cursor={}", login_resp.user.id, last_prev_cursor.unwrap()).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .to_request();
+    let todo_resp: TodoPageReturnForm = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(todo_resp.items.iter().map(|t| t.id).collect::<Vec<i32>>(), cursor_pages[2]);
+    assert!(todo_resp.next_cursor.is_some());
+    assert!(todo_resp.prev_cursor.is_some());
+
+    // Offset page also gives cursor
+    let req = test::TestRequest::get()
+        .uri(format!("/user/{}/todo?page=1", login_resp.user.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .to_request();
+    let todo_resp: TodoPageReturnForm = test::call_and_read_body_json(&app, req).await;
+
+    assert!(todo_resp.next_cursor.is_some());
+    assert!(todo_resp.prev_cursor.is_some());
+
+    let req = test::TestRequest::delete()
+        .uri(format!("/user/{}/todo/{}", login_resp.user.id, inserted.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .to_request();
+    test::call_service(&app, req).await;
+
+    // FILTER, SEARCH AND SORT TODO //
+    let test_datas = [
+        // Overdue
+        TodoForm{
+            name: "Buy groceries".to_owned(),
+            contents: Some("Milk and eggs".to_owned()),
+            due_date: Some(NaiveDate::from_ymd_opt(2000, 1, 1).unwrap()),
+            completed: false,
+        },
+        // No due date
+        TodoForm{
+            name: "Read book".to_owned(),
+            contents: None,
+            due_date: None,
+            completed: false,
+        },
+        // Far future, wildcard letter in contents
+        TodoForm{
+            name: "Pay bill".to_owned(),
+            contents: Some("Pay 100% of bill".to_owned()),
+            due_date: Some(NaiveDate::from_ymd_opt(2999, 1, 1).unwrap()),
+            completed: false,
+        },
+    ];
+    for data in test_datas {
+        let req = test::TestRequest::post()
+            .uri(format!("/user/{}/todo/register", login_resp.user.id).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+            .set_json(data)
+            .to_request();
+        let _: TodoReturnForm = test::call_and_read_body_json(&app, req).await;
+    }
+
+    // (query, expected names)
+    let test_datas = [
+        ("completed=false&sort=name&order=asc", vec!["Buy groceries", "Pay bill", "Read book"]),
+        ("overdue=true", vec!["Buy groceries"]),
+        ("overdue=false&completed=false&sort=name&order=asc", vec!["Pay bill", "Read book"]),
+        ("no_due_date=true", vec!["Read book"]),
+        ("due_after=2023-10-18", vec!["Pay bill"]),
+        ("due_before=2023-10-18", vec!["Buy groceries"]),
+        ("q=GROCERIES", vec!["Buy groceries"]),
+        ("q=eggs", vec!["Buy groceries"]),
+        ("q=%25", vec!["Pay bill"]),
+        ("completed=false&sort=due_date&order=desc", vec!["Pay bill", "Buy groceries", "Read book"]),
+    ];
+    for (query, names) in test_datas {
+        let req = test::TestRequest::get()
+            .uri(format!("/user/{}/todo?{}", login_resp.user.id, query).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+            .to_request();
+        let todo_resp: TodoPageReturnForm = test::call_and_read_body_json(&app, req).await;
+        let fetch_names: Vec<&str> = todo_resp.items.iter().map(|t| t.name.as_str()).collect();
+
+        assert_eq!(fetch_names, names, "{}", query);
+        assert_eq!(todo_resp.total, names.len() as u64, "{}", query);
+    }
+
+    // Cursor walk gives same order as one big page
+    for sort in ["sort=due_date&order=asc", "sort=due_date&order=desc", "sort=name&order=asc", "sort=created_at&order=desc"] {
+        let req = test::TestRequest::get()
+            .uri(format!("/user/{}/todo?{}&per_page=100", login_resp.user.id, sort).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+            .to_request();
+        let todo_resp: TodoPageReturnForm = test::call_and_read_body_json(&app, req).await;
+        let expected: Vec<i32> = todo_resp.items.iter().map(|t| t.id).collect();
+
+        let mut cursor_ids = Vec::new();
+        let mut cursor = Some("".to_owned());
+        while let Some(c) = cursor {
+            let req = test::TestRequest::get()
+                .uri(format!("/user/{}/todo?{}&per_page=2&cursor={}", login_resp.user.id, sort, c).as_str())
+                .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+                .to_request();
+            let todo_resp: TodoPageReturnForm = test::call_and_read_body_json(&app, req).await;
+            cursor_ids.extend(todo_resp.items.iter().map(|t| t.id));
+            cursor = todo_resp.next_cursor;
+        }
+
+        assert_eq!(cursor_ids, expected, "{}", sort);
+    }
+
+    // Cursor made by other sort
+    let req = test::TestRequest::get()
+        .uri(format!("/user/{}/todo?sort=name&per_page=2", login_resp.user.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .to_request();
+    let todo_resp: TodoPageReturnForm = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get()
+        .uri(format!("/user/{}/todo?sort=due_date&cursor={}", login_resp.user.id, todo_resp.next_cursor.unwrap()).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), 400);
+
+    // per_page is limited by server
+    let req = test::TestRequest::get()
+        .uri(format!("/user/{}/todo?per_page=1000", login_resp.user.id).as_str())
+        .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+        .to_request();
+    let todo_resp: TodoPageReturnForm = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(todo_resp.per_page, 100);
+    assert_eq!(todo_resp.total, 23);
+    assert_eq!(todo_resp.items.len(), 23);
+    assert_eq!(todo_resp.total_pages, 1);
+
+    // Invalid
+    let test_datas = ["per_page=0", "sort=unknown", "completed=maybe", "due_before=yesterday", "cursor=not-a-cursor"];
+    for query in test_datas {
+        let req = test::TestRequest::get()
+            .uri(format!("/user/{}/todo?{}", login_resp.user.id, query).as_str())
+            .insert_header(("Authorization", format!("Bearer {}", login_resp.access_token)))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), 400, "{}", query);
+    }
+
     // Delete test user
     // Todo list also remove all cause by on_delete_cascade
     let req = test::TestRequest::delete()
@@ -1,15 +1,15 @@
 use actix_web::{
-    post, put, delete, HttpResponse, web
+    post, put, delete, HttpRequest, HttpResponse, web, http::header
 };
 use sea_orm::TryIntoModel;
-use super::{
-    auth, AppState,
-    entity::{
-        entities::users,
-        mutation::*
-    },
+use chrono::DateTime;
+use super::{auth, AppState};
+use entity::{
+    entities::users,
+    mutation::*
 };
-use bcrypt;
+use notifier::Mail;
+use serde::{Deserialize, Serialize};
 use serde_json::json;
 use myerror::ServerError;
 
@@ -18,64 +18,321 @@ async fn signup_user(
     state: web::Data<AppState>, 
     user_form: web::Form<users::Model>
 ) -> Result<HttpResponse, ServerError> {
-    let resp = Mutation::create_user(&state.conn, user_form.into_inner()).await?;
+    let resp = Mutation::create_user(&state.conn, &state.hasher, user_form.into_inner()).await?;
+    let token = Mutation::create_email_verification_token(&state.conn, &state.config.auth, resp.id, &resp.email).await?;
+    send_verification_mail(&state, &resp.username, &resp.email, &token).await;
     Ok(HttpResponse::Created().json(resp))
 }
 
+/// Mail errors are only logged, user can request it again.
+async fn send_verification_mail(state: &AppState, username: &str, email: &str, token: &str) {
+    let auth = &state.config.auth;
+    let link = auth.email_verification_url.as_ref()
+        .map(|url| format!("\n{}\n", url.replace("{token}", token)))
+        .unwrap_or_default();
+    let mail = Mail {
+        to: email.to_owned(),
+        subject: "[Todo] Verify your email".to_owned(),
+        body: format!(
+            "Hello {},\n\nUse this token to verify your email within {} hours.\n\n{}\n{}\nIf you did not request it, ignore this mail.",
+            username, auth.email_verification_exp_hours, token, link
+        ),
+    };
+    if let Err(e) = state.mailer.send(&mail).await {
+        log::error!("Email verification mail error: {}", e);
+    }
+}
+
+#[derive(Deserialize)]
+struct VerifyEmailForm {
+    token: String
+}
+
+#[post("/user/email/verify")]
+async fn verify_email(
+    state: web::Data<AppState>,
+    verify_form: web::Form<VerifyEmailForm>
+) -> Result<HttpResponse, ServerError> {
+    let model = Mutation::verify_email(&state.conn, &verify_form.token).await?;
+    Ok(HttpResponse::Ok().json(model))
+}
+
 #[post("/user/login")]
 async fn login_user(
     state: web::Data<AppState>,
+    req: HttpRequest,
     login_form: web::Form<LoginForm>
 ) -> Result<HttpResponse, ServerError> {
+    let ip = req.peer_addr().map(|addr| addr.ip());
+    let username = login_form.username.clone();
+    state.throttle.check(&username, ip)?;
+
     // Get login user data by username.
     let login_password = login_form.password.clone();
     let user_data = Mutation::login(&state.conn, login_form.into_inner()).await?;
 
-    // User validation
-    match user_data {
-        // No user
-        None => {
-            Err(ServerError::UnauthorizedError { msg: "Username does not exist", detail: "No user exists".to_owned() })
+    // Password is checked even if user does not exist, so both failures take same time.
+    let hash_password = match &user_data {
+        Some(user) => user.password.as_str(),
+        None => state.hasher.dummy_hash().await?,
+    };
+    let verified = state.hasher.verify(&login_password, hash_password).await?;
+    let user = match user_data {
+        Some(user) if verified.valid => user,
+        // Same error for no user and wrong password, so usernames are not exposed.
+        _ => {
+            state.throttle.fail(&username, ip);
+            return Err(ServerError::UnauthorizedError { code: "INVALID_CREDENTIALS", msg: "Invalid username or password", detail: "Username or password is wrong".to_owned() })
         },
-        // User Exist
-        Some(user) => {
-            // Password check
-            if login_validate(login_password, &user.password).await? {
-                let token = auth::generate_token(user.id).await?;
-                return Ok(HttpResponse::Ok().json(json!({
-                    "user" : user,
-                    "access_token" : token
-                })))
-            }
-            Err(ServerError::UnauthorizedError { msg: "Wrong password", detail: "Password is not same".to_owned() })
+    };
+
+    // Outdated hash is upgraded while plain password is known.
+    // Login does not fail by it, old hash is still valid.
+    if verified.needs_rehash {
+        match state.hasher.hash(&login_password).await {
+            Ok(hash) => {
+                if let Err(e) = Mutation::update_password_hash(&state.conn, user.id, hash).await {
+                    log::error!("Password rehash error: {}", e);
+                }
+            },
+            Err(e) => log::error!("Password rehash error: {}", e),
         }
     }
+
+    // Two-factor user gets tokens in second step with TOTP code.
+    // Failures are cleared after second step, so code guessing is throttled too.
+    if user.totp_enabled_at.is_some() {
+        let mfa_token = auth::generate_mfa_token(&state.keys, &state.config.auth, user.id).await?;
+        return Ok(HttpResponse::Ok().json(json!({
+            "mfa_required" : true,
+            "mfa_token" : mfa_token
+        })))
+    }
+    state.throttle.succeed(&username);
+    start_session(&state, &req, user).await
+}
+
+#[derive(Deserialize)]
+struct MfaLoginForm {
+    mfa_token: String,
+    /// TOTP code or recovery code.
+    code: String
+}
+
+#[post("/user/login/mfa")]
+async fn login_mfa_user(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    mfa_form: web::Form<MfaLoginForm>
+) -> Result<HttpResponse, ServerError> {
+    let user_id = auth::verify_mfa_token(&state.keys, &mfa_form.mfa_token).await?;
+    let user = Mutation::get_user(&state.conn, user_id).await?;
+    let ip = req.peer_addr().map(|addr| addr.ip());
+    state.throttle.check(&user.username, ip)?;
+
+    if !Mutation::verify_second_factor(&state.conn, user_id, &mfa_form.code).await? {
+        state.throttle.fail(&user.username, ip);
+        return Err(ServerError::UnauthorizedError { code: "MFA_CODE_INVALID", msg: "Invalid code", detail: "Code is wrong or already used".to_owned() })
+    }
+    state.throttle.succeed(&user.username);
+    start_session(&state, &req, user).await
+}
+
+/// Account disabled or waiting for password reset by admin can not login or refresh.
+fn check_account(user: &users::Model) -> Result<(), ServerError> {
+    if user.disabled_at.is_some() {
+        return Err(ServerError::ForbiddenError { code: "ACCOUNT_DISABLED", msg: "Account disabled", detail: "Account is disabled by admin".to_owned() })
+    }
+    if user.password_reset_required {
+        return Err(ServerError::ForbiddenError { code: "PASSWORD_RESET_REQUIRED", msg: "Password reset required", detail: "Reset password by email to login".to_owned() })
+    }
+    Ok(())
+}
+
+/// Each login is a session of device.
+async fn start_session(state: &AppState, req: &HttpRequest, user: users::Model) -> Result<HttpResponse, ServerError> {
+    check_account(&user)?;
+    let user_agent = req.headers().get(header::USER_AGENT)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.chars().take(255).collect());
+    let ip = req.peer_addr().map(|addr| addr.ip().to_string());
+    let session = Mutation::create_session(&state.conn, user.id, user_agent, ip).await?;
+
+    let token = auth::generate_token(&state.keys, &state.config.auth, user.id, session.id, &user.role).await?;
+    let refresh_token = Mutation::create_refresh_token(&state.conn, &state.config.auth, user.id, session.id, None).await?;
+    Ok(HttpResponse::Ok().json(json!({
+        "user" : user,
+        "access_token" : token,
+        "refresh_token" : refresh_token
+    })))
+}
+
+#[derive(Deserialize)]
+struct RefreshForm {
+    refresh_token: String
+}
+
+#[post("/user/refresh")]
+async fn refresh_user(
+    state: web::Data<AppState>,
+    refresh_form: web::Form<RefreshForm>
+) -> Result<HttpResponse, ServerError> {
+    // Old refresh token is consumed, new one is issued in same family.
+    let (user_id, session_id, refresh_token) = Mutation::rotate_refresh_token(&state.conn, &state.config.auth, &refresh_form.refresh_token).await?;
+    let user = Mutation::get_user(&state.conn, user_id).await?;
+    check_account(&user)?;
+    let token = auth::generate_token(&state.keys, &state.config.auth, user_id, session_id, &user.role).await?;
+    Ok(HttpResponse::Ok().json(json!({
+        "access_token" : token,
+        "refresh_token" : refresh_token
+    })))
+}
+
+#[derive(Deserialize)]
+struct ForgotPasswordForm {
+    email: String
+}
+
+#[post("/user/password/forgot")]
+async fn forgot_password(
+    state: web::Data<AppState>,
+    forgot_form: web::Form<ForgotPasswordForm>
+) -> Result<HttpResponse, ServerError> {
+    // Same response whether email exists or not, so emails of users are not exposed.
+    if let Some((user, token)) = Mutation::create_password_reset_token(&state.conn, &state.config.auth, &forgot_form.email).await? {
+        send_password_reset_mail(&state, &user, &token, false).await;
+    }
+    Ok(HttpResponse::Accepted().finish())
+}
+
+/// Reset required by admin can not be ignored, so mail says so.
+/// Mail errors are only logged, user can request it again by forgot password.
+pub async fn send_password_reset_mail(state: &AppState, user: &users::Model, token: &str, required: bool) {
+    let auth = &state.config.auth;
+    let link = auth.password_reset_url.as_ref()
+        .map(|url| format!("\n{}\n", url.replace("{token}", token)))
+        .unwrap_or_default();
+    let (intro, outro) = if required {
+        ("Administrator requires you to reset your password, you can not login until then.\n", "")
+    } else {
+        ("", "If you did not request it, ignore this mail.")
+    };
+    let mail = Mail {
+        to: user.email.clone(),
+        subject: "[Todo] Password reset".to_owned(),
+        body: format!(
+            "Hello {},\n\n{}Use this token to reset your password within {} minutes.\n\n{}\n{}\n{}",
+            user.username, intro, auth.password_reset_exp_min, token, link, outro
+        ),
+    };
+    if let Err(e) = state.mailer.send(&mail).await {
+        log::error!("Password reset mail error: {}", e);
+    }
+}
+
+#[derive(Deserialize)]
+struct ResetPasswordForm {
+    token: String,
+    password: String
 }
 
-async fn login_validate(login_password: String, hash_password: &str) -> Result<bool, ServerError> {
-    bcrypt::verify(login_password, hash_password)
-        .map_err(|e|
-            ServerError::InternalServerError { msg: "Password decrypt error", detail: e.to_string() }
-        )
+#[post("/user/password/reset")]
+async fn reset_password(
+    state: web::Data<AppState>,
+    reset_form: web::Form<ResetPasswordForm>
+) -> Result<HttpResponse, ServerError> {
+    let ResetPasswordForm { token, password } = reset_form.into_inner();
+    let (user_id, valid_after) = Mutation::reset_password(&state.conn, &state.hasher, &token, password).await?;
+    // Every session is logged out.
+    state.revocations.revoke_all(user_id, valid_after);
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[derive(Serialize)]
+struct ModifyReturnForm {
+    #[serde(flatten)]
+    user: users::Model,
+    /// New email waiting for verification.
+    pending_email: Option<String>,
 }
 
 #[put("")]
 async fn modify_user(
     state: web::Data<AppState>,
-    req_data: web::ReqData<auth::JwtClaim>,
+    user_id: web::Path<i32>,
     user_form: web::Form<ModifyForm>
 ) -> Result<HttpResponse, ServerError> {
     let mut user_form = user_form.into_inner();
-    user_form.id = req_data.user_id;
-    let model = Mutation::update_user(&state.conn, user_form).await?;
-    Ok(HttpResponse::Ok().json(model.try_into_model().unwrap()))
+    user_form.id = user_id.into_inner();
+    let email = user_form.email.clone();
+    let user = Mutation::update_user(&state.conn, &state.hasher, user_form).await?.try_into_model().unwrap();
+
+    // Changed email is used after it is verified.
+    if email != user.email {
+        let token = Mutation::create_email_verification_token(&state.conn, &state.config.auth, user.id, &email).await?;
+        send_verification_mail(&state, &user.username, &email, &token).await;
+    }
+    let pending_email = Mutation::get_pending_email(&state.conn, user.id).await?
+        .filter(|pending| pending != &user.email);
+    Ok(HttpResponse::Ok().json(ModifyReturnForm { user, pending_email }))
+}
+
+#[post("/email/resend")]
+async fn resend_verification_email(
+    state: web::Data<AppState>,
+    user_id: web::Path<i32>,
+) -> Result<HttpResponse, ServerError> {
+    let user = Mutation::get_user(&state.conn, user_id.into_inner()).await?;
+    // Pending new email, or current email if it is not verified yet.
+    let email = match Mutation::get_pending_email(&state.conn, user.id).await? {
+        Some(email) => email,
+        None if user.email_verified_at.is_none() => user.email.clone(),
+        None => return Err(ServerError::ConflictError { code: "EMAIL_ALREADY_VERIFIED", msg: "Email already verified", detail: "No email is waiting for verification".to_owned(), field: None }),
+    };
+    let token = Mutation::create_email_verification_token(&state.conn, &state.config.auth, user.id, &email).await?;
+    send_verification_mail(&state, &user.username, &email, &token).await;
+    Ok(HttpResponse::Accepted().finish())
 }
 
 #[delete("")]
 async fn withdrawal_user(
+    state: web::Data<AppState>,
+    user_id: web::Path<i32>,
+) -> Result<HttpResponse, ServerError> {
+    Mutation::delete_user(&state.conn, user_id.into_inner()).await?;
+    Ok(HttpResponse::NoContent().finish())
+}   
+
+#[derive(Deserialize)]
+struct LogoutForm {
+    refresh_token: Option<String>
+}
+
+#[post("/logout")]
+async fn logout_user(
     state: web::Data<AppState>,
     req_data: web::ReqData<auth::JwtClaim>,
+    logout_form: Option<web::Form<LogoutForm>>
 ) -> Result<HttpResponse, ServerError> {
-    Mutation::delete_user(&state.conn, req_data.user_id).await?;
+    // Refresh token of this login can not issue new access token.
+    if let Some(refresh_token) = logout_form.and_then(|f| f.into_inner().refresh_token) {
+        Mutation::revoke_refresh_token(&state.conn, req_data.user_id, &refresh_token).await?;
+    }
+    let expires_at = DateTime::from_timestamp(req_data.exp as i64, 0).unwrap_or_default();
+    Mutation::revoke_access_token(&state.conn, req_data.user_id, &req_data.jti, expires_at).await?;
+    state.revocations.revoke(&req_data);
+    // Session of this login is ended.
+    let revoked_at = Mutation::revoke_session(&state.conn, req_data.user_id, req_data.sid).await?;
+    state.revocations.revoke_session(req_data.sid, revoked_at);
     Ok(HttpResponse::NoContent().finish())
-}   
\ No newline at end of file
+}
+
+#[post("/logout/all")]
+async fn logout_all_user(
+    state: web::Data<AppState>,
+    req_data: web::ReqData<auth::JwtClaim>,
+) -> Result<HttpResponse, ServerError> {
+    let valid_after = Mutation::revoke_all_tokens(&state.conn, req_data.user_id).await?;
+    state.revocations.revoke_all(req_data.user_id, valid_after);
+    Ok(HttpResponse::NoContent().finish())
+}
@@ -0,0 +1,99 @@
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use sea_orm::{*, prelude::{Date, DateTimeWithTimeZone}};
+use serde::{Deserialize, Serialize};
+use myerror::ServerError;
+use super::{
+    entities::todos,
+    mutation::{TodoSort, SortOrder},
+};
+
+/// Position in todo list for keyset pagination.
+/// Client receives it as opaque base64 string.
+#[derive(Serialize, Deserialize)]
+pub struct TodoCursor {
+    /// Sort of list which made this cursor, cursor can not be used with other sort.
+    pub sort: TodoSort,
+    pub order: SortOrder,
+
+    /// Sort key of the todo, None if due_date is null or sort is id.
+    pub key: Option<String>,
+    pub id: i32,
+
+    /// true: todos after this position, false: todos before this position.
+    pub forward: bool,
+}
+
+impl TodoCursor {
+    /// Make cursor pointing the todo.
+    pub fn new(sort: TodoSort, order: SortOrder, model: &todos::Model, forward: bool) -> Self {
+        let key = match sort {
+            TodoSort::Id => None,
+            TodoSort::DueDate => model.due_date.map(|d| d.to_string()),
+            TodoSort::CreatedAt => Some(model.created_at.to_rfc3339()),
+            TodoSort::Name => Some(model.name.clone()),
+        };
+        TodoCursor { sort, order, key, id: model.id, forward }
+    }
+
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).unwrap())
+    }
+
+    pub fn decode(cursor: &str) -> Result<Self, ServerError> {
+        let invalid = |detail: String| ServerError::BadRequestError { code: "INVALID_CURSOR", msg: "Invalid cursor", detail };
+
+        let raw = URL_SAFE_NO_PAD.decode(cursor)
+            .map_err(|e| invalid(e.to_string()))?;
+        serde_json::from_slice(&raw)
+            .map_err(|e| invalid(e.to_string()))
+    }
+
+    /// Condition selecting todos after (or before) this cursor.
+    /// Must be used with same order as `Mutation::get_todo`, due_date null is always last.
+    pub fn condition(&self) -> Result<Condition, ServerError> {
+        let invalid = |detail: String| ServerError::BadRequestError { code: "INVALID_CURSOR", msg: "Invalid cursor", detail };
+
+        // Direction of comparison in natural order of key.
+        let greater = self.forward == matches!(self.order, SortOrder::Asc);
+        let cmp = |col: todos::Column, value: Value| -> sea_query::SimpleExpr {
+            if greater { col.gt(value) } else { col.lt(value) }
+        };
+        let id_cmp = cmp(todos::Column::Id, self.id.into());
+
+        let (column, value): (todos::Column, Value) = match (self.sort, &self.key) {
+            (TodoSort::Id, _) => return Ok(Condition::all().add(id_cmp)),
+            (TodoSort::DueDate, None) => {
+                // Cursor is in no due date part, which is last.
+                return Ok(if self.forward {
+                    Condition::all()
+                        .add(todos::Column::DueDate.is_null())
+                        .add(id_cmp)
+                } else {
+                    Condition::any()
+                        .add(todos::Column::DueDate.is_not_null())
+                        .add(Condition::all().add(todos::Column::DueDate.is_null()).add(id_cmp))
+                })
+            },
+            (TodoSort::DueDate, Some(key)) => (
+                todos::Column::DueDate,
+                key.parse::<Date>().map_err(|e| invalid(e.to_string()))?.into(),
+            ),
+            (TodoSort::CreatedAt, Some(key)) => (
+                todos::Column::CreatedAt,
+                DateTimeWithTimeZone::parse_from_rfc3339(key).map_err(|e| invalid(e.to_string()))?.into(),
+            ),
+            (TodoSort::Name, Some(key)) => (todos::Column::Name, key.clone().into()),
+            (_, None) => return Err(invalid("No sort key".to_owned())),
+        };
+
+        let mut condition = Condition::any()
+            .add(cmp(column, value.clone()))
+            .add(Condition::all().add(column.eq(value)).add(id_cmp));
+
+        // No due date todos are after every dated todo.
+        if matches!(self.sort, TodoSort::DueDate) && self.forward {
+            condition = condition.add(todos::Column::DueDate.is_null());
+        }
+        Ok(condition)
+    }
+}
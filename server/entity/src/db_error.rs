@@ -0,0 +1,98 @@
+use sea_orm::{DbErr, RuntimeErr, SqlxPostgresError};
+use myerror::ServerError;
+
+/// Constraint violated by a statement, classified by SQLSTATE of Postgres.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Violation {
+    /// 23505, columns of the unique key.
+    Unique { constraint: String, columns: Vec<String> },
+    /// 23503, referenced is true when deleted or updated row is still referenced by other table.
+    ForeignKey { constraint: String, columns: Vec<String>, referenced: bool },
+    /// 23502
+    NotNull { column: String },
+    /// 23514
+    Check { constraint: String },
+}
+
+/// Unique constraints with own code and field of request.
+/// Other unique constraints are ALREADY_EXISTS with columns of the key.
+const UNIQUE_KEYS: [(&str, &str, &str); 3] = [
+    ("users_username_key", "USERNAME_TAKEN", "username"),
+    ("users_email_key", "EMAIL_TAKEN", "email"),
+    ("idx-tags-user_id-name", "TAG_NAME_TAKEN", "name"),
+];
+
+/// Constraint violation of database error, None for other errors.
+pub fn classify(e: &DbErr) -> Option<Violation> {
+    let pg = match e {
+        DbErr::Exec(RuntimeErr::SqlxError(e)) | DbErr::Query(RuntimeErr::SqlxError(e)) => {
+            e.as_database_error()?.try_downcast_ref::<SqlxPostgresError>()?
+        },
+        _ => return None,
+    };
+    let constraint = pg.constraint().unwrap_or_default().to_owned();
+    // Detail is like "Key (user_id, name)=(1, work) already exists."
+    let columns = pg.detail()
+        .and_then(|detail| detail.strip_prefix("Key (")?.split_once(")="))
+        .map(|(columns, _)| columns.split(", ").map(str::to_owned).collect())
+        .unwrap_or_default();
+
+    match pg.code() {
+        "23505" => Some(Violation::Unique { constraint, columns }),
+        "23503" => {
+            let referenced = pg.detail().is_some_and(|detail| detail.contains("is still referenced"));
+            Some(Violation::ForeignKey { constraint, columns, referenced })
+        },
+        "23502" => Some(Violation::NotNull { column: pg.column().unwrap_or_default().to_owned() }),
+        "23514" => Some(Violation::Check { constraint }),
+        _ => None,
+    }
+}
+
+/// Error of failed statement, msg is used for internal error.
+/// Constraint violations are client errors naming the field, values of the row are not shown.
+pub fn db_error(msg: &'static str, e: DbErr) -> ServerError {
+    let field = |columns: Vec<String>| (!columns.is_empty()).then(|| columns.join(", "));
+    match classify(&e) {
+        Some(Violation::Unique { constraint, columns }) => {
+            let (code, field) = match UNIQUE_KEYS.iter().find(|(name, ..)| *name == constraint) {
+                Some((_, code, field)) => (*code, Some(field.to_string())),
+                None => ("ALREADY_EXISTS", field(columns)),
+            };
+            ServerError::ConflictError {
+                code,
+                msg: "Already exists",
+                detail: format!("{} is already taken", field.as_deref().unwrap_or("Value")),
+                field,
+            }
+        },
+        Some(Violation::ForeignKey { referenced: true, .. }) => ServerError::ConflictError {
+            code: "STILL_REFERENCED",
+            msg: "Still referenced",
+            detail: "Resource is still referenced by other resources".to_owned(),
+            field: None,
+        },
+        Some(Violation::ForeignKey { columns, .. }) => {
+            let field = field(columns);
+            ServerError::UnprocessableEntityError {
+                code: "INVALID_REFERENCE",
+                msg: "Invalid reference",
+                detail: format!("{} refers to missing resource", field.as_deref().unwrap_or("Value")),
+                field,
+            }
+        },
+        Some(Violation::NotNull { column }) => ServerError::UnprocessableEntityError {
+            code: "FIELD_REQUIRED",
+            msg: "Missing field",
+            detail: format!("{} is required", column),
+            field: Some(column),
+        },
+        Some(Violation::Check { constraint }) => ServerError::UnprocessableEntityError {
+            code: "CHECK_VIOLATION",
+            msg: "Invalid value",
+            detail: format!("Value violates {}", constraint),
+            field: None,
+        },
+        None => ServerError::InternalServerError { msg, detail: e.to_string() },
+    }
+}
@@ -2,5 +2,19 @@
 
 pub mod prelude;
 
+pub mod admin_audit_logs;
+pub mod email_verification_tokens;
+pub mod notifications;
+pub mod password_reset_tokens;
+pub mod personal_access_tokens;
+pub mod projects;
+pub mod recovery_codes;
+pub mod refresh_tokens;
+pub mod reminders;
+pub mod revoked_tokens;
+pub mod series;
+pub mod sessions;
+pub mod tags;
+pub mod todo_tags;
 pub mod todos;
 pub mod users;
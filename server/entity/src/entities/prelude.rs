@@ -1,4 +1,18 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.11.2
 
+pub use super::admin_audit_logs::Entity as AdminAuditLogs;
+pub use super::email_verification_tokens::Entity as EmailVerificationTokens;
+pub use super::notifications::Entity as Notifications;
+pub use super::password_reset_tokens::Entity as PasswordResetTokens;
+pub use super::personal_access_tokens::Entity as PersonalAccessTokens;
+pub use super::projects::Entity as Projects;
+pub use super::recovery_codes::Entity as RecoveryCodes;
+pub use super::refresh_tokens::Entity as RefreshTokens;
+pub use super::reminders::Entity as Reminders;
+pub use super::revoked_tokens::Entity as RevokedTokens;
+pub use super::series::Entity as Series;
+pub use super::sessions::Entity as Sessions;
+pub use super::tags::Entity as Tags;
+pub use super::todo_tags::Entity as TodoTags;
 pub use super::todos::Entity as Todos;
 pub use super::users::Entity as Users;
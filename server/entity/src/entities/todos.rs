@@ -13,6 +13,17 @@ pub struct Model {
 
     #[serde(skip_deserializing)]
     pub user_id: i32,
+
+    /// None means inbox. Changed only by moving todo.
+    #[serde(skip_deserializing)]
+    pub project_id: Option<i32>,
+
+    /// Parent todo of subtask. None means top level todo.
+    pub parent_id: Option<i32>,
+
+    /// Recurring series of the todo. Set only by creating todo with recurrence.
+    #[serde(skip_deserializing)]
+    pub series_id: Option<i32>,
     
     #[validate(length(min=1, max=100))]
     pub name: String,
@@ -20,11 +31,14 @@ pub struct Model {
     #[validate(length(max=255))]
     pub contents: Option<String>,
 
+    /// Day of due. Todo without due_at is all day todo.
     pub due_date: Option<Date>,
+    /// Time of due. due_date is its date in user's time zone.
+    pub due_at: Option<DateTimeWithTimeZone>,
     pub completed: bool,
 
     #[serde(skip_deserializing)]
-    pub created_at: DateTime,
+    pub created_at: DateTimeWithTimeZone,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -37,6 +51,34 @@ pub enum Relation {
         on_delete = "Cascade"
     )]
     Users,
+    #[sea_orm(
+        belongs_to = "super::projects::Entity",
+        from = "Column::ProjectId",
+        to = "super::projects::Column::Id",
+        on_update = "NoAction",
+        on_delete = "SetNull"
+    )]
+    Projects,
+    #[sea_orm(
+        belongs_to = "Entity",
+        from = "Column::ParentId",
+        to = "Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    SelfRef,
+    #[sea_orm(
+        belongs_to = "super::series::Entity",
+        from = "Column::SeriesId",
+        to = "super::series::Column::Id",
+        on_update = "NoAction",
+        on_delete = "SetNull"
+    )]
+    Series,
+    #[sea_orm(has_many = "super::reminders::Entity")]
+    Reminders,
+    #[sea_orm(has_many = "super::todo_tags::Entity")]
+    TodoTags,
 }
 
 impl Related<super::users::Entity> for Entity {
@@ -45,4 +87,38 @@ impl Related<super::users::Entity> for Entity {
     }
 }
 
+impl Related<super::projects::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Projects.def()
+    }
+}
+
+impl Related<super::series::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Series.def()
+    }
+}
+
+impl Related<super::reminders::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Reminders.def()
+    }
+}
+
+impl Related<super::todo_tags::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::TodoTags.def()
+    }
+}
+
+impl Related<super::tags::Entity> for Entity {
+    fn to() -> RelationDef {
+        super::todo_tags::Relation::Tags.def()
+    }
+
+    fn via() -> Option<RelationDef> {
+        Some(super::todo_tags::Relation::Todos.def().rev())
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -2,21 +2,69 @@
 
 use sea_orm::entity::prelude::*;
 use serde::{Serialize, Deserialize};
-use lazy_static::lazy_static;
-use fancy_regex::Regex;
-use validator::{Validate, ValidationError};
+use validator::{Validate, ValidationError, ValidationErrors};
+use chrono_tz::Tz;
+use crate::validation::rule;
 
-lazy_static!{
-    static ref PASSWORD_REGEX: Regex = Regex::new(r"^(?=.*[a-z])(?=.*[A-Z])(?=.*\d)(?=.*[$@$!%*?&])[A-Za-z\d$@$!%*?&]{8,20}$").unwrap();
+/// Every role of user.
+pub const ROLE_USER: &str = "user";
+pub const ROLE_ADMIN: &str = "admin";
+pub const ROLES: [&str; 2] = [ROLE_USER, ROLE_ADMIN];
+
+pub const PASSWORD_MIN_LEN: usize = 8;
+pub const PASSWORD_MAX_LEN: usize = 20;
+pub const PASSWORD_SPECIALS: &str = "$@!%*?&";
+
+/// Password must be 8..=20 letters of alphabet, number and special($@!%*?&),
+/// with at least one lowercase, uppercase, number and special letter.
+/// Every broken rule is returned, so client can show all of them at once.
+pub fn password_errors(pwd: &str) -> Vec<ValidationError> {
+    let mut errors = Vec::new();
+    let len = pwd.chars().count();
+    if len < PASSWORD_MIN_LEN {
+        let mut e = rule("too_short", format!("Password must be at least {} letters", PASSWORD_MIN_LEN));
+        e.add_param("min".into(), &PASSWORD_MIN_LEN);
+        errors.push(e);
+    }
+    if len > PASSWORD_MAX_LEN {
+        let mut e = rule("too_long", format!("Password must be at most {} letters", PASSWORD_MAX_LEN));
+        e.add_param("max".into(), &PASSWORD_MAX_LEN);
+        errors.push(e);
+    }
+    if !pwd.chars().any(|c| c.is_ascii_lowercase()) {
+        errors.push(rule("missing_lowercase", "Password must contain a lowercase letter"));
+    }
+    if !pwd.chars().any(|c| c.is_ascii_uppercase()) {
+        errors.push(rule("missing_uppercase", "Password must contain an uppercase letter"));
+    }
+    if !pwd.chars().any(|c| c.is_ascii_digit()) {
+        errors.push(rule("missing_digit", "Password must contain a number"));
+    }
+    if !pwd.chars().any(|c| PASSWORD_SPECIALS.contains(c)) {
+        let mut e = rule("missing_special", format!("Password must contain one of {}", PASSWORD_SPECIALS));
+        e.add_param("allowed".into(), &PASSWORD_SPECIALS);
+        errors.push(e);
+    }
+    if pwd.chars().any(|c| !c.is_ascii_alphanumeric() && !PASSWORD_SPECIALS.contains(c)) {
+        let mut e = rule("invalid_character", format!("Password can only contain alphabet, number and {}", PASSWORD_SPECIALS));
+        e.add_param("allowed".into(), &PASSWORD_SPECIALS);
+        errors.push(e);
+    }
+    errors
 }
 
-fn password_validator(pwd: &str) -> Result<(), ValidationError> {
-    if !PASSWORD_REGEX.is_match(pwd).unwrap() {
-        return Err(ValidationError::new("Invalid password form"))
+/// Time zone must be IANA name. (e.g. Asia/Seoul)
+fn time_zone_validator(time_zone: &str) -> Result<(), ValidationError> {
+    if time_zone.parse::<Tz>().is_err() {
+        return Err(rule("invalid_time_zone", "Time zone must be IANA name (e.g. Asia/Seoul)"))
     }
     Ok(())
 }
 
+fn default_time_zone() -> String {
+    "UTC".to_owned()
+}
+
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize, Validate)]
 #[sea_orm(table_name = "users")]
 pub struct Model {
@@ -28,22 +76,83 @@ pub struct Model {
     #[validate(length(min=1, max=20))]
     pub username: String,
 
+    /// Checked by validate_all, refer to password_errors.
     #[serde(skip_serializing)]
-    #[validate(custom = "password_validator")]
     pub password: String,
 
     #[sea_orm(unique)]
     #[validate(email, length(max=255))]
     pub email: String,
 
+    /// "Today" and "overdue" of todos are decided in this time zone.
+    #[serde(default = "default_time_zone")]
+    #[validate(custom = "time_zone_validator")]
+    pub time_zone: String,
+
+    /// Access tokens issued before this time are revoked.
+    #[serde(skip)]
+    pub tokens_valid_after: Option<DateTimeWithTimeZone>,
+
+    /// Null until user confirms the email.
+    #[serde(skip_deserializing)]
+    pub email_verified_at: Option<DateTimeWithTimeZone>,
+
+    /// Base32 TOTP secret, set on enrollment.
+    #[serde(skip)]
+    pub totp_secret: Option<String>,
+
+    /// Login requires TOTP code if set.
+    #[serde(skip_deserializing)]
+    pub totp_enabled_at: Option<DateTimeWithTimeZone>,
+
+    /// Last used time step, same code can not be used twice.
+    #[serde(skip)]
+    pub totp_last_step: Option<i64>,
+
+    /// One of ROLES, admin can use admin API.
     #[serde(skip_deserializing)]
-    pub created_at: DateTime,
+    pub role: String,
+
+    /// Disabled by admin, user can not login and every token is revoked.
+    #[serde(skip_deserializing)]
+    pub disabled_at: Option<DateTimeWithTimeZone>,
+
+    /// Set by admin, user can not login until password is reset.
+    #[serde(skip_deserializing)]
+    pub password_reset_required: bool,
+
+    #[serde(skip_deserializing)]
+    pub created_at: DateTimeWithTimeZone,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
     #[sea_orm(has_many = "super::todos::Entity")]
     Todos,
+    #[sea_orm(has_many = "super::email_verification_tokens::Entity")]
+    EmailVerificationTokens,
+    #[sea_orm(has_many = "super::notifications::Entity")]
+    Notifications,
+    #[sea_orm(has_many = "super::password_reset_tokens::Entity")]
+    PasswordResetTokens,
+    #[sea_orm(has_many = "super::personal_access_tokens::Entity")]
+    PersonalAccessTokens,
+    #[sea_orm(has_many = "super::projects::Entity")]
+    Projects,
+    #[sea_orm(has_many = "super::recovery_codes::Entity")]
+    RecoveryCodes,
+    #[sea_orm(has_many = "super::refresh_tokens::Entity")]
+    RefreshTokens,
+    #[sea_orm(has_many = "super::reminders::Entity")]
+    Reminders,
+    #[sea_orm(has_many = "super::revoked_tokens::Entity")]
+    RevokedTokens,
+    #[sea_orm(has_many = "super::series::Entity")]
+    Series,
+    #[sea_orm(has_many = "super::sessions::Entity")]
+    Sessions,
+    #[sea_orm(has_many = "super::tags::Entity")]
+    Tags,
 }
 
 impl Related<super::todos::Entity> for Entity {
@@ -52,4 +161,90 @@ impl Related<super::todos::Entity> for Entity {
     }
 }
 
+impl Related<super::email_verification_tokens::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::EmailVerificationTokens.def()
+    }
+}
+
+impl Related<super::notifications::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Notifications.def()
+    }
+}
+
+impl Related<super::password_reset_tokens::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::PasswordResetTokens.def()
+    }
+}
+
+impl Related<super::personal_access_tokens::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::PersonalAccessTokens.def()
+    }
+}
+
+impl Related<super::projects::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Projects.def()
+    }
+}
+
+impl Related<super::recovery_codes::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::RecoveryCodes.def()
+    }
+}
+
+impl Related<super::refresh_tokens::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::RefreshTokens.def()
+    }
+}
+
+impl Related<super::reminders::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Reminders.def()
+    }
+}
+
+impl Related<super::revoked_tokens::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::RevokedTokens.def()
+    }
+}
+
+impl Related<super::series::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Series.def()
+    }
+}
+
+impl Related<super::sessions::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Sessions.def()
+    }
+}
+
+impl Related<super::tags::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Tags.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
+
+impl Model {
+    /// Validate fields with every password rule, use this instead of validate.
+    pub fn validate_all(&self) -> Result<(), ValidationErrors> {
+        let mut errors = self.validate().err().unwrap_or_default();
+        for e in password_errors(&self.password) {
+            errors.add("password", e);
+        }
+        if errors.is_empty() {
+            return Ok(())
+        }
+        Err(errors)
+    }
+}
@@ -1,9 +1,21 @@
-use super::entities::{users, todos};
-use bcrypt::DEFAULT_COST;
-use sea_orm::*;
-use serde::Deserialize;
+use super::{
+    entities::{admin_audit_logs, users, todos, refresh_tokens, tags, todo_tags, projects, series, reminders, notifications, revoked_tokens, sessions, personal_access_tokens, password_reset_tokens, email_verification_tokens, recovery_codes},
+    cursor::TodoCursor,
+    recurrence::RecurrenceRule,
+    totp::Totp,
+    password::HashPool,
+    db_error::db_error,
+    validation::{validation_error, field_error},
+};
+use std::collections::HashMap;
+use chrono::{DateTime, Utc, Duration, TimeZone};
+use chrono_tz::Tz;
+use sha2::{Sha256, Digest};
+use sea_orm::{*, prelude::{Date, DateTimeWithTimeZone}, sea_query::{extension::postgres::PgExpr, LockType, LockBehavior}};
+use serde::{Deserialize, Serialize};
 use validator::Validate;
 use myerror::ServerError;
+use myconfig::{AuthConfig, TodoConfig};
 
 /// Data for login.
 #[derive(Deserialize)]
@@ -20,73 +32,353 @@ pub struct ModifyForm {
 
     pub password: String,
     pub email: String,
+
+    /// IANA time zone name, not changed if None.
+    pub time_zone: Option<String>,
+}
+
+/// Default number of todos per page.
+const DEFAULT_TODOS_PER_PAGE: u64 = 5;
+
+/// Maximum number of todos per page, bigger per_page is reduced to this.
+const MAX_TODOS_PER_PAGE: u64 = 100;
+
+/// Default number of users or audit logs per page in admin API.
+const DEFAULT_ADMIN_PER_PAGE: u64 = 20;
+
+/// Maximum number of users or audit logs per page, bigger per_page is reduced to this.
+const MAX_ADMIN_PER_PAGE: u64 = 100;
+
+/// Number of recovery codes issued when two-factor is turned on.
+const RECOVERY_CODE_COUNT: usize = 10;
+
+/// Sort key of todo list.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TodoSort {
+    #[default]
+    Id,
+    DueDate,
+    CreatedAt,
+    Name,
+}
+
+/// Sort direction of todo list.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+/// How todos are matched with multiple tags.
+#[derive(Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TagMode {
+    /// Todo has at least one of tags.
+    #[default]
+    Any,
+    /// Todo has every tag.
+    All,
+}
+
+/// Query for todo list. Every filter is optional and combined with AND.
+#[derive(Deserialize, Default)]
+pub struct TodoQuery {
+    /// Offset pagination, ignored if cursor is given.
+    #[serde(default)]
+    pub page: u64,
+    pub per_page: Option<u64>,
+
+    /// Keyset pagination. Empty string means first page.
+    pub cursor: Option<String>,
+
+    pub completed: Option<bool>,
+
+    /// due_date < due_before
+    pub due_before: Option<Date>,
+    /// due_date > due_after
+    pub due_after: Option<Date>,
+    /// Not completed and due_at < now, or all day todo with due_date < today.
+    /// Today is decided in user's time zone.
+    pub overdue: Option<bool>,
+    /// due_date is today in user's time zone.
+    pub due_today: Option<bool>,
+    /// due_date is null.
+    pub no_due_date: Option<bool>,
+
+    /// Case insensitive substring of name or contents.
+    pub q: Option<String>,
+
+    /// Todos in the project.
+    pub project_id: Option<i32>,
+    /// Todos without project.
+    pub inbox: Option<bool>,
+
+    /// Subtasks of the todo.
+    pub parent_id: Option<i32>,
+    /// Top level todos, which are not subtask.
+    pub root: Option<bool>,
+
+    /// Todos of recurring series.
+    pub series_id: Option<i32>,
+
+    /// Comma separated tag ids.
+    pub tags: Option<String>,
+    #[serde(default)]
+    pub tag_mode: TagMode,
+
+    #[serde(default)]
+    pub sort: TodoSort,
+    #[serde(default)]
+    pub order: SortOrder,
+}
+
+/// Data for moving todo to other project.
+#[derive(Deserialize)]
+pub struct MoveTodoForm {
+    /// None means inbox.
+    pub project_id: Option<i32>,
+}
+
+/// Query for project list.
+#[derive(Deserialize)]
+pub struct ProjectQuery {
+    pub archived: Option<bool>,
+}
+
+/// What to do with todos of deleted project.
+#[derive(Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectTodos {
+    /// Move todos to inbox.
+    #[default]
+    Inbox,
+    /// Delete todos with project.
+    Cascade,
+}
+
+/// Query for project delete.
+#[derive(Deserialize)]
+pub struct DeleteProjectQuery {
+    #[serde(default)]
+    pub todos: ProjectTodos,
+}
+
+/// Data for create and modify todo.
+#[derive(Deserialize)]
+pub struct TodoForm {
+    #[serde(flatten)]
+    pub todo: todos::Model,
+
+    /// Tag ids of the todo. On modify, tags are not changed if None.
+    pub tags: Option<Vec<i32>>,
+
+    /// RRULE of recurring series starting from due_date. Used only on create.
+    pub recurrence: Option<String>,
+}
+
+/// Todo with its tags.
+#[derive(Serialize)]
+pub struct TodoWithTags {
+    #[serde(flatten)]
+    pub todo: todos::Model,
+    pub tags: Vec<tags::Model>,
+}
+
+/// Todo with its whole subtree.
+#[derive(Serialize)]
+pub struct TodoTree {
+    #[serde(flatten)]
+    pub todo: TodoWithTags,
+    pub subtasks: Vec<TodoTree>,
+}
+
+/// One page of todo list.
+#[derive(Serialize)]
+pub struct TodoPage {
+    pub items: Vec<TodoWithTags>,
+    pub total: u64,
+    pub page: u64,
+    pub per_page: u64,
+    pub total_pages: u64,
+
+    /// Cursor of next and previous page, None if there is no more todo.
+    pub next_cursor: Option<String>,
+    pub prev_cursor: Option<String>,
+}
+
+/// Query for inbox notification list.
+#[derive(Deserialize)]
+pub struct NotificationQuery {
+    pub read: Option<bool>,
+}
+
+/// Who does admin action, recorded in audit log.
+pub struct AuditActor {
+    /// None if action is done by server command.
+    pub admin_id: Option<i32>,
+    pub ip: Option<String>,
+}
+
+/// Query for admin user list. Every filter is optional and combined with AND.
+#[derive(Deserialize, Default)]
+pub struct UserQuery {
+    #[serde(default)]
+    pub page: u64,
+    pub per_page: Option<u64>,
+
+    /// Case insensitive substring of username or email.
+    pub q: Option<String>,
+    pub role: Option<String>,
+    pub disabled: Option<bool>,
+}
+
+#[derive(Serialize)]
+pub struct UserPage {
+    pub items: Vec<users::Model>,
+    pub total: u64,
+    pub page: u64,
+    pub per_page: u64,
+    pub total_pages: u64,
+}
+
+/// Query for admin audit logs, newest first. Every filter is optional and combined with AND.
+#[derive(Deserialize, Default)]
+pub struct AuditQuery {
+    #[serde(default)]
+    pub page: u64,
+    pub per_page: Option<u64>,
+
+    pub admin_id: Option<i32>,
+    pub target_user_id: Option<i32>,
+    pub action: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct AuditPage {
+    pub items: Vec<admin_audit_logs::Model>,
+    pub total: u64,
+    pub page: u64,
+    pub per_page: u64,
+    pub total_pages: u64,
+}
+
+/// Reminder to fire with its todo and user.
+pub struct DueReminder {
+    pub reminder: reminders::Model,
+    pub todo: todos::Model,
+    pub user: users::Model,
+}
+
+/// Revocations created after a time.
+pub struct Revocations {
+    pub tokens: Vec<revoked_tokens::Model>,
+    /// user_id and tokens_valid_after of users who logged out all sessions.
+    pub users: Vec<(i32, DateTimeWithTimeZone)>,
+    /// id and revoked_at of revoked sessions.
+    pub sessions: Vec<(i32, DateTimeWithTimeZone)>,
 }
 
 /// Communicate function class for database.
 pub struct Mutation;
 
 impl Mutation {
-    /// Password crypt for save in database.
-    fn password_hashing(pwd: String) -> Result<String, ServerError> {
-        bcrypt::hash(pwd, DEFAULT_COST)
-        .map_err(|e|
-            ServerError::InternalServerError { msg: "Password crypt error", detail: e.to_string() }
-        )
-    }
-
-    /// Validated password will be bcrypt.
+    /// Validated password will be hashed.
     /// Password must be 8..=20 letter, at least 1 upper, lower and special($@$!%*?&) letter, number.
-    pub async fn create_user(db: &DbConn, data: users::Model) -> Result<users::Model, ServerError> {
+    pub async fn create_user(db: &DbConn, hasher: &HashPool, data: users::Model) -> Result<users::Model, ServerError> {
         // Validation detail refer to users::Model.
-        data.validate()
-            .map_err(|e|
-                ServerError::BadRequestError { msg: "Validation error", detail: e.to_string() }
-            )?;
+        data.validate_all().map_err(validation_error)?;
 
-        // Bcrypt hash password.
-        let hashed_password = Self::password_hashing(data.password)?;
+        let hashed_password = hasher.hash(&data.password).await?;
 
         // Insert sign up user data to database.
         users::ActiveModel {
             username: Set(data.username),
             password: Set(hashed_password),
             email: Set(data.email),
+            time_zone: Set(data.time_zone),
             ..Default::default()
         }
         .insert(db).await
         .map_err(|e| 
-            ServerError::InternalServerError { msg: "Database insert error", detail: e.to_string() }
+            db_error("Database insert error", e)
         )
     }
     
+    /// Replace stored hash, used to upgrade outdated hash on login.
+    pub async fn update_password_hash(db: &DbConn, user_id: i32, hash: String) -> Result<(), ServerError> {
+        users::Entity::update_many()
+            .col_expr(users::Column::Password, sea_query::Expr::value(hash))
+            .filter(users::Column::Id.eq(user_id))
+            .exec(db).await
+            .map_err(|e|
+                db_error("Database update error", e)
+            )?;
+        Ok(())
+    }
+
+    /// Get user by user_id.
+    pub async fn get_user(db: &DbConn, user_id: i32) -> Result<users::Model, ServerError> {
+        users::Entity::find_by_id(user_id)
+            .one(db).await
+            .map_err(|e|
+                db_error("Database fetch error", e)
+            )?
+            .ok_or(ServerError::NotFound { code: "USER_NOT_FOUND", msg: "User not found" })
+    }
+
     /// Username can not change.
     /// Get user data by user_id.
     /// Modify data by form data. You can not change username.
-    pub async fn update_user(db: &DbConn, data: ModifyForm) -> Result<users::Model, ServerError> {
+    /// New email is only validated, it is changed when verified. (refer to verify_email)
+    pub async fn update_user(db: &DbConn, hasher: &HashPool, data: ModifyForm) -> Result<users::Model, ServerError> {
         // Get user
         let mut model = users::Entity::find_by_id(data.id)
             .one(db).await
             .map_err(|e|
-                ServerError::InternalServerError { msg: "Database fetch error", detail: e.to_string() }
+                db_error("Database fetch error", e)
             )?.unwrap();
         
         // Validate
+        let old_time_zone = model.time_zone.clone();
+        let old_email = model.email.clone();
         model.password = data.password;
         model.email = data.email;
-        model.validate()
-            .map_err(|e|
-                ServerError::BadRequestError { msg: "Validation error", detail: e.to_string() }
-            )?;
+        if let Some(time_zone) = data.time_zone {
+            model.time_zone = time_zone;
+        }
+        model.validate_all().map_err(validation_error)?;
 
-        // Bcrypt hash password.
-        model.password = Self::password_hashing(model.password)?;
+        // Old email stays active until new one is verified.
+        model.email = old_email;
+
+        model.password = hasher.hash(&model.password).await?;
 
         // Update
-        model.into_active_model().reset_all()
+        let model = model.into_active_model().reset_all()
         .update(db).await
         .map_err(|e|
-            ServerError::InternalServerError { msg: "Database update error", detail: e.to_string() }
-        )
+            db_error("Database update error", e)
+        )?;
+
+        // All day todo is due at midnight of new time zone.
+        if model.time_zone != old_time_zone {
+            let time_zone = model.time_zone.parse().unwrap_or(Tz::UTC);
+            let todos = todos::Entity::find()
+                .filter(todos::Column::UserId.eq(model.id))
+                .filter(todos::Column::DueAt.is_null())
+                .filter(todos::Column::DueDate.is_not_null())
+                .all(db).await
+                .map_err(|e|
+                    db_error("Database fetch error", e)
+                )?;
+            for todo in todos {
+                Self::reschedule_reminders(db, time_zone, &todo).await?;
+            }
+        }
+        Ok(model)
     }
 
     /// Get user data by username.
@@ -96,7 +388,7 @@ impl Mutation {
         .filter(users::Column::Username.eq(data.username))
         .one(db).await
         .map_err(|e|
-            ServerError::InternalServerError { msg: "Database fetch error", detail: e.to_string() }
+            db_error("Database fetch error", e)
         )
     }
 
@@ -104,107 +396,2245 @@ impl Mutation {
     pub async fn delete_user(db: &DbConn, user_id: i32) -> Result<DeleteResult, ServerError>{
         users::Entity::delete_by_id(user_id).exec(db).await
             .map_err(|e|
-                ServerError::InternalServerError { msg: "Database delete error", detail: e.to_string() }
+                db_error("Database delete error", e)
             )
     }
 
+    /// Time zone of user, UTC if user does not exist.
+    async fn user_time_zone<C: ConnectionTrait>(db: &C, user_id: i32) -> Result<Tz, ServerError> {
+        let user = users::Entity::find_by_id(user_id)
+            .one(db).await
+            .map_err(|e|
+                db_error("Database fetch error", e)
+            )?;
+        Ok(user.and_then(|u| u.time_zone.parse().ok()).unwrap_or(Tz::UTC))
+    }
+
+    /// due_date of todo with due_at is its date in user's time zone.
+    /// If both are given, they must be same day.
+    fn resolve_due_date(time_zone: Tz, due_date: Option<Date>, due_at: Option<DateTimeWithTimeZone>) -> Result<Option<Date>, ServerError> {
+        let Some(due_at) = due_at else { return Ok(due_date) };
+        let local_date = due_at.with_timezone(&time_zone).date_naive();
+        if due_date.is_some_and(|d| d != local_date) {
+            return Err(field_error("due_date", "due_date_mismatch", format!("due_date must be {} which is date of due_at in {}", local_date, time_zone)))
+        }
+        Ok(Some(local_date))
+    }
+
     /// Get todo.
-    pub async fn get_todo(db: &DbConn, user_id: i32, page: u64) -> Result<Vec<todos::Model>, ServerError> {
+    /// Filter, search and sort by query. Id is used as tie breaker so paging is stable.
+    /// Paging by page number(offset) or cursor(keyset), cursor is stable under insert and delete.
+    pub async fn get_todo(db: &DbConn, user_id: i32, query: TodoQuery) -> Result<TodoPage, ServerError> {
         // Each page's number of contents.
-        let contents_per_page = 5;
+        let per_page = query.per_page.unwrap_or(DEFAULT_TODOS_PER_PAGE).min(MAX_TODOS_PER_PAGE);
+        if per_page == 0 {
+            return Err(ServerError::BadRequestError { code: "INVALID_QUERY", msg: "Invalid query", detail: "per_page must be greater than 0".to_owned() })
+        }
 
-        // Filter previous pages and limit todo datas.
-        let start = page.saturating_mul(contents_per_page);
+        // Filter.
+        let mut condition = Condition::all()
+            .add(todos::Column::UserId.eq(user_id));
+        if let Some(completed) = query.completed {
+            condition = condition.add(todos::Column::Completed.eq(completed));
+        }
+        if let Some(date) = query.due_before {
+            condition = condition.add(todos::Column::DueDate.lt(date));
+        }
+        if let Some(date) = query.due_after {
+            condition = condition.add(todos::Column::DueDate.gt(date));
+        }
+        let now = Utc::now();
+        let today = now.with_timezone(&Self::user_time_zone(db, user_id).await?).date_naive();
+        if let Some(overdue) = query.overdue {
+            // Negating is not NULL-safe, so both sides are written out.
+            condition = condition.add(if overdue {
+                Condition::all()
+                    .add(todos::Column::Completed.eq(false))
+                    .add(
+                        Condition::any()
+                            .add(todos::Column::DueAt.lt(now))
+                            .add(
+                                Condition::all()
+                                    .add(todos::Column::DueAt.is_null())
+                                    .add(todos::Column::DueDate.lt(today))
+                            )
+                    )
+            } else {
+                Condition::any()
+                    .add(todos::Column::Completed.eq(true))
+                    .add(todos::Column::DueAt.gte(now))
+                    .add(
+                        Condition::all()
+                            .add(todos::Column::DueAt.is_null())
+                            .add(
+                                Condition::any()
+                                    .add(todos::Column::DueDate.is_null())
+                                    .add(todos::Column::DueDate.gte(today))
+                            )
+                    )
+            });
+        }
+        if let Some(due_today) = query.due_today {
+            condition = condition.add(if due_today {
+                Condition::all().add(todos::Column::DueDate.eq(today))
+            } else {
+                Condition::any()
+                    .add(todos::Column::DueDate.ne(today))
+                    .add(todos::Column::DueDate.is_null())
+            });
+        }
+        if let Some(no_due_date) = query.no_due_date {
+            condition = condition.add(if no_due_date {
+                todos::Column::DueDate.is_null()
+            } else {
+                todos::Column::DueDate.is_not_null()
+            });
+        }
+        if let Some(q) = query.q.filter(|q| !q.is_empty()) {
+            // Escape LIKE wildcard.
+            let pattern = format!("%{}%", q.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_"));
+            condition = condition.add(
+                Condition::any()
+                    .add(sea_query::Expr::col(todos::Column::Name).ilike(pattern.as_str()))
+                    .add(sea_query::Expr::col(todos::Column::Contents).ilike(pattern.as_str()))
+            );
+        }
+        if let Some(project_id) = query.project_id {
+            condition = condition.add(todos::Column::ProjectId.eq(project_id));
+        }
+        if let Some(inbox) = query.inbox {
+            condition = condition.add(if inbox {
+                todos::Column::ProjectId.is_null()
+            } else {
+                todos::Column::ProjectId.is_not_null()
+            });
+        }
+        if let Some(parent_id) = query.parent_id {
+            condition = condition.add(todos::Column::ParentId.eq(parent_id));
+        }
+        if let Some(series_id) = query.series_id {
+            condition = condition.add(todos::Column::SeriesId.eq(series_id));
+        }
+        if let Some(root) = query.root {
+            condition = condition.add(if root {
+                todos::Column::ParentId.is_null()
+            } else {
+                todos::Column::ParentId.is_not_null()
+            });
+        }
+        if let Some(tags) = query.tags.as_deref().filter(|t| !t.is_empty()) {
+            let mut tag_ids = tags.split(',')
+                .map(|t| t.trim().parse::<i32>())
+                .collect::<Result<Vec<i32>, _>>()
+                .map_err(|e|
+                    ServerError::BadRequestError { code: "INVALID_QUERY", msg: "Invalid query", detail: format!("tags: {}", e) }
+                )?;
+            tag_ids.sort_unstable();
+            tag_ids.dedup();
 
-        // Fetch models.
-        todos::Entity::find()
-            .filter(todos::Column::UserId.eq(user_id))
-            .order_by_desc(todos::Column::Id)
-            .limit(contents_per_page)
-            .offset(start)
-            .all(db).await
+            let mut tagged = sea_query::Query::select();
+            tagged.column(todo_tags::Column::TodoId)
+                .from(todo_tags::Entity)
+                .and_where(todo_tags::Column::TagId.is_in(tag_ids.clone()));
+            if let TagMode::All = query.tag_mode {
+                tagged.group_by_col(todo_tags::Column::TodoId)
+                    .and_having(
+                        sea_query::Expr::expr(sea_query::Expr::col(todo_tags::Column::TagId).count())
+                            .eq(tag_ids.len() as i64)
+                    );
+            }
+            condition = condition.add(todos::Column::Id.in_subquery(tagged.to_owned()));
+        }
+
+        let select = todos::Entity::find().filter(condition);
+        let counts = select.clone().paginate(db, per_page).num_items_and_pages().await
             .map_err(|e|
-                ServerError::InternalServerError { msg: "Database fetch error", detail: e.to_string() }
-            )
+                db_error("Database fetch error", e)
+            )?;
+
+        let cursor = match query.cursor.as_deref() {
+            None | Some("") => None,
+            Some(c) => {
+                let cursor = TodoCursor::decode(c)?;
+                if cursor.sort != query.sort || cursor.order != query.order {
+                    return Err(ServerError::BadRequestError { code: "INVALID_CURSOR", msg: "Invalid cursor", detail: "Cursor is made by other sort".to_owned() })
+                }
+                Some(cursor)
+            },
+        };
+        let forward = cursor.as_ref().is_none_or(|c| c.forward);
+
+        // Sort. Going backward, query is reversed and result is reversed again.
+        let order = match (query.order, forward) {
+            (SortOrder::Asc, true) | (SortOrder::Desc, false) => Order::Asc,
+            (SortOrder::Desc, true) | (SortOrder::Asc, false) => Order::Desc,
+        };
+        let nulls = if forward { Order::Asc } else { Order::Desc };
+        let mut select = match query.sort {
+            TodoSort::Id => select,
+            TodoSort::DueDate => select
+                // No due date is always last.
+                .order_by(sea_query::Expr::col(todos::Column::DueDate).is_null(), nulls)
+                .order_by(todos::Column::DueDate, order.clone()),
+            TodoSort::CreatedAt => select.order_by(todos::Column::CreatedAt, order.clone()),
+            TodoSort::Name => select.order_by(todos::Column::Name, order.clone()),
+        };
+        select = select.order_by(todos::Column::Id, order);
+
+        let make_cursor = |model: &TodoWithTags, forward| TodoCursor::new(query.sort, query.order, &model.todo, forward).encode();
+
+        // Offset pagination.
+        if query.cursor.is_none() {
+            let items = select.paginate(db, per_page).fetch_page(query.page).await
+                .map_err(|e|
+                    db_error("Database fetch error", e)
+                )?;
+            let items = Self::load_tags(db, items).await?;
+            let next_cursor = items.last()
+                .filter(|_| query.page + 1 < counts.number_of_pages)
+                .map(|m| make_cursor(m, true));
+            let prev_cursor = items.first()
+                .filter(|_| query.page > 0)
+                .map(|m| make_cursor(m, false));
+
+            return Ok(TodoPage {
+                items,
+                total: counts.number_of_items,
+                page: query.page,
+                per_page,
+                total_pages: counts.number_of_pages,
+                next_cursor,
+                prev_cursor,
+            })
+        }
+
+        // Keyset pagination. One more todo is fetched to know whether there is more.
+        if let Some(cursor) = &cursor {
+            select = select.filter(cursor.condition()?);
+        }
+        let mut items = select.limit(per_page + 1).all(db).await
+            .map_err(|e|
+                db_error("Database fetch error", e)
+            )?;
+        let has_more = items.len() as u64 > per_page;
+        items.truncate(per_page as usize);
+        if !forward {
+            items.reverse();
+        }
+        let items = Self::load_tags(db, items).await?;
+
+        // Moving forward, previous page exists if cursor is given.
+        let (has_next, has_prev) = if forward {
+            (has_more, cursor.is_some())
+        } else {
+            (true, has_more)
+        };
+        let next_cursor = items.last()
+            .filter(|_| has_next)
+            .map(|m| make_cursor(m, true));
+        let prev_cursor = items.first()
+            .filter(|_| has_prev)
+            .map(|m| make_cursor(m, false));
+
+        Ok(TodoPage {
+            items,
+            total: counts.number_of_items,
+            page: query.page,
+            per_page,
+            total_pages: counts.number_of_pages,
+            next_cursor,
+            prev_cursor,
+        })
     }
 
-    /// Create todo.
-    pub async fn create_todo(db: &DbConn, data: todos::Model) -> Result<todos::Model, ServerError> {
+    /// Create todo with tags.
+    pub async fn create_todo(db: &DbConn, config: &TodoConfig, data: TodoForm) -> Result<TodoWithTags, ServerError> {
         // Validation detail refer to todos::Model.
-        data.validate().map_err(|e|
-            ServerError::BadRequestError { msg: "Validation error", detail: e.to_string() }
-        )?;
+        let TodoForm { todo: mut data, tags, recurrence } = data;
+        data.validate().map_err(validation_error)?;
+        let time_zone = Self::user_time_zone(db, data.user_id).await?;
+        data.due_date = Self::resolve_due_date(time_zone, data.due_date, data.due_at)?;
+
+        let txn = db.begin().await
+            .map_err(|e|
+                db_error("Database transaction error", e)
+            )?;
+
+        if let Some(project_id) = data.project_id {
+            Self::find_project(&txn, data.user_id, project_id).await?;
+        }
+        if let Some(parent_id) = data.parent_id {
+            Self::check_parent(&txn, config, data.user_id, None, parent_id).await?;
+        }
+
+        let series_id = match recurrence {
+            Some(rrule) => {
+                let dtstart = data.due_date.ok_or_else(||
+                    field_error("due_date", "required", "Recurring todo needs due_date")
+                )?;
+                Some(Self::create_series(&txn, data.user_id, &rrule, dtstart).await?.id)
+            },
+            None => None,
+        };
 
         // Insert new todo data to database.
-        todos::ActiveModel{
+        let model = todos::ActiveModel{
             user_id: Set(data.user_id),
+            project_id: Set(data.project_id),
+            parent_id: Set(data.parent_id),
+            series_id: Set(series_id),
             name: Set(data.name),
             contents: Set(data.contents),
             due_date: Set(data.due_date),
+            due_at: Set(data.due_at),
             completed: Set(data.completed),
             ..Default::default()
         }
-        .insert(db).await
+        .insert(&txn).await
         .map_err(|e|
-            ServerError::InternalServerError { msg: "Database insert error", detail: e.to_string() }
-        )
+            db_error("Database insert error", e)
+        )?;
+
+        let tags = Self::set_todo_tags(&txn, &model, tags.unwrap_or_default()).await?;
+
+        if config.completion_roll_up {
+            if let Some(parent_id) = model.parent_id {
+                Self::roll_up_completion(&txn, parent_id).await?;
+            }
+        }
+
+        txn.commit().await
+            .map_err(|e|
+                db_error("Database transaction error", e)
+            )?;
+
+        Ok(TodoWithTags { todo: model, tags })
     }
 
     /// Update todo.
-    pub async fn update_todo(db: &DbConn, data: todos::Model) -> Result<todos::Model, ServerError> {
+    /// Tags are replaced if given.
+    /// Parent must not be the todo itself or its subtask, and tree must not be deeper than max_depth.
+    /// Completing todo of recurring series creates next occurrence.
+    pub async fn update_todo(db: &DbConn, config: &TodoConfig, data: TodoForm) -> Result<TodoWithTags, ServerError> {
+        let TodoForm { todo: data, tags, .. } = data;
+
         // Get todo by user_id and todo_id
         let model = todos::Entity::find_by_id(data.id)
             .filter(todos::Column::UserId.eq(data.user_id))
             .one(db).await
             .map_err(|e|
-                ServerError::InternalServerError { msg: "Database fetch error", detail: e.to_string() }
+                db_error("Database fetch error", e)
             )?;
         
         // No exist
         if model.is_none() {
-            return Err(ServerError::NotFound)
+            return Err(ServerError::NotFound { code: "TODO_NOT_FOUND", msg: "Todo not found" })
         }
 
         // Validate data
         let mut model = model.unwrap();
+        let old_parent_id = model.parent_id;
+        let old_due = (model.due_date, model.due_at);
+        let was_completed = model.completed;
         model.name = data.name;
         model.contents = data.contents;
-        model.due_date = data.due_date;
+        let time_zone = Self::user_time_zone(db, model.user_id).await?;
+        model.due_date = Self::resolve_due_date(time_zone, data.due_date, data.due_at)?;
+        model.due_at = data.due_at;
         model.completed = data.completed;
+        model.parent_id = data.parent_id;
 
-        model.validate()
-        .map_err(|e|
-            ServerError::BadRequestError { msg: "validation error", detail: e.to_string() }
-        )?;
+        model.validate().map_err(validation_error)?;
+
+        let txn = db.begin().await
+            .map_err(|e|
+                db_error("Database transaction error", e)
+            )?;
+
+        if let Some(parent_id) = model.parent_id.filter(|id| Some(*id) != old_parent_id) {
+            Self::check_parent(&txn, config, model.user_id, Some(model.id), parent_id).await?;
+        }
         
         // Update        
-        model.into_active_model().reset_all()
-        .update(db).await
+        let model = model.into_active_model().reset_all()
+        .update(&txn).await
         .map_err(|e|
-            ServerError::InternalServerError { msg: "Database update error", detail: e.to_string() }
-        )
+            db_error("Database update error", e)
+        )?;
+
+        let tags = match tags {
+            Some(tags) => Self::set_todo_tags(&txn, &model, tags).await?,
+            None => model.find_related(tags::Entity)
+                .order_by_asc(tags::Column::Name)
+                .all(&txn).await
+                .map_err(|e|
+                    db_error("Database fetch error", e)
+                )?,
+        };
+
+        if (model.due_date, model.due_at) != old_due {
+            Self::reschedule_reminders(&txn, time_zone, &model).await?;
+        }
+
+        if !was_completed && model.completed {
+            if let Some(series_id) = model.series_id {
+                Self::create_next_occurrence(&txn, &model, &tags, series_id).await?;
+            }
+        }
+
+        if config.completion_roll_up {
+            if model.completed {
+                Self::complete_subtree(&txn, model.id).await?;
+            }
+            if let Some(parent_id) = model.parent_id {
+                Self::roll_up_completion(&txn, parent_id).await?;
+            }
+            if let Some(parent_id) = old_parent_id.filter(|id| Some(*id) != model.parent_id) {
+                Self::roll_up_completion(&txn, parent_id).await?;
+            }
+        }
+
+        txn.commit().await
+            .map_err(|e|
+                db_error("Database transaction error", e)
+            )?;
+
+        Ok(TodoWithTags { todo: model, tags })
+    }
+
+    /// Create next todo of series after completed todo.
+    /// Nothing is created if series is over or next todo already exists.
+    async fn create_next_occurrence<C: ConnectionTrait>(db: &C, todo: &todos::Model, tags: &[tags::Model], series_id: i32) -> Result<(), ServerError> {
+        let Some(due_date) = todo.due_date else { return Ok(()) };
+        let series = series::Entity::find_by_id(series_id)
+            .one(db).await
+            .map_err(|e|
+                db_error("Database fetch error", e)
+            )?;
+        let Some(series) = series else { return Ok(()) };
+
+        let rule: RecurrenceRule = series.rrule.parse()
+            .map_err(|e|
+                ServerError::InternalServerError { msg: "Invalid saved rrule", detail: e }
+            )?;
+        let Some(next_date) = rule.next_after(series.dtstart, due_date) else { return Ok(()) };
+
+        // Next todo is due at same local time.
+        let time_zone = Self::user_time_zone(db, todo.user_id).await?;
+        let next_at = match todo.due_at {
+            Some(due_at) => {
+                let local = next_date.and_time(due_at.with_timezone(&time_zone).time());
+                let next_at = time_zone.from_local_datetime(&local).earliest()
+                    // Local time skipped by daylight saving.
+                    .or_else(|| time_zone.from_local_datetime(&(local + Duration::hours(1))).earliest());
+                next_at.map(|t| t.fixed_offset())
+            },
+            None => None,
+        };
+
+        // Completing same todo again does not make duplicate.
+        let exists = todos::Entity::find()
+            .filter(todos::Column::SeriesId.eq(series_id))
+            .filter(todos::Column::DueDate.gt(due_date))
+            .count(db).await
+            .map_err(|e|
+                db_error("Database fetch error", e)
+            )?;
+        if exists > 0 {
+            return Ok(())
+        }
+
+        let next = todos::ActiveModel {
+            user_id: Set(todo.user_id),
+            project_id: Set(todo.project_id),
+            parent_id: Set(todo.parent_id),
+            series_id: Set(Some(series_id)),
+            name: Set(todo.name.clone()),
+            contents: Set(todo.contents.clone()),
+            due_date: Set(Some(next_date)),
+            due_at: Set(next_at),
+            completed: Set(false),
+            ..Default::default()
+        }
+        .insert(db).await
+        .map_err(|e|
+            db_error("Database insert error", e)
+        )?;
+
+        Self::set_todo_tags(db, &next, tags.iter().map(|t| t.id).collect()).await?;
+
+        // Reminders relative to due repeat with todo.
+        let relative = reminders::Entity::find()
+            .filter(reminders::Column::TodoId.eq(todo.id))
+            .filter(reminders::Column::OffsetMinutes.is_not_null())
+            .all(db).await
+            .map_err(|e|
+                db_error("Database fetch error", e)
+            )?;
+        for reminder in relative {
+            reminders::ActiveModel {
+                user_id: Set(next.user_id),
+                todo_id: Set(next.id),
+                offset_minutes: Set(reminder.offset_minutes),
+                channel: Set(reminder.channel),
+                fire_at: Set(Self::reminder_fire_at(time_zone, &next, None, reminder.offset_minutes)),
+                ..Default::default()
+            }
+            .insert(db).await
+            .map_err(|e|
+                db_error("Database insert error", e)
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Move todo to project. None means inbox.
+    pub async fn move_todo(db: &DbConn, user_id: i32, todo_id: i32, project_id: Option<i32>) -> Result<TodoWithTags, ServerError> {
+        // Get todo by user_id and todo_id
+        let model = todos::Entity::find_by_id(todo_id)
+            .filter(todos::Column::UserId.eq(user_id))
+            .one(db).await
+            .map_err(|e|
+                db_error("Database fetch error", e)
+            )?
+            .ok_or(ServerError::NotFound { code: "TODO_NOT_FOUND", msg: "Todo not found" })?;
+
+        if let Some(project_id) = project_id {
+            Self::find_project(db, user_id, project_id).await?;
+        }
+
+        let mut model = model.into_active_model();
+        model.project_id = Set(project_id);
+        let model = model.update(db).await
+            .map_err(|e|
+                db_error("Database update error", e)
+            )?;
+
+        Ok(Self::load_tags(db, vec![model]).await?.remove(0))
+    }
+
+    /// Replace tags of todo. Every tag must be owned by todo's user.
+    async fn set_todo_tags<C: ConnectionTrait>(db: &C, todo: &todos::Model, mut tag_ids: Vec<i32>) -> Result<Vec<tags::Model>, ServerError> {
+        tag_ids.sort_unstable();
+        tag_ids.dedup();
+
+        // Check tags.
+        let tags = tags::Entity::find()
+            .filter(tags::Column::Id.is_in(tag_ids.clone()))
+            .filter(tags::Column::UserId.eq(todo.user_id))
+            .order_by_asc(tags::Column::Name)
+            .all(db).await
+            .map_err(|e|
+                db_error("Database fetch error", e)
+            )?;
+        if tags.len() != tag_ids.len() {
+            return Err(ServerError::UnprocessableEntityError { code: "INVALID_TAG", msg: "Invalid tag", detail: "Tag does not exist".to_owned(), field: Some("tags".to_owned()) })
+        }
+
+        // Replace.
+        todo_tags::Entity::delete_many()
+            .filter(todo_tags::Column::TodoId.eq(todo.id))
+            .exec(db).await
+            .map_err(|e|
+                db_error("Database delete error", e)
+            )?;
+        if !tag_ids.is_empty() {
+            todo_tags::Entity::insert_many(tag_ids.into_iter().map(|tag_id|
+                todo_tags::ActiveModel { todo_id: Set(todo.id), tag_id: Set(tag_id) }
+            ))
+            .exec(db).await
+            .map_err(|e|
+                db_error("Database insert error", e)
+            )?;
+        }
+
+        Ok(tags)
+    }
+
+    /// Get tags of each todo, order of todos is kept.
+    async fn load_tags(db: &DbConn, todos: Vec<todos::Model>) -> Result<Vec<TodoWithTags>, ServerError> {
+        if todos.is_empty() {
+            return Ok(Vec::new())
+        }
+        let tags = todos.load_many_to_many(tags::Entity, todo_tags::Entity, db).await
+            .map_err(|e|
+                db_error("Database fetch error", e)
+            )?;
+
+        Ok(todos.into_iter().zip(tags)
+            .map(|(todo, mut tags)| {
+                tags.sort_by(|a, b| a.name.cmp(&b.name));
+                TodoWithTags { todo, tags }
+            })
+            .collect())
     }
 
-    /// Delete todo.
-    pub async fn delete_todo(db: &DbConn, user_id: i32, todo_id: i32) -> Result<DeleteResult, ServerError> {
+    /// Delete todo. Subtasks are deleted together.
+    pub async fn delete_todo(db: &DbConn, config: &TodoConfig, user_id: i32, todo_id: i32) -> Result<DeleteResult, ServerError> {
         // Search delete model.
         let delete_model = todos::Entity::find_by_id(todo_id)
             .filter(todos::Column::UserId.eq(user_id))
             .one(db).await
             .map_err(|e|
-                ServerError::InternalServerError { msg: "Database delete error", detail: e.to_string() }
+                db_error("Database delete error", e)
             )?;
 
         // Not exist.
         if delete_model.is_none() {
-            return Err(ServerError::NotFound)
+            return Err(ServerError::NotFound { code: "TODO_NOT_FOUND", msg: "Todo not found" })
         }
 
+        let txn = db.begin().await
+            .map_err(|e|
+                db_error("Database transaction error", e)
+            )?;
+
         // Exist.
-        delete_model.unwrap()
-            .delete(db).await
+        let delete_model = delete_model.unwrap();
+        let parent_id = delete_model.parent_id;
+        let result = delete_model
+            .delete(&txn).await
             .map_err(|e|
-                ServerError::InternalServerError { msg: "Database delete error", detail: e.to_string() }
-            )
+                db_error("Database delete error", e)
+            )?;
+
+        if config.completion_roll_up {
+            if let Some(parent_id) = parent_id {
+                Self::roll_up_completion(&txn, parent_id).await?;
+            }
+        }
+
+        txn.commit().await
+            .map_err(|e|
+                db_error("Database transaction error", e)
+            )?;
+
+        Ok(result)
+    }
+
+    /// Get todo with its whole subtree. Subtasks are in id order.
+    pub async fn get_todo_tree(db: &DbConn, user_id: i32, todo_id: i32) -> Result<TodoTree, ServerError> {
+        let root = todos::Entity::find_by_id(todo_id)
+            .filter(todos::Column::UserId.eq(user_id))
+            .one(db).await
+            .map_err(|e|
+                db_error("Database fetch error", e)
+            )?
+            .ok_or(ServerError::NotFound { code: "TODO_NOT_FOUND", msg: "Todo not found" })?;
+
+        let mut models = vec![root];
+        models.extend(Self::subtree_levels(db, todo_id).await?.into_iter().flatten());
+        let mut models = Self::load_tags(db, models).await?.into_iter();
+
+        let root = models.next().unwrap();
+        let mut subtasks: HashMap<i32, Vec<TodoWithTags>> = HashMap::new();
+        for model in models {
+            subtasks.entry(model.todo.parent_id.unwrap_or_default()).or_default().push(model);
+        }
+
+        fn build(todo: TodoWithTags, subtasks: &mut HashMap<i32, Vec<TodoWithTags>>) -> TodoTree {
+            let children = subtasks.remove(&todo.todo.id).unwrap_or_default();
+            TodoTree {
+                todo,
+                subtasks: children.into_iter().map(|c| build(c, subtasks)).collect(),
+            }
+        }
+        Ok(build(root, &mut subtasks))
+    }
+
+    /// Subtasks of todo grouped by level, nearest level first.
+    async fn subtree_levels<C: ConnectionTrait>(db: &C, todo_id: i32) -> Result<Vec<Vec<todos::Model>>, ServerError> {
+        let mut levels = Vec::new();
+        let mut frontier = vec![todo_id];
+        while !frontier.is_empty() {
+            let level = todos::Entity::find()
+                .filter(todos::Column::ParentId.is_in(frontier))
+                .order_by_asc(todos::Column::Id)
+                .all(db).await
+                .map_err(|e|
+                    db_error("Database fetch error", e)
+                )?;
+            if level.is_empty() {
+                break
+            }
+            frontier = level.iter().map(|m| m.id).collect();
+            levels.push(level);
+        }
+        Ok(levels)
+    }
+
+    /// Check new parent of todo. todo_id is None when todo is being created.
+    /// Parent must be owned by user and must not make cycle or tree deeper than max_depth.
+    async fn check_parent<C: ConnectionTrait>(db: &C, config: &TodoConfig, user_id: i32, todo_id: Option<i32>, parent_id: i32) -> Result<(), ServerError> {
+        let invalid = |code: &'static str, detail: String| ServerError::UnprocessableEntityError { code, msg: "Invalid parent", detail, field: Some("parent_id".to_owned()) };
+
+        let parent = todos::Entity::find_by_id(parent_id)
+            .filter(todos::Column::UserId.eq(user_id))
+            .one(db).await
+            .map_err(|e|
+                db_error("Database fetch error", e)
+            )?
+            .ok_or_else(|| invalid("INVALID_PARENT", format!("Todo {} is not found", parent_id)))?;
+
+        // Level of parent, top level is 1.
+        let mut parent_level = 1;
+        let mut ancestor_id = parent.parent_id;
+        while let Some(id) = ancestor_id {
+            if Some(id) == todo_id {
+                return Err(invalid("PARENT_CYCLE", "Todo can not be subtask of its subtask".to_owned()))
+            }
+            parent_level += 1;
+            ancestor_id = todos::Entity::find_by_id(id)
+                .one(db).await
+                .map_err(|e|
+                    db_error("Database fetch error", e)
+                )?
+                .and_then(|m| m.parent_id);
+        }
+
+        // Height of todo's subtree, todo itself is 1.
+        let height = match todo_id {
+            Some(id) if id == parent_id => return Err(invalid("PARENT_CYCLE", "Todo can not be subtask of itself".to_owned())),
+            Some(id) => Self::subtree_levels(db, id).await?.len() as u32 + 1,
+            None => 1,
+        };
+
+        if parent_level + height > config.max_depth {
+            return Err(invalid("TODO_TOO_DEEP", format!("Todo tree must not be deeper than {}", config.max_depth)))
+        }
+        Ok(())
+    }
+
+    /// Complete every subtask of todo.
+    async fn complete_subtree<C: ConnectionTrait>(db: &C, todo_id: i32) -> Result<(), ServerError> {
+        let ids: Vec<i32> = Self::subtree_levels(db, todo_id).await?
+            .into_iter().flatten()
+            .filter(|m| !m.completed)
+            .map(|m| m.id)
+            .collect();
+        if ids.is_empty() {
+            return Ok(())
+        }
+        todos::Entity::update_many()
+            .col_expr(todos::Column::Completed, sea_query::Expr::value(true))
+            .filter(todos::Column::Id.is_in(ids))
+            .exec(db).await
+            .map_err(|e|
+                db_error("Database update error", e)
+            )?;
+        Ok(())
+    }
+
+    /// Parent is completed when every subtask is completed, otherwise not completed.
+    /// Goes up to ancestors while parent is changed.
+    async fn roll_up_completion<C: ConnectionTrait>(db: &C, parent_id: i32) -> Result<(), ServerError> {
+        let mut parent_id = Some(parent_id);
+        while let Some(id) = parent_id {
+            let parent = todos::Entity::find_by_id(id)
+                .one(db).await
+                .map_err(|e|
+                    db_error("Database fetch error", e)
+                )?;
+            let Some(parent) = parent else { break };
+
+            let subtasks = todos::Entity::find()
+                .filter(todos::Column::ParentId.eq(id))
+                .all(db).await
+                .map_err(|e|
+                    db_error("Database fetch error", e)
+                )?;
+            // Todo without subtask keeps its completion.
+            if subtasks.is_empty() {
+                break
+            }
+            let completed = subtasks.iter().all(|m| m.completed);
+            if parent.completed == completed {
+                break
+            }
+
+            parent_id = parent.parent_id;
+            let mut parent = parent.into_active_model();
+            parent.completed = Set(completed);
+            parent.update(db).await
+                .map_err(|e|
+                    db_error("Database update error", e)
+                )?;
+        }
+        Ok(())
+    }
+
+    /// Get every tag of user.
+    pub async fn get_tags(db: &DbConn, user_id: i32) -> Result<Vec<tags::Model>, ServerError> {
+        tags::Entity::find()
+            .filter(tags::Column::UserId.eq(user_id))
+            .order_by_asc(tags::Column::Name)
+            .all(db).await
+            .map_err(|e|
+                db_error("Database fetch error", e)
+            )
+    }
+
+    /// Create tag. Tag name is unique per user.
+    pub async fn create_tag(db: &DbConn, data: tags::Model) -> Result<tags::Model, ServerError> {
+        // Validation detail refer to tags::Model.
+        data.validate().map_err(validation_error)?;
+
+        tags::ActiveModel {
+            user_id: Set(data.user_id),
+            name: Set(data.name),
+            ..Default::default()
+        }
+        .insert(db).await
+        .map_err(|e|
+            db_error("Database insert error", e)
+        )
+    }
+
+    /// Rename tag.
+    pub async fn update_tag(db: &DbConn, data: tags::Model) -> Result<tags::Model, ServerError> {
+        // Get tag by user_id and tag_id
+        let mut model = tags::Entity::find_by_id(data.id)
+            .filter(tags::Column::UserId.eq(data.user_id))
+            .one(db).await
+            .map_err(|e|
+                db_error("Database fetch error", e)
+            )?
+            .ok_or(ServerError::NotFound { code: "TAG_NOT_FOUND", msg: "Tag not found" })?;
+
+        // Validate data
+        model.name = data.name;
+        model.validate().map_err(validation_error)?;
+
+        // Update
+        model.into_active_model().reset_all()
+            .update(db).await
+            .map_err(|e|
+                db_error("Database update error", e)
+            )
+    }
+
+    /// Delete tag. Tag is removed from every todo.
+    pub async fn delete_tag(db: &DbConn, user_id: i32, tag_id: i32) -> Result<DeleteResult, ServerError> {
+        let result = tags::Entity::delete_many()
+            .filter(tags::Column::Id.eq(tag_id))
+            .filter(tags::Column::UserId.eq(user_id))
+            .exec(db).await
+            .map_err(|e|
+                db_error("Database delete error", e)
+            )?;
+
+        // Not exist.
+        if result.rows_affected == 0 {
+            return Err(ServerError::NotFound { code: "TAG_NOT_FOUND", msg: "Tag not found" })
+        }
+        Ok(result)
+    }
+
+    /// Get project owned by user.
+    async fn find_project<C: ConnectionTrait>(db: &C, user_id: i32, project_id: i32) -> Result<projects::Model, ServerError> {
+        projects::Entity::find_by_id(project_id)
+            .filter(projects::Column::UserId.eq(user_id))
+            .one(db).await
+            .map_err(|e|
+                db_error("Database fetch error", e)
+            )?
+            .ok_or(ServerError::NotFound { code: "PROJECT_NOT_FOUND", msg: "Project not found" })
+    }
+
+    /// Get projects of user in sort order.
+    pub async fn get_projects(db: &DbConn, user_id: i32, query: ProjectQuery) -> Result<Vec<projects::Model>, ServerError> {
+        let mut select = projects::Entity::find()
+            .filter(projects::Column::UserId.eq(user_id));
+        if let Some(archived) = query.archived {
+            select = select.filter(projects::Column::Archived.eq(archived));
+        }
+        select
+            .order_by_asc(projects::Column::SortOrder)
+            .order_by_asc(projects::Column::Id)
+            .all(db).await
+            .map_err(|e|
+                db_error("Database fetch error", e)
+            )
+    }
+
+    /// Get todo of project owned by user.
+    pub async fn get_project_todo(db: &DbConn, user_id: i32, project_id: i32, mut query: TodoQuery) -> Result<TodoPage, ServerError> {
+        Self::find_project(db, user_id, project_id).await?;
+
+        query.project_id = Some(project_id);
+        query.inbox = None;
+        Self::get_todo(db, user_id, query).await
+    }
+
+    /// Create project.
+    pub async fn create_project(db: &DbConn, data: projects::Model) -> Result<projects::Model, ServerError> {
+        // Validation detail refer to projects::Model.
+        data.validate().map_err(validation_error)?;
+
+        projects::ActiveModel {
+            user_id: Set(data.user_id),
+            name: Set(data.name),
+            color: Set(data.color),
+            sort_order: Set(data.sort_order),
+            archived: Set(data.archived),
+            ..Default::default()
+        }
+        .insert(db).await
+        .map_err(|e|
+            db_error("Database insert error", e)
+        )
+    }
+
+    /// Update project.
+    pub async fn update_project(db: &DbConn, data: projects::Model) -> Result<projects::Model, ServerError> {
+        let mut model = Self::find_project(db, data.user_id, data.id).await?;
+
+        // Validate data
+        model.name = data.name;
+        model.color = data.color;
+        model.sort_order = data.sort_order;
+        model.archived = data.archived;
+        model.validate().map_err(validation_error)?;
+
+        // Update
+        model.into_active_model().reset_all()
+            .update(db).await
+            .map_err(|e|
+                db_error("Database update error", e)
+            )
+    }
+
+    /// Delete project. Its todos are deleted or moved to inbox.
+    pub async fn delete_project(db: &DbConn, user_id: i32, project_id: i32, todos: ProjectTodos) -> Result<DeleteResult, ServerError> {
+        let txn = db.begin().await
+            .map_err(|e|
+                db_error("Database transaction error", e)
+            )?;
+
+        let model = Self::find_project(&txn, user_id, project_id).await?;
+
+        match todos {
+            ProjectTodos::Inbox => {
+                todos::Entity::update_many()
+                    .col_expr(todos::Column::ProjectId, sea_query::Expr::value(Option::<i32>::None))
+                    .filter(todos::Column::ProjectId.eq(project_id))
+                    .exec(&txn).await
+                    .map_err(|e|
+                        db_error("Database update error", e)
+                    )?;
+            },
+            ProjectTodos::Cascade => {
+                todos::Entity::delete_many()
+                    .filter(todos::Column::ProjectId.eq(project_id))
+                    .exec(&txn).await
+                    .map_err(|e|
+                        db_error("Database delete error", e)
+                    )?;
+            },
+        }
+
+        let result = model.delete(&txn).await
+            .map_err(|e|
+                db_error("Database delete error", e)
+            )?;
+
+        txn.commit().await
+            .map_err(|e|
+                db_error("Database transaction error", e)
+            )?;
+
+        Ok(result)
+    }
+
+    /// Parse rrule and make it canonical form.
+    fn parse_rrule(rrule: &str) -> Result<String, ServerError> {
+        let rrule = rrule.parse::<RecurrenceRule>()
+            .map_err(|e| field_error("rrule", "invalid_rrule", e.to_string()))?
+            .to_string();
+        if rrule.len() > 255 {
+            return Err(field_error("rrule", "too_long", "Rule is too long"))
+        }
+        Ok(rrule)
+    }
+
+    /// Create recurring series.
+    async fn create_series<C: ConnectionTrait>(db: &C, user_id: i32, rrule: &str, dtstart: Date) -> Result<series::Model, ServerError> {
+        series::ActiveModel {
+            user_id: Set(user_id),
+            rrule: Set(Self::parse_rrule(rrule)?),
+            dtstart: Set(dtstart),
+            ..Default::default()
+        }
+        .insert(db).await
+        .map_err(|e|
+            db_error("Database insert error", e)
+        )
+    }
+
+    /// Get every series of user.
+    pub async fn get_series(db: &DbConn, user_id: i32) -> Result<Vec<series::Model>, ServerError> {
+        series::Entity::find()
+            .filter(series::Column::UserId.eq(user_id))
+            .order_by_asc(series::Column::Id)
+            .all(db).await
+            .map_err(|e|
+                db_error("Database fetch error", e)
+            )
+    }
+
+    /// Update rule of series. Next occurrences follow new rule.
+    pub async fn update_series(db: &DbConn, data: series::Model) -> Result<series::Model, ServerError> {
+        let model = series::Entity::find_by_id(data.id)
+            .filter(series::Column::UserId.eq(data.user_id))
+            .one(db).await
+            .map_err(|e|
+                db_error("Database fetch error", e)
+            )?
+            .ok_or(ServerError::NotFound { code: "SERIES_NOT_FOUND", msg: "Series not found" })?;
+
+        let mut model = model.into_active_model();
+        model.rrule = Set(Self::parse_rrule(&data.rrule)?);
+        model.update(db).await
+            .map_err(|e|
+                db_error("Database update error", e)
+            )
+    }
+
+    /// Stop series. Its todos are kept without series.
+    pub async fn delete_series(db: &DbConn, user_id: i32, series_id: i32) -> Result<DeleteResult, ServerError> {
+        let result = series::Entity::delete_many()
+            .filter(series::Column::Id.eq(series_id))
+            .filter(series::Column::UserId.eq(user_id))
+            .exec(db).await
+            .map_err(|e|
+                db_error("Database delete error", e)
+            )?;
+
+        // Not exist.
+        if result.rows_affected == 0 {
+            return Err(ServerError::NotFound { code: "SERIES_NOT_FOUND", msg: "Series not found" })
+        }
+        Ok(result)
+    }
+
+    /// Fire time of reminder. Relative reminder of todo without due is not scheduled.
+    fn reminder_fire_at(time_zone: Tz, todo: &todos::Model, remind_at: Option<DateTimeWithTimeZone>, offset_minutes: Option<i32>) -> Option<DateTimeWithTimeZone> {
+        if remind_at.is_some() {
+            return remind_at
+        }
+        let offset = Duration::minutes(offset_minutes? as i64);
+        let due = match (todo.due_at, todo.due_date) {
+            (Some(due_at), _) => due_at,
+            // All day todo is due at start of the day.
+            (None, Some(due_date)) => time_zone.from_local_datetime(&due_date.and_hms_opt(0, 0, 0)?)
+                .earliest()?
+                .fixed_offset(),
+            (None, None) => return None,
+        };
+        Some(due - offset)
+    }
+
+    /// Recompute fire time of unsent relative reminders after due of todo is changed.
+    async fn reschedule_reminders<C: ConnectionTrait>(db: &C, time_zone: Tz, todo: &todos::Model) -> Result<(), ServerError> {
+        let models = reminders::Entity::find()
+            .filter(reminders::Column::TodoId.eq(todo.id))
+            .filter(reminders::Column::OffsetMinutes.is_not_null())
+            .filter(reminders::Column::SentAt.is_null())
+            .all(db).await
+            .map_err(|e|
+                db_error("Database fetch error", e)
+            )?;
+
+        for model in models {
+            let fire_at = Self::reminder_fire_at(time_zone, todo, None, model.offset_minutes);
+            let mut model = model.into_active_model();
+            model.fire_at = Set(fire_at);
+            model.attempts = Set(0);
+            model.last_error = Set(None);
+            model.update(db).await
+                .map_err(|e|
+                    db_error("Database update error", e)
+                )?;
+        }
+        Ok(())
+    }
+
+    /// Find todo of user.
+    async fn find_todo<C: ConnectionTrait>(db: &C, user_id: i32, todo_id: i32) -> Result<todos::Model, ServerError> {
+        todos::Entity::find_by_id(todo_id)
+            .filter(todos::Column::UserId.eq(user_id))
+            .one(db).await
+            .map_err(|e|
+                db_error("Database fetch error", e)
+            )?
+            .ok_or(ServerError::NotFound { code: "TODO_NOT_FOUND", msg: "Todo not found" })
+    }
+
+    /// Get reminders of todo.
+    pub async fn get_reminders(db: &DbConn, user_id: i32, todo_id: i32) -> Result<Vec<reminders::Model>, ServerError> {
+        Self::find_todo(db, user_id, todo_id).await?;
+        reminders::Entity::find()
+            .filter(reminders::Column::TodoId.eq(todo_id))
+            .order_by_asc(reminders::Column::Id)
+            .all(db).await
+            .map_err(|e|
+                db_error("Database fetch error", e)
+            )
+    }
+
+    /// Create reminder of todo.
+    /// Exactly one of remind_at and offset_minutes is required.
+    pub async fn create_reminder(db: &DbConn, data: reminders::Model) -> Result<reminders::Model, ServerError> {
+        // Validation detail refer to reminders::Model.
+        data.validate().map_err(validation_error)?;
+        if data.remind_at.is_some() == data.offset_minutes.is_some() {
+            return Err(field_error("remind_at", "exactly_one", "Exactly one of remind_at and offset_minutes is required"))
+        }
+
+        let todo = Self::find_todo(db, data.user_id, data.todo_id).await?;
+        let time_zone = Self::user_time_zone(db, data.user_id).await?;
+
+        reminders::ActiveModel {
+            user_id: Set(data.user_id),
+            todo_id: Set(data.todo_id),
+            remind_at: Set(data.remind_at),
+            offset_minutes: Set(data.offset_minutes),
+            channel: Set(data.channel),
+            fire_at: Set(Self::reminder_fire_at(time_zone, &todo, data.remind_at, data.offset_minutes)),
+            ..Default::default()
+        }
+        .insert(db).await
+        .map_err(|e|
+            db_error("Database insert error", e)
+        )
+    }
+
+    /// Delete reminder of todo.
+    pub async fn delete_reminder(db: &DbConn, user_id: i32, todo_id: i32, reminder_id: i32) -> Result<DeleteResult, ServerError> {
+        let result = reminders::Entity::delete_many()
+            .filter(reminders::Column::Id.eq(reminder_id))
+            .filter(reminders::Column::TodoId.eq(todo_id))
+            .filter(reminders::Column::UserId.eq(user_id))
+            .exec(db).await
+            .map_err(|e|
+                db_error("Database delete error", e)
+            )?;
+
+        // Not exist.
+        if result.rows_affected == 0 {
+            return Err(ServerError::NotFound { code: "REMINDER_NOT_FOUND", msg: "Reminder not found" })
+        }
+        Ok(result)
+    }
+
+    /// Lock unsent reminders due by now, oldest first.
+    /// Reminders of completed todo are skipped, and failed ones are retried until max_attempts.
+    /// Rows locked by other transaction are skipped, so several servers can fire reminders together.
+    pub async fn lock_due_reminders<C: ConnectionTrait>(db: &C, now: DateTime<Utc>, limit: u64, max_attempts: i32) -> Result<Vec<DueReminder>, ServerError> {
+        let mut select = reminders::Entity::find()
+            .find_also_related(todos::Entity)
+            .filter(reminders::Column::SentAt.is_null())
+            .filter(reminders::Column::FireAt.lte(now))
+            .filter(reminders::Column::Attempts.lt(max_attempts))
+            .filter(todos::Column::Completed.eq(false))
+            .order_by_asc(reminders::Column::FireAt)
+            .order_by_asc(reminders::Column::Id)
+            .limit(limit);
+        QueryTrait::query(&mut select)
+            .lock_with_tables_behavior(LockType::Update, [reminders::Entity], LockBehavior::SkipLocked);
+        let rows = select.all(db).await
+            .map_err(|e|
+                db_error("Database fetch error", e)
+            )?;
+
+        let user_ids: Vec<i32> = rows.iter().map(|(r, _)| r.user_id).collect();
+        let users: HashMap<i32, users::Model> = users::Entity::find()
+            .filter(users::Column::Id.is_in(user_ids))
+            .all(db).await
+            .map_err(|e|
+                db_error("Database fetch error", e)
+            )?
+            .into_iter()
+            .map(|u| (u.id, u))
+            .collect();
+
+        Ok(rows.into_iter()
+            .filter_map(|(reminder, todo)| {
+                let user = users.get(&reminder.user_id)?.clone();
+                Some(DueReminder { reminder, todo: todo?, user })
+            })
+            .collect())
+    }
+
+    /// Mark reminder as delivered.
+    pub async fn mark_reminder_sent<C: ConnectionTrait>(db: &C, reminder: reminders::Model, now: DateTime<Utc>) -> Result<reminders::Model, ServerError> {
+        let mut model = reminder.into_active_model();
+        model.sent_at = Set(Some(now.fixed_offset()));
+        model.last_error = Set(None);
+        model.update(db).await
+            .map_err(|e|
+                db_error("Database update error", e)
+            )
+    }
+
+    /// Record failed delivery and retry at the time.
+    pub async fn mark_reminder_failed<C: ConnectionTrait>(db: &C, reminder: reminders::Model, error: &str, retry_at: DateTime<Utc>) -> Result<reminders::Model, ServerError> {
+        let attempts = reminder.attempts + 1;
+        let mut model = reminder.into_active_model();
+        model.attempts = Set(attempts);
+        model.last_error = Set(Some(error.chars().take(255).collect()));
+        model.fire_at = Set(Some(retry_at.fixed_offset()));
+        model.update(db).await
+            .map_err(|e|
+                db_error("Database update error", e)
+            )
+    }
+
+    /// Get inbox notifications of user, newest first.
+    pub async fn get_notifications(db: &DbConn, user_id: i32, query: NotificationQuery) -> Result<Vec<notifications::Model>, ServerError> {
+        let mut select = notifications::Entity::find()
+            .filter(notifications::Column::UserId.eq(user_id));
+        if let Some(read) = query.read {
+            select = select.filter(notifications::Column::Read.eq(read));
+        }
+        select
+            .order_by_desc(notifications::Column::CreatedAt)
+            .order_by_desc(notifications::Column::Id)
+            .all(db).await
+            .map_err(|e|
+                db_error("Database fetch error", e)
+            )
+    }
+
+    /// Save notification to user's inbox.
+    pub async fn create_notification<C: ConnectionTrait>(db: &C, user_id: i32, todo_id: Option<i32>, title: &str, body: &str) -> Result<notifications::Model, ServerError> {
+        notifications::ActiveModel {
+            user_id: Set(user_id),
+            todo_id: Set(todo_id),
+            title: Set(title.chars().take(100).collect()),
+            body: Set(body.chars().take(255).collect()),
+            ..Default::default()
+        }
+        .insert(db).await
+        .map_err(|e|
+            db_error("Database insert error", e)
+        )
+    }
+
+    /// Mark notification as read.
+    pub async fn read_notification(db: &DbConn, user_id: i32, notification_id: i32) -> Result<notifications::Model, ServerError> {
+        let model = notifications::Entity::find_by_id(notification_id)
+            .filter(notifications::Column::UserId.eq(user_id))
+            .one(db).await
+            .map_err(|e|
+                db_error("Database fetch error", e)
+            )?
+            .ok_or(ServerError::NotFound { code: "NOTIFICATION_NOT_FOUND", msg: "Notification not found" })?;
+
+        let mut model = model.into_active_model();
+        model.read = Set(true);
+        model.update(db).await
+            .map_err(|e|
+                db_error("Database update error", e)
+            )
+    }
+
+    /// Delete notification.
+    pub async fn delete_notification(db: &DbConn, user_id: i32, notification_id: i32) -> Result<DeleteResult, ServerError> {
+        let result = notifications::Entity::delete_many()
+            .filter(notifications::Column::Id.eq(notification_id))
+            .filter(notifications::Column::UserId.eq(user_id))
+            .exec(db).await
+            .map_err(|e|
+                db_error("Database delete error", e)
+            )?;
+
+        // Not exist.
+        if result.rows_affected == 0 {
+            return Err(ServerError::NotFound { code: "NOTIFICATION_NOT_FOUND", msg: "Notification not found" })
+        }
+        Ok(result)
+    }
+
+    /// Refresh and personal access tokens are random strings, only sha256 digest is saved in database.
+    fn token_hashing(token: &str) -> String {
+        hex::encode(Sha256::digest(token.as_bytes()))
+    }
+
+    /// Issue refresh token of the session.
+    /// If family is None, new token family is started (e.g. login).
+    /// Return raw token, it can not be restored from database.
+    pub async fn create_refresh_token(db: &DbConn, config: &AuthConfig, user_id: i32, session_id: i32, family: Option<String>) -> Result<String, ServerError> {
+        let token = hex::encode(rand::random::<[u8; 32]>());
+        let family = family.unwrap_or_else(|| hex::encode(rand::random::<[u8; 16]>()));
+
+        // Insert refresh token to database.
+        refresh_tokens::ActiveModel {
+            user_id: Set(user_id),
+            token_hash: Set(Self::token_hashing(&token)),
+            family: Set(family),
+            session_id: Set(Some(session_id)),
+            used: Set(false),
+            revoked: Set(false),
+            expires_at: Set(Utc::now().naive_utc() + Duration::days(config.refresh_token_exp_days)),
+            ..Default::default()
+        }
+        .insert(db).await
+        .map_err(|e|
+            db_error("Database insert error", e)
+        )?;
+
+        Ok(token)
+    }
+
+    /// Exchange refresh token to new one in same family.
+    /// Each token is single-use. Reusing a used token revokes whole family.
+    /// Return owner's user_id, session_id and new refresh token.
+    pub async fn rotate_refresh_token(db: &DbConn, config: &AuthConfig, token: &str) -> Result<(i32, i32, String), ServerError> {
+        // Get token by hash.
+        let model = refresh_tokens::Entity::find()
+            .filter(refresh_tokens::Column::TokenHash.eq(Self::token_hashing(token)))
+            .one(db).await
+            .map_err(|e|
+                db_error("Database fetch error", e)
+            )?
+            .ok_or(ServerError::UnauthorizedError { code: "REFRESH_TOKEN_INVALID", msg: "Invalid refresh token", detail: "No refresh token exists".to_owned() })?;
+
+        if model.revoked {
+            return Err(ServerError::UnauthorizedError { code: "REFRESH_TOKEN_REVOKED", msg: "Invalid refresh token", detail: "Refresh token is revoked".to_owned() })
+        }
+
+        // Already used, token may be stolen.
+        // Checked before expiry, stolen token replayed after it expires revokes the family too.
+        if model.used {
+            return Err(Self::refresh_token_reused(db, &model.family).await)
+        }
+
+        if model.expires_at < Utc::now().naive_utc() {
+            return Err(ServerError::UnauthorizedError { code: "REFRESH_TOKEN_EXPIRED", msg: "Invalid refresh token", detail: "Refresh token is expired".to_owned() })
+        }
+
+        // Mark as used. Only one of concurrent requests can succeed.
+        let result = refresh_tokens::Entity::update_many()
+            .col_expr(refresh_tokens::Column::Used, sea_query::Expr::value(true))
+            .filter(refresh_tokens::Column::Id.eq(model.id))
+            .filter(refresh_tokens::Column::Used.eq(false))
+            .exec(db).await
+            .map_err(|e|
+                db_error("Database update error", e)
+            )?;
+
+        // Used by concurrent request.
+        if result.rows_affected == 0 {
+            return Err(Self::refresh_token_reused(db, &model.family).await)
+        }
+
+        // Token issued before sessions, it can not be bound to a session.
+        let session_id = model.session_id
+            .ok_or(ServerError::UnauthorizedError { code: "REFRESH_TOKEN_INVALID", msg: "Invalid refresh token", detail: "Refresh token has no session".to_owned() })?;
+        Self::touch_session(db, session_id).await?;
+
+        let new_token = Self::create_refresh_token(db, config, model.user_id, session_id, Some(model.family)).await?;
+        Ok((model.user_id, session_id, new_token))
+    }
+
+    /// Revoke family of reused refresh token.
+    async fn refresh_token_reused(db: &DbConn, family: &str) -> ServerError {
+        if let Err(e) = Self::revoke_refresh_token_family(db, family).await {
+            return e
+        }
+        ServerError::UnauthorizedError { code: "REFRESH_TOKEN_REUSED", msg: "Invalid refresh token", detail: "Refresh token is reused".to_owned() }
+    }
+
+    /// Revoke every refresh token in family.
+    pub async fn revoke_refresh_token_family(db: &DbConn, family: &str) -> Result<UpdateResult, ServerError> {
+        refresh_tokens::Entity::update_many()
+            .col_expr(refresh_tokens::Column::Revoked, sea_query::Expr::value(true))
+            .filter(refresh_tokens::Column::Family.eq(family))
+            .exec(db).await
+            .map_err(|e|
+                db_error("Database update error", e)
+            )
+    }
+
+    /// Revoke refresh token of the user and every token rotated from same login.
+    pub async fn revoke_refresh_token(db: &DbConn, user_id: i32, token: &str) -> Result<UpdateResult, ServerError> {
+        let model = refresh_tokens::Entity::find()
+            .filter(refresh_tokens::Column::TokenHash.eq(Self::token_hashing(token)))
+            .filter(refresh_tokens::Column::UserId.eq(user_id))
+            .one(db).await
+            .map_err(|e|
+                db_error("Database fetch error", e)
+            )?
+            .ok_or(ServerError::UnauthorizedError { code: "REFRESH_TOKEN_INVALID", msg: "Invalid refresh token", detail: "No refresh token exists".to_owned() })?;
+        Self::revoke_refresh_token_family(db, &model.family).await
+    }
+
+    /// Revoke access token until it expires.
+    pub async fn revoke_access_token(db: &DbConn, user_id: i32, jti: &str, expires_at: DateTime<Utc>) -> Result<(), ServerError> {
+        revoked_tokens::Entity::insert(revoked_tokens::ActiveModel {
+            jti: Set(jti.to_owned()),
+            user_id: Set(user_id),
+            expires_at: Set(expires_at.fixed_offset()),
+            ..Default::default()
+        })
+        .on_conflict(
+            sea_query::OnConflict::column(revoked_tokens::Column::Jti)
+                .do_nothing()
+                .to_owned()
+        )
+        .exec_without_returning(db).await
+        .map_err(|e|
+            db_error("Database insert error", e)
+        )?;
+        Ok(())
+    }
+
+    /// Revoke every access and refresh token of user issued until now.
+    /// Return new tokens_valid_after of the user.
+    pub async fn revoke_all_tokens(db: &DbConn, user_id: i32) -> Result<DateTime<Utc>, ServerError> {
+        let now = Utc::now();
+        let txn = db.begin().await
+            .map_err(|e|
+                db_error("Database transaction error", e)
+            )?;
+
+        Self::revoke_user_tokens(&txn, user_id, now).await?;
+
+        txn.commit().await
+            .map_err(|e|
+                db_error("Database transaction error", e)
+            )?;
+        Ok(now)
+    }
+
+    /// Revoke tokens and sessions of user issued until the time.
+    async fn revoke_user_tokens<C: ConnectionTrait>(db: &C, user_id: i32, now: DateTime<Utc>) -> Result<(), ServerError> {
+        users::Entity::update_many()
+            .col_expr(users::Column::TokensValidAfter, sea_query::Expr::value(now.fixed_offset()))
+            .filter(users::Column::Id.eq(user_id))
+            .exec(db).await
+            .map_err(|e|
+                db_error("Database update error", e)
+            )?;
+
+        refresh_tokens::Entity::update_many()
+            .col_expr(refresh_tokens::Column::Revoked, sea_query::Expr::value(true))
+            .filter(refresh_tokens::Column::UserId.eq(user_id))
+            .exec(db).await
+            .map_err(|e|
+                db_error("Database update error", e)
+            )?;
+
+        sessions::Entity::update_many()
+            .col_expr(sessions::Column::RevokedAt, sea_query::Expr::value(now.fixed_offset()))
+            .filter(sessions::Column::UserId.eq(user_id))
+            .filter(sessions::Column::RevokedAt.is_null())
+            .exec(db).await
+            .map_err(|e|
+                db_error("Database update error", e)
+            )?;
+        Ok(())
+    }
+
+    /// Get unexpired revocations created after the time, every revocation if None.
+    /// Sessions revoked longer than access token lifetime ago have no valid token, so they are skipped.
+    /// Expired revoked tokens are deleted.
+    pub async fn get_revocations(db: &DbConn, config: &AuthConfig, since: Option<DateTime<Utc>>) -> Result<Revocations, ServerError> {
+        let now = Utc::now();
+        revoked_tokens::Entity::delete_many()
+            .filter(revoked_tokens::Column::ExpiresAt.lt(now))
+            .exec(db).await
+            .map_err(|e|
+                db_error("Database delete error", e)
+            )?;
+
+        let mut tokens = revoked_tokens::Entity::find();
+        let mut users = users::Entity::find()
+            .filter(users::Column::TokensValidAfter.is_not_null());
+        let sessions_since = now - Duration::minutes(config.access_token_exp_min as i64);
+        let sessions = sessions::Entity::find()
+            .select_only()
+            .column(sessions::Column::Id)
+            .column(sessions::Column::RevokedAt)
+            .filter(sessions::Column::RevokedAt.gte(since.map_or(sessions_since, |since| since.max(sessions_since))))
+            .into_tuple::<(i32, DateTimeWithTimeZone)>()
+            .all(db).await
+            .map_err(|e|
+                db_error("Database fetch error", e)
+            )?;
+        if let Some(since) = since {
+            tokens = tokens.filter(revoked_tokens::Column::CreatedAt.gte(since));
+            users = users.filter(users::Column::TokensValidAfter.gte(since));
+        }
+
+        let tokens = tokens.all(db).await
+            .map_err(|e|
+                db_error("Database fetch error", e)
+            )?;
+        let users = users
+            .select_only()
+            .column(users::Column::Id)
+            .column(users::Column::TokensValidAfter)
+            .into_tuple::<(i32, DateTimeWithTimeZone)>()
+            .all(db).await
+            .map_err(|e|
+                db_error("Database fetch error", e)
+            )?;
+
+        Ok(Revocations { tokens, users, sessions })
+    }
+
+    /// Start session of new login.
+    pub async fn create_session(db: &DbConn, user_id: i32, user_agent: Option<String>, ip: Option<String>) -> Result<sessions::Model, ServerError> {
+        let now = Utc::now().fixed_offset();
+        sessions::ActiveModel {
+            user_id: Set(user_id),
+            user_agent: Set(user_agent),
+            ip: Set(ip),
+            created_at: Set(now),
+            last_seen_at: Set(now),
+            ..Default::default()
+        }
+        .insert(db).await
+        .map_err(|e|
+            db_error("Database insert error", e)
+        )
+    }
+
+    /// Update last seen time of the session.
+    pub async fn touch_session(db: &DbConn, session_id: i32) -> Result<UpdateResult, ServerError> {
+        sessions::Entity::update_many()
+            .col_expr(sessions::Column::LastSeenAt, sea_query::Expr::value(Utc::now().fixed_offset()))
+            .filter(sessions::Column::Id.eq(session_id))
+            .exec(db).await
+            .map_err(|e|
+                db_error("Database update error", e)
+            )
+    }
+
+    /// Get active sessions of user, recently seen first.
+    /// Session not refreshed for refresh token lifetime can not be resumed, so it is not active.
+    pub async fn get_sessions(db: &DbConn, config: &AuthConfig, user_id: i32) -> Result<Vec<sessions::Model>, ServerError> {
+        sessions::Entity::find()
+            .filter(sessions::Column::UserId.eq(user_id))
+            .filter(sessions::Column::RevokedAt.is_null())
+            .filter(sessions::Column::LastSeenAt.gte(Utc::now() - Duration::days(config.refresh_token_exp_days)))
+            .order_by_desc(sessions::Column::LastSeenAt)
+            .all(db).await
+            .map_err(|e|
+                db_error("Database fetch error", e)
+            )
+    }
+
+    /// Revoke session and every refresh token of it.
+    /// Return revoked time, access tokens of the session are not accepted after it.
+    pub async fn revoke_session(db: &DbConn, user_id: i32, session_id: i32) -> Result<DateTime<Utc>, ServerError> {
+        let now = Utc::now();
+        let txn = db.begin().await
+            .map_err(|e|
+                db_error("Database transaction error", e)
+            )?;
+
+        let result = sessions::Entity::update_many()
+            .col_expr(sessions::Column::RevokedAt, sea_query::Expr::value(now.fixed_offset()))
+            .filter(sessions::Column::Id.eq(session_id))
+            .filter(sessions::Column::UserId.eq(user_id))
+            .filter(sessions::Column::RevokedAt.is_null())
+            .exec(&txn).await
+            .map_err(|e|
+                db_error("Database update error", e)
+            )?;
+
+        // Not exist.
+        if result.rows_affected == 0 {
+            return Err(ServerError::NotFound { code: "SESSION_NOT_FOUND", msg: "Session not found" })
+        }
+
+        refresh_tokens::Entity::update_many()
+            .col_expr(refresh_tokens::Column::Revoked, sea_query::Expr::value(true))
+            .filter(refresh_tokens::Column::SessionId.eq(session_id))
+            .exec(&txn).await
+            .map_err(|e|
+                db_error("Database update error", e)
+            )?;
+
+        txn.commit().await
+            .map_err(|e|
+                db_error("Database transaction error", e)
+            )?;
+        Ok(now)
+    }
+
+    /// Get personal access tokens of user, newest first.
+    pub async fn get_access_tokens(db: &DbConn, user_id: i32) -> Result<Vec<personal_access_tokens::Model>, ServerError> {
+        personal_access_tokens::Entity::find()
+            .filter(personal_access_tokens::Column::UserId.eq(user_id))
+            .order_by_desc(personal_access_tokens::Column::CreatedAt)
+            .all(db).await
+            .map_err(|e|
+                db_error("Database fetch error", e)
+            )
+    }
+
+    /// Issue personal access token.
+    /// Return raw token, it can not be restored from database.
+    pub async fn create_access_token(db: &DbConn, data: personal_access_tokens::Model) -> Result<(personal_access_tokens::Model, String), ServerError> {
+        // Validation detail refer to personal_access_tokens::Model.
+        data.validate().map_err(validation_error)?;
+        if data.expires_at.is_some_and(|t| t <= Utc::now()) {
+            return Err(field_error("expires_at", "not_future", "expires_at must be in the future"))
+        }
+
+        let token = format!("{}{}", personal_access_tokens::TOKEN_PREFIX, hex::encode(rand::random::<[u8; 32]>()));
+        // Duplicated scopes are saved once.
+        let mut scopes: Vec<&str> = data.scopes.split_whitespace().collect();
+        scopes.sort_unstable();
+        scopes.dedup();
+
+        let model = personal_access_tokens::ActiveModel {
+            user_id: Set(data.user_id),
+            name: Set(data.name),
+            token_hash: Set(Self::token_hashing(&token)),
+            scopes: Set(scopes.join(" ")),
+            expires_at: Set(data.expires_at),
+            ..Default::default()
+        }
+        .insert(db).await
+        .map_err(|e|
+            db_error("Database insert error", e)
+        )?;
+
+        Ok((model, token))
+    }
+
+    /// Get unexpired personal access token by raw token.
+    /// Token of disabled user is not returned.
+    pub async fn find_access_token(db: &DbConn, token: &str) -> Result<Option<personal_access_tokens::Model>, ServerError> {
+        personal_access_tokens::Entity::find()
+            .inner_join(users::Entity)
+            .filter(users::Column::DisabledAt.is_null())
+            .filter(personal_access_tokens::Column::TokenHash.eq(Self::token_hashing(token)))
+            .filter(
+                Condition::any()
+                    .add(personal_access_tokens::Column::ExpiresAt.is_null())
+                    .add(personal_access_tokens::Column::ExpiresAt.gt(Utc::now()))
+            )
+            .one(db).await
+            .map_err(|e|
+                db_error("Database fetch error", e)
+            )
+    }
+
+    /// Delete personal access token, it is not accepted anymore.
+    pub async fn delete_access_token(db: &DbConn, user_id: i32, token_id: i32) -> Result<DeleteResult, ServerError> {
+        let result = personal_access_tokens::Entity::delete_many()
+            .filter(personal_access_tokens::Column::Id.eq(token_id))
+            .filter(personal_access_tokens::Column::UserId.eq(user_id))
+            .exec(db).await
+            .map_err(|e|
+                db_error("Database delete error", e)
+            )?;
+
+        // Not exist.
+        if result.rows_affected == 0 {
+            return Err(ServerError::NotFound { code: "ACCESS_TOKEN_NOT_FOUND", msg: "Access token not found" })
+        }
+        Ok(result)
+    }
+
+    /// Issue password reset token of user who has the email.
+    /// Return None if no user has the email. Return raw token, it is sent by mail only.
+    pub async fn create_password_reset_token(db: &DbConn, config: &AuthConfig, email: &str) -> Result<Option<(users::Model, String)>, ServerError> {
+        let user = users::Entity::find()
+            .filter(users::Column::Email.eq(email))
+            .one(db).await
+            .map_err(|e|
+                db_error("Database fetch error", e)
+            )?;
+        let Some(user) = user else {
+            return Ok(None)
+        };
+
+        let token = Self::insert_password_reset_token(db, config, user.id).await?;
+        Ok(Some((user, token)))
+    }
+
+    /// Return raw token, only its hash is saved.
+    async fn insert_password_reset_token<C: ConnectionTrait>(db: &C, config: &AuthConfig, user_id: i32) -> Result<String, ServerError> {
+        let token = hex::encode(rand::random::<[u8; 32]>());
+        password_reset_tokens::ActiveModel {
+            user_id: Set(user_id),
+            token_hash: Set(Self::token_hashing(&token)),
+            expires_at: Set((Utc::now() + Duration::minutes(config.password_reset_exp_min)).fixed_offset()),
+            ..Default::default()
+        }
+        .insert(db).await
+        .map_err(|e|
+            db_error("Database insert error", e)
+        )?;
+        Ok(token)
+    }
+
+    /// Change password by reset token.
+    /// Token is consumed with every other reset token of the user, and every session is revoked.
+    /// Return user_id and new tokens_valid_after of the user.
+    pub async fn reset_password(db: &DbConn, hasher: &HashPool, token: &str, password: String) -> Result<(i32, DateTime<Utc>), ServerError> {
+        let now = Utc::now();
+        let invalid_token = || ServerError::BadRequestError { code: "RESET_TOKEN_INVALID", msg: "Invalid reset token", detail: "Reset token is invalid, used or expired".to_owned() };
+        let (reset_token, user) = password_reset_tokens::Entity::find()
+            .find_also_related(users::Entity)
+            .filter(password_reset_tokens::Column::TokenHash.eq(Self::token_hashing(token)))
+            .filter(password_reset_tokens::Column::UsedAt.is_null())
+            .filter(password_reset_tokens::Column::ExpiresAt.gt(now))
+            .one(db).await
+            .map_err(|e|
+                db_error("Database fetch error", e)
+            )?
+            .ok_or_else(invalid_token)?;
+        let mut user = user.ok_or_else(invalid_token)?;
+
+        // Same validation as sign up, detail refer to users::Model.
+        user.password = password;
+        user.validate_all().map_err(validation_error)?;
+        // Slow hashing is done before transaction, so it does not hold a connection.
+        let hashed_password = hasher.hash(&user.password).await?;
+
+        let txn = db.begin().await
+            .map_err(|e|
+                db_error("Database transaction error", e)
+            )?;
+
+        // Only one of concurrent requests can use the token, it may be used while hashing.
+        let result = password_reset_tokens::Entity::update_many()
+            .col_expr(password_reset_tokens::Column::UsedAt, sea_query::Expr::value(now.fixed_offset()))
+            .filter(password_reset_tokens::Column::Id.eq(reset_token.id))
+            .filter(password_reset_tokens::Column::UsedAt.is_null())
+            .exec(&txn).await
+            .map_err(|e|
+                db_error("Database update error", e)
+            )?;
+        if result.rows_affected == 0 {
+            return Err(invalid_token())
+        }
+        // Other tokens of the user can not be used anymore.
+        password_reset_tokens::Entity::update_many()
+            .col_expr(password_reset_tokens::Column::UsedAt, sea_query::Expr::value(now.fixed_offset()))
+            .filter(password_reset_tokens::Column::UserId.eq(reset_token.user_id))
+            .filter(password_reset_tokens::Column::UsedAt.is_null())
+            .exec(&txn).await
+            .map_err(|e|
+                db_error("Database update error", e)
+            )?;
+
+        users::Entity::update_many()
+            .col_expr(users::Column::Password, sea_query::Expr::value(hashed_password))
+            // Reset required by admin is done.
+            .col_expr(users::Column::PasswordResetRequired, sea_query::Expr::value(false))
+            .filter(users::Column::Id.eq(user.id))
+            .exec(&txn).await
+            .map_err(|e|
+                db_error("Database update error", e)
+            )?;
+
+        Self::revoke_user_tokens(&txn, user.id, now).await?;
+
+        txn.commit().await
+            .map_err(|e|
+                db_error("Database transaction error", e)
+            )?;
+        Ok((user.id, now))
+    }
+
+    /// Issue email verification token of the email, previous tokens of the user can not be used anymore.
+    /// Return raw token, it is sent by mail only.
+    pub async fn create_email_verification_token(db: &DbConn, config: &AuthConfig, user_id: i32, email: &str) -> Result<String, ServerError> {
+        let now = Utc::now();
+        let txn = db.begin().await
+            .map_err(|e|
+                db_error("Database transaction error", e)
+            )?;
+
+        // Only the latest requested email can be verified.
+        email_verification_tokens::Entity::update_many()
+            .col_expr(email_verification_tokens::Column::UsedAt, sea_query::Expr::value(now.fixed_offset()))
+            .filter(email_verification_tokens::Column::UserId.eq(user_id))
+            .filter(email_verification_tokens::Column::UsedAt.is_null())
+            .exec(&txn).await
+            .map_err(|e|
+                db_error("Database update error", e)
+            )?;
+
+        let token = hex::encode(rand::random::<[u8; 32]>());
+        email_verification_tokens::ActiveModel {
+            user_id: Set(user_id),
+            email: Set(email.to_owned()),
+            token_hash: Set(Self::token_hashing(&token)),
+            expires_at: Set((now + Duration::hours(config.email_verification_exp_hours)).fixed_offset()),
+            ..Default::default()
+        }
+        .insert(&txn).await
+        .map_err(|e|
+            db_error("Database insert error", e)
+        )?;
+
+        txn.commit().await
+            .map_err(|e|
+                db_error("Database transaction error", e)
+            )?;
+        Ok(token)
+    }
+
+    /// Email which is waiting for verification, None if there is no valid token.
+    pub async fn get_pending_email(db: &DbConn, user_id: i32) -> Result<Option<String>, ServerError> {
+        let token = email_verification_tokens::Entity::find()
+            .filter(email_verification_tokens::Column::UserId.eq(user_id))
+            .filter(email_verification_tokens::Column::UsedAt.is_null())
+            .filter(email_verification_tokens::Column::ExpiresAt.gt(Utc::now()))
+            .order_by_desc(email_verification_tokens::Column::Id)
+            .one(db).await
+            .map_err(|e|
+                db_error("Database fetch error", e)
+            )?;
+        Ok(token.map(|t| t.email))
+    }
+
+    /// Confirm email by verification token.
+    /// Email of user is changed to the verified one, and token is consumed.
+    pub async fn verify_email(db: &DbConn, token: &str) -> Result<users::Model, ServerError> {
+        let now = Utc::now();
+        let invalid_token = || ServerError::BadRequestError { code: "VERIFICATION_TOKEN_INVALID", msg: "Invalid verification token", detail: "Verification token is invalid, used or expired".to_owned() };
+        let txn = db.begin().await
+            .map_err(|e|
+                db_error("Database transaction error", e)
+            )?;
+
+        let verification = email_verification_tokens::Entity::find()
+            .filter(email_verification_tokens::Column::TokenHash.eq(Self::token_hashing(token)))
+            .filter(email_verification_tokens::Column::UsedAt.is_null())
+            .filter(email_verification_tokens::Column::ExpiresAt.gt(now))
+            .one(&txn).await
+            .map_err(|e|
+                db_error("Database fetch error", e)
+            )?
+            .ok_or_else(invalid_token)?;
+
+        // Only one of concurrent requests can use the token.
+        let result = email_verification_tokens::Entity::update_many()
+            .col_expr(email_verification_tokens::Column::UsedAt, sea_query::Expr::value(now.fixed_offset()))
+            .filter(email_verification_tokens::Column::Id.eq(verification.id))
+            .filter(email_verification_tokens::Column::UsedAt.is_null())
+            .exec(&txn).await
+            .map_err(|e|
+                db_error("Database update error", e)
+            )?;
+        if result.rows_affected == 0 {
+            return Err(invalid_token())
+        }
+
+        let model = users::ActiveModel {
+            id: Unchanged(verification.user_id),
+            email: Set(verification.email),
+            email_verified_at: Set(Some(now.fixed_offset())),
+            ..Default::default()
+        }
+        .update(&txn).await
+        .map_err(|e|
+            db_error("Database update error", e)
+        )?;
+
+        txn.commit().await
+            .map_err(|e|
+                db_error("Database transaction error", e)
+            )?;
+        Ok(model)
+    }
+
+    /// Recovery code is 20 hex letters in groups of 5, and compared without dashes and case.
+    fn recovery_code_hashing(code: &str) -> String {
+        let normalized: String = code.chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .map(|c| c.to_ascii_lowercase())
+            .collect();
+        Self::token_hashing(&normalized)
+    }
+
+    /// Start TOTP enrollment with new secret, previous unconfirmed secret is replaced.
+    /// Return user with the secret.
+    pub async fn enroll_totp(db: &DbConn, user_id: i32) -> Result<users::Model, ServerError> {
+        let mut user = Self::get_user(db, user_id).await?;
+        if user.totp_enabled_at.is_some() {
+            return Err(ServerError::ConflictError { code: "MFA_ALREADY_ENABLED", msg: "Two-factor already enabled", detail: "Disable two-factor before enrolling again".to_owned(), field: None })
+        }
+
+        let secret = Totp::generate_secret();
+        users::Entity::update_many()
+            .col_expr(users::Column::TotpSecret, sea_query::Expr::value(secret.clone()))
+            .filter(users::Column::Id.eq(user_id))
+            .exec(db).await
+            .map_err(|e|
+                db_error("Database update error", e)
+            )?;
+        user.totp_secret = Some(secret);
+        Ok(user)
+    }
+
+    /// Turn on two-factor with a code of enrolled secret.
+    /// Return raw recovery codes, they are shown only once.
+    pub async fn confirm_totp(db: &DbConn, user_id: i32, code: &str) -> Result<Vec<String>, ServerError> {
+        let now = Utc::now();
+        let user = Self::get_user(db, user_id).await?;
+        if user.totp_enabled_at.is_some() {
+            return Err(ServerError::ConflictError { code: "MFA_ALREADY_ENABLED", msg: "Two-factor already enabled", detail: "Disable two-factor before enrolling again".to_owned(), field: None })
+        }
+        let step = user.totp_secret.as_deref()
+            .and_then(Totp::new)
+            .ok_or(ServerError::BadRequestError { code: "MFA_NOT_ENROLLED", msg: "Two-factor not enrolled", detail: "Enroll TOTP before confirming".to_owned() })?
+            .verify(code, now)
+            .ok_or(ServerError::BadRequestError { code: "MFA_CODE_INVALID", msg: "Invalid code", detail: "TOTP code is wrong or expired".to_owned() })?;
+
+        let txn = db.begin().await
+            .map_err(|e|
+                db_error("Database transaction error", e)
+            )?;
+
+        users::Entity::update_many()
+            .col_expr(users::Column::TotpEnabledAt, sea_query::Expr::value(now.fixed_offset()))
+            .col_expr(users::Column::TotpLastStep, sea_query::Expr::value(step))
+            .filter(users::Column::Id.eq(user_id))
+            .exec(&txn).await
+            .map_err(|e|
+                db_error("Database update error", e)
+            )?;
+
+        // Codes of previous enrollment can not be used.
+        recovery_codes::Entity::delete_many()
+            .filter(recovery_codes::Column::UserId.eq(user_id))
+            .exec(&txn).await
+            .map_err(|e|
+                db_error("Database delete error", e)
+            )?;
+
+        let codes: Vec<String> = (0..RECOVERY_CODE_COUNT)
+            .map(|_| {
+                let code = hex::encode(rand::random::<[u8; 10]>());
+                code.as_bytes().chunks(5)
+                    .map(|chunk| std::str::from_utf8(chunk).unwrap())
+                    .collect::<Vec<_>>()
+                    .join("-")
+            })
+            .collect();
+        recovery_codes::Entity::insert_many(codes.iter().map(|code| recovery_codes::ActiveModel {
+            user_id: Set(user_id),
+            code_hash: Set(Self::recovery_code_hashing(code)),
+            ..Default::default()
+        }))
+        .exec(&txn).await
+        .map_err(|e|
+            db_error("Database insert error", e)
+        )?;
+
+        txn.commit().await
+            .map_err(|e|
+                db_error("Database transaction error", e)
+            )?;
+        Ok(codes)
+    }
+
+    /// Check TOTP code or recovery code of user, false if user does not use two-factor.
+    /// Code is consumed, TOTP code of used time step and used recovery code are rejected.
+    pub async fn verify_second_factor(db: &DbConn, user_id: i32, code: &str) -> Result<bool, ServerError> {
+        let now = Utc::now();
+        let user = Self::get_user(db, user_id).await?;
+        if user.totp_enabled_at.is_none() {
+            return Ok(false)
+        }
+
+        let step = user.totp_secret.as_deref()
+            .and_then(Totp::new)
+            .and_then(|totp| totp.verify(code, now));
+        let result = match step {
+            // Only one of concurrent requests can use the step.
+            Some(step) => users::Entity::update_many()
+                .col_expr(users::Column::TotpLastStep, sea_query::Expr::value(step))
+                .filter(users::Column::Id.eq(user_id))
+                .filter(
+                    Condition::any()
+                        .add(users::Column::TotpLastStep.is_null())
+                        .add(users::Column::TotpLastStep.lt(step))
+                )
+                .exec(db).await,
+            None => recovery_codes::Entity::update_many()
+                .col_expr(recovery_codes::Column::UsedAt, sea_query::Expr::value(now.fixed_offset()))
+                .filter(recovery_codes::Column::UserId.eq(user_id))
+                .filter(recovery_codes::Column::CodeHash.eq(Self::recovery_code_hashing(code)))
+                .filter(recovery_codes::Column::UsedAt.is_null())
+                .exec(db).await,
+        }
+        .map_err(|e|
+            db_error("Database update error", e)
+        )?;
+        Ok(result.rows_affected > 0)
+    }
+
+    /// Turn off two-factor by TOTP code or recovery code.
+    pub async fn disable_totp(db: &DbConn, user_id: i32, code: &str) -> Result<(), ServerError> {
+        if !Self::verify_second_factor(db, user_id, code).await? {
+            return Err(ServerError::BadRequestError { code: "MFA_CODE_INVALID", msg: "Invalid code", detail: "Code is wrong, used or two-factor is not enabled".to_owned() })
+        }
+
+        let txn = db.begin().await
+            .map_err(|e|
+                db_error("Database transaction error", e)
+            )?;
+
+        users::Entity::update_many()
+            .col_expr(users::Column::TotpSecret, sea_query::Expr::value(Option::<String>::None))
+            .col_expr(users::Column::TotpEnabledAt, sea_query::Expr::value(Option::<DateTimeWithTimeZone>::None))
+            .col_expr(users::Column::TotpLastStep, sea_query::Expr::value(Option::<i64>::None))
+            .filter(users::Column::Id.eq(user_id))
+            .exec(&txn).await
+            .map_err(|e|
+                db_error("Database update error", e)
+            )?;
+
+        recovery_codes::Entity::delete_many()
+            .filter(recovery_codes::Column::UserId.eq(user_id))
+            .exec(&txn).await
+            .map_err(|e|
+                db_error("Database delete error", e)
+            )?;
+
+        txn.commit().await
+            .map_err(|e|
+                db_error("Database transaction error", e)
+            )
+    }
+
+    /// Record admin action. It is written in same transaction as the action if db is transaction.
+    pub async fn create_audit_log<C: ConnectionTrait>(db: &C, actor: &AuditActor, action: &str, target_user_id: Option<i32>, detail: Option<String>) -> Result<(), ServerError> {
+        admin_audit_logs::ActiveModel {
+            admin_id: Set(actor.admin_id),
+            action: Set(action.to_owned()),
+            target_user_id: Set(target_user_id),
+            detail: Set(detail.filter(|d| !d.is_empty())),
+            ip: Set(actor.ip.clone()),
+            ..Default::default()
+        }
+        .insert(db).await
+        .map_err(|e|
+            db_error("Database insert error", e)
+        )?;
+        Ok(())
+    }
+
+    /// Search users by admin, ordered by id.
+    pub async fn search_users(db: &DbConn, query: UserQuery) -> Result<UserPage, ServerError> {
+        let per_page = query.per_page.unwrap_or(DEFAULT_ADMIN_PER_PAGE).min(MAX_ADMIN_PER_PAGE);
+        if per_page == 0 {
+            return Err(ServerError::BadRequestError { code: "INVALID_QUERY", msg: "Invalid query", detail: "per_page must be greater than 0".to_owned() })
+        }
+
+        let mut condition = Condition::all();
+        if let Some(q) = query.q.filter(|q| !q.is_empty()) {
+            // Escape LIKE wildcard.
+            let pattern = format!("%{}%", q.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_"));
+            condition = condition.add(
+                Condition::any()
+                    .add(sea_query::Expr::col(users::Column::Username).ilike(pattern.as_str()))
+                    .add(sea_query::Expr::col(users::Column::Email).ilike(pattern.as_str()))
+            );
+        }
+        if let Some(role) = query.role {
+            condition = condition.add(users::Column::Role.eq(role));
+        }
+        if let Some(disabled) = query.disabled {
+            condition = condition.add(if disabled {
+                users::Column::DisabledAt.is_not_null()
+            } else {
+                users::Column::DisabledAt.is_null()
+            });
+        }
+
+        let paginator = users::Entity::find()
+            .filter(condition)
+            .order_by_asc(users::Column::Id)
+            .paginate(db, per_page);
+        let counts = paginator.num_items_and_pages().await
+            .map_err(|e|
+                db_error("Database fetch error", e)
+            )?;
+        let items = paginator.fetch_page(query.page).await
+            .map_err(|e|
+                db_error("Database fetch error", e)
+            )?;
+
+        Ok(UserPage {
+            items,
+            total: counts.number_of_items,
+            page: query.page,
+            per_page,
+            total_pages: counts.number_of_pages,
+        })
+    }
+
+    /// Disable or enable user by admin.
+    /// Disabling revokes every token and session, return new tokens_valid_after in that case.
+    pub async fn set_user_disabled(db: &DbConn, actor: &AuditActor, user_id: i32, disabled: bool) -> Result<(users::Model, Option<DateTime<Utc>>), ServerError> {
+        // Admin would lock out itself.
+        if disabled && actor.admin_id == Some(user_id) {
+            return Err(ServerError::BadRequestError { code: "CANNOT_DISABLE_SELF", msg: "Invalid user", detail: "Can not disable yourself".to_owned() })
+        }
+        let now = Utc::now();
+        let txn = db.begin().await
+            .map_err(|e|
+                db_error("Database transaction error", e)
+            )?;
+
+        let user = users::Entity::find_by_id(user_id)
+            .one(&txn).await
+            .map_err(|e|
+                db_error("Database fetch error", e)
+            )?
+            .ok_or(ServerError::NotFound { code: "USER_NOT_FOUND", msg: "User not found" })?;
+
+        // Time of first disable is kept.
+        let disabled_at = if disabled { user.disabled_at.or(Some(now.fixed_offset())) } else { None };
+        let mut model = user.into_active_model();
+        model.disabled_at = Set(disabled_at);
+        let user = model.update(&txn).await
+            .map_err(|e|
+                db_error("Database update error", e)
+            )?;
+
+        let valid_after = if disabled {
+            Self::revoke_user_tokens(&txn, user_id, now).await?;
+            Some(now)
+        } else {
+            None
+        };
+        let action = if disabled { "user.disable" } else { "user.enable" };
+        Self::create_audit_log(&txn, actor, action, Some(user_id), None).await?;
+
+        txn.commit().await
+            .map_err(|e|
+                db_error("Database transaction error", e)
+            )?;
+        Ok((user, valid_after))
+    }
+
+    /// Force user to reset password by admin.
+    /// Every token and session is revoked, and login is refused until password is reset by the returned token.
+    /// Return user, raw reset token and new tokens_valid_after.
+    pub async fn require_password_reset(db: &DbConn, config: &AuthConfig, actor: &AuditActor, user_id: i32) -> Result<(users::Model, String, DateTime<Utc>), ServerError> {
+        let now = Utc::now();
+        let txn = db.begin().await
+            .map_err(|e|
+                db_error("Database transaction error", e)
+            )?;
+
+        let user = users::Entity::find_by_id(user_id)
+            .one(&txn).await
+            .map_err(|e|
+                db_error("Database fetch error", e)
+            )?
+            .ok_or(ServerError::NotFound { code: "USER_NOT_FOUND", msg: "User not found" })?;
+
+        let mut model = user.into_active_model();
+        model.password_reset_required = Set(true);
+        let user = model.update(&txn).await
+            .map_err(|e|
+                db_error("Database update error", e)
+            )?;
+
+        Self::revoke_user_tokens(&txn, user_id, now).await?;
+        let token = Self::insert_password_reset_token(&txn, config, user_id).await?;
+        Self::create_audit_log(&txn, actor, "user.password_reset", Some(user_id), None).await?;
+
+        txn.commit().await
+            .map_err(|e|
+                db_error("Database transaction error", e)
+            )?;
+        Ok((user, token, now))
+    }
+
+    /// Change role of user, used by server command.
+    /// Tokens are revoked if role is changed, so no token has old role.
+    pub async fn set_user_role(db: &DbConn, actor: &AuditActor, username: &str, role: &str) -> Result<users::Model, ServerError> {
+        if !users::ROLES.contains(&role) {
+            return Err(ServerError::BadRequestError { code: "INVALID_ROLE", msg: "Invalid role", detail: format!("Role must be one of {}", users::ROLES.join(", ")) })
+        }
+        let now = Utc::now();
+        let txn = db.begin().await
+            .map_err(|e|
+                db_error("Database transaction error", e)
+            )?;
+
+        let user = users::Entity::find()
+            .filter(users::Column::Username.eq(username))
+            .one(&txn).await
+            .map_err(|e|
+                db_error("Database fetch error", e)
+            )?
+            .ok_or(ServerError::NotFound { code: "USER_NOT_FOUND", msg: "User not found" })?;
+        if user.role == role {
+            return Ok(user)
+        }
+
+        let mut model = user.into_active_model();
+        model.role = Set(role.to_owned());
+        let user = model.update(&txn).await
+            .map_err(|e|
+                db_error("Database update error", e)
+            )?;
+
+        Self::revoke_user_tokens(&txn, user.id, now).await?;
+        Self::create_audit_log(&txn, actor, "user.role", Some(user.id), Some(role.to_owned())).await?;
+
+        txn.commit().await
+            .map_err(|e|
+                db_error("Database transaction error", e)
+            )?;
+        Ok(user)
+    }
+
+    /// Get audit logs, newest first.
+    pub async fn get_audit_logs(db: &DbConn, query: AuditQuery) -> Result<AuditPage, ServerError> {
+        let per_page = query.per_page.unwrap_or(DEFAULT_ADMIN_PER_PAGE).min(MAX_ADMIN_PER_PAGE);
+        if per_page == 0 {
+            return Err(ServerError::BadRequestError { code: "INVALID_QUERY", msg: "Invalid query", detail: "per_page must be greater than 0".to_owned() })
+        }
+
+        let mut condition = Condition::all();
+        if let Some(admin_id) = query.admin_id {
+            condition = condition.add(admin_audit_logs::Column::AdminId.eq(admin_id));
+        }
+        if let Some(target_user_id) = query.target_user_id {
+            condition = condition.add(admin_audit_logs::Column::TargetUserId.eq(target_user_id));
+        }
+        if let Some(action) = query.action {
+            condition = condition.add(admin_audit_logs::Column::Action.eq(action));
+        }
+
+        let paginator = admin_audit_logs::Entity::find()
+            .filter(condition)
+            .order_by_desc(admin_audit_logs::Column::Id)
+            .paginate(db, per_page);
+        let counts = paginator.num_items_and_pages().await
+            .map_err(|e|
+                db_error("Database fetch error", e)
+            )?;
+        let items = paginator.fetch_page(query.page).await
+            .map_err(|e|
+                db_error("Database fetch error", e)
+            )?;
+
+        Ok(AuditPage {
+            items,
+            total: counts.number_of_items,
+            page: query.page,
+            per_page,
+            total_pages: counts.number_of_pages,
+        })
     }
 }
@@ -0,0 +1,171 @@
+use argon2::{
+    password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Algorithm, Argon2, Params, Version,
+};
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::{mpsc, Arc, Mutex},
+    thread,
+};
+use tokio::sync::{oneshot, OnceCell};
+use myconfig::{PasswordAlgorithm, PasswordConfig};
+use myerror::ServerError;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// Dedicated threads for password hashing, so slow hashes do not block async workers.
+/// Queue is bounded, job is rejected with 503 when every thread is busy and queue is full.
+pub struct HashPool {
+    config: Arc<PasswordConfig>,
+    sender: mpsc::SyncSender<Job>,
+    /// Hash of random password, verified when login user does not exist.
+    dummy: OnceCell<String>,
+}
+
+impl HashPool {
+    /// Threads stop when pool is dropped.
+    pub fn new(config: &PasswordConfig) -> Self {
+        let (sender, receiver) = mpsc::sync_channel::<Job>(config.hash_queue_size);
+        let receiver = Arc::new(Mutex::new(receiver));
+        for i in 0..config.hash_workers {
+            let receiver = receiver.clone();
+            thread::Builder::new()
+                .name(format!("password-hash-{}", i))
+                .spawn(move || loop {
+                    // Lock is released before running the job.
+                    let job = match receiver.lock().unwrap().recv() {
+                        Ok(job) => job,
+                        Err(_) => break,
+                    };
+                    // Panicked job drops its result sender, thread keeps working.
+                    let _ = panic::catch_unwind(AssertUnwindSafe(job));
+                })
+                .expect("Password hash thread spawn error");
+        }
+        Self { config: Arc::new(config.clone()), sender, dummy: OnceCell::new() }
+    }
+
+    /// Hash of password in configured algorithm, refer to hash.
+    pub async fn hash(&self, password: &str) -> Result<String, ServerError> {
+        let password = password.to_owned();
+        self.run(move |config| hash(config, &password)).await?
+            .map_err(|e|
+                ServerError::InternalServerError { msg: "Password crypt error", detail: e }
+            )
+    }
+
+    /// Verify password against hash in any supported algorithm, refer to verify.
+    pub async fn verify(&self, password: &str, hash: &str) -> Result<Verified, ServerError> {
+        let (password, hash) = (password.to_owned(), hash.to_owned());
+        self.run(move |config| verify(config, &password, &hash)).await?
+            .map_err(|e|
+                ServerError::InternalServerError { msg: "Password decrypt error", detail: e }
+            )
+    }
+
+    /// Hash of random password in configured algorithm, made once on first use.
+    /// Verified when login user does not exist, so the time is same as users with current hash.
+    /// Users whose hash is not upgraded yet take other time until they log in again,
+    /// which is accepted instead of counting stored hashes to follow their algorithms.
+    pub async fn dummy_hash(&self) -> Result<&str, ServerError> {
+        self.dummy.get_or_try_init(|| async {
+            self.hash(&hex::encode(rand::random::<[u8; 16]>())).await
+        }).await
+            .map(|hash| hash.as_str())
+    }
+
+    async fn run<T, F>(&self, f: F) -> Result<T, ServerError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&PasswordConfig) -> T + Send + 'static,
+    {
+        let (tx, rx) = oneshot::channel();
+        let config = self.config.clone();
+        let job: Job = Box::new(move || {
+            // Requester may be gone already.
+            let _ = tx.send(f(&config));
+        });
+        self.sender.try_send(job)
+            .map_err(|e| match e {
+                mpsc::TrySendError::Full(_) => ServerError::ServiceUnavailableError {
+                    code: "SERVER_BUSY",
+                    msg: "Server is busy",
+                    detail: "Too many password hashing requests, try again later".to_owned(),
+                },
+                mpsc::TrySendError::Disconnected(_) => ServerError::InternalServerError {
+                    msg: "Password hash pool error",
+                    detail: "Hashing threads are stopped".to_owned(),
+                },
+            })?;
+        rx.await
+            .map_err(|_|
+                ServerError::InternalServerError { msg: "Password hash pool error", detail: "Hashing job panicked".to_owned() }
+            )
+    }
+}
+
+/// Result of verifying a password against stored hash.
+pub struct Verified {
+    pub valid: bool,
+    /// Hash uses other algorithm or parameters than config, so it should be hashed again.
+    pub needs_rehash: bool,
+}
+
+/// Hash of password in configured algorithm.
+/// Argon2id is PHC string. (`$argon2id$v=19$m=..,t=..,p=..$salt$hash`)
+/// Bcrypt is `$2b$cost$...` of 60 letters.
+pub fn hash(config: &PasswordConfig, password: &str) -> Result<String, String> {
+    match config.algorithm {
+        PasswordAlgorithm::Argon2id => {
+            let salt = SaltString::encode_b64(&rand::random::<[u8; 16]>())
+                .map_err(|e| e.to_string())?;
+            argon2(config)?
+                .hash_password(password.as_bytes(), &salt)
+                .map(|hash| hash.to_string())
+                .map_err(|e| e.to_string())
+        }
+        PasswordAlgorithm::Bcrypt => bcrypt::hash(password, config.bcrypt_cost)
+            .map_err(|e| e.to_string()),
+    }
+}
+
+/// Verify password against hash in any supported algorithm.
+/// Error is only for malformed hash.
+pub fn verify(config: &PasswordConfig, password: &str, hash: &str) -> Result<Verified, String> {
+    // Bcrypt hash has prefix of $2a$, $2b$, $2x$ or $2y$.
+    if hash.starts_with("$2") {
+        let valid = bcrypt::verify(password, hash).map_err(|e| e.to_string())?;
+        let needs_rehash = config.algorithm != PasswordAlgorithm::Bcrypt
+            || bcrypt_cost(hash) != Some(config.bcrypt_cost);
+        return Ok(Verified { valid, needs_rehash })
+    }
+
+    let parsed = PasswordHash::new(hash).map_err(|e| e.to_string())?;
+    // Verifying uses parameters in hash, not in config.
+    let valid = Argon2::default()
+        .verify_password(password.as_bytes(), &parsed)
+        .is_ok();
+    let needs_rehash = config.algorithm != PasswordAlgorithm::Argon2id
+        || parsed.algorithm != Algorithm::Argon2id.ident()
+        || parsed.version != Some(Version::V0x13.into())
+        || Params::try_from(&parsed).map_or(true, |params|
+            params.m_cost() != config.argon2_memory_kib
+                || params.t_cost() != config.argon2_iterations
+                || params.p_cost() != config.argon2_parallelism
+        );
+    Ok(Verified { valid, needs_rehash })
+}
+
+fn argon2(config: &PasswordConfig) -> Result<Argon2<'static>, String> {
+    let params = Params::new(
+        config.argon2_memory_kib,
+        config.argon2_iterations,
+        config.argon2_parallelism,
+        None,
+    ).map_err(|e| e.to_string())?;
+    Ok(Argon2::new(Algorithm::Argon2id, Version::V0x13, params))
+}
+
+fn bcrypt_cost(hash: &str) -> Option<u32> {
+    hash.split('$').nth(2)?.parse().ok()
+}
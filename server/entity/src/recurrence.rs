@@ -0,0 +1,305 @@
+use std::{fmt, str::FromStr};
+use chrono::{Datelike, Duration, Months, NaiveDate, Weekday};
+
+/// Upper bound of periods searched for next occurrence.
+/// Rule like `FREQ=YEARLY;BYMONTHDAY=30` started on February never matches.
+const MAX_PERIODS: u32 = 10_000;
+
+/// Largest INTERVAL of each FREQ, so a period does not overflow the date range.
+fn max_interval(freq: Frequency) -> u32 {
+    match freq {
+        Frequency::Daily => 1000,
+        Frequency::Weekly => 520,
+        Frequency::Monthly => 120,
+        Frequency::Yearly => 100,
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// Weekday of BYDAY. Ordinal is used only in monthly rule. (e.g. 1MO, -1FR)
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ByDay {
+    pub ordinal: Option<i32>,
+    pub weekday: Weekday,
+}
+
+/// Subset of RFC 5545 RRULE for date based todos.
+/// Supported parts are FREQ, INTERVAL, COUNT, UNTIL, BYDAY and BYMONTHDAY(monthly only).
+#[derive(Clone, PartialEq, Debug)]
+pub struct RecurrenceRule {
+    pub freq: Frequency,
+    pub interval: u32,
+    pub count: Option<u32>,
+    pub until: Option<NaiveDate>,
+    pub by_day: Vec<ByDay>,
+    pub by_month_day: Vec<i32>,
+}
+
+fn weekday_from_str(s: &str) -> Option<Weekday> {
+    Some(match s {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return None,
+    })
+}
+
+fn weekday_to_str(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "MO",
+        Weekday::Tue => "TU",
+        Weekday::Wed => "WE",
+        Weekday::Thu => "TH",
+        Weekday::Fri => "FR",
+        Weekday::Sat => "SA",
+        Weekday::Sun => "SU",
+    }
+}
+
+impl FromStr for RecurrenceRule {
+    type Err = String;
+
+    /// Parse RRULE value, `RRULE:` prefix is allowed.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let s = s.strip_prefix("RRULE:").unwrap_or(s);
+
+        let mut freq = None;
+        let mut rule = RecurrenceRule {
+            freq: Frequency::Daily,
+            interval: 1,
+            count: None,
+            until: None,
+            by_day: Vec::new(),
+            by_month_day: Vec::new(),
+        };
+
+        for part in s.split(';') {
+            let (name, value) = part.split_once('=')
+                .ok_or_else(|| format!("Invalid rule part {}", part))?;
+            let invalid = || format!("Invalid {} value {}", name, value);
+            match name.to_ascii_uppercase().as_str() {
+                "FREQ" => freq = Some(match value.to_ascii_uppercase().as_str() {
+                    "DAILY" => Frequency::Daily,
+                    "WEEKLY" => Frequency::Weekly,
+                    "MONTHLY" => Frequency::Monthly,
+                    "YEARLY" => Frequency::Yearly,
+                    _ => return Err(format!("Unsupported FREQ {}", value)),
+                }),
+                "INTERVAL" => {
+                    rule.interval = value.parse().map_err(|_| invalid())?;
+                    if rule.interval == 0 {
+                        return Err(invalid())
+                    }
+                },
+                "COUNT" => {
+                    let count: u32 = value.parse().map_err(|_| invalid())?;
+                    if count == 0 {
+                        return Err(invalid())
+                    }
+                    rule.count = Some(count);
+                },
+                "UNTIL" => {
+                    // Only date is used. (e.g. 20261231 or 20261231T235959Z)
+                    let date = value.get(..8).ok_or_else(invalid)?;
+                    rule.until = Some(NaiveDate::parse_from_str(date, "%Y%m%d").map_err(|_| invalid())?);
+                },
+                "BYDAY" => for day in value.split(',') {
+                    // Weekday is split by byte index.
+                    if !day.is_ascii() {
+                        return Err(invalid())
+                    }
+                    let day = day.to_ascii_uppercase();
+                    let split = day.len().checked_sub(2).ok_or_else(invalid)?;
+                    let (ordinal, weekday) = day.split_at(split);
+                    let weekday = weekday_from_str(weekday).ok_or_else(invalid)?;
+                    let ordinal = match ordinal {
+                        "" => None,
+                        ordinal => {
+                            let ordinal: i32 = ordinal.parse().map_err(|_| invalid())?;
+                            if ordinal == 0 || ordinal.abs() > 5 {
+                                return Err(invalid())
+                            }
+                            Some(ordinal)
+                        },
+                    };
+                    rule.by_day.push(ByDay { ordinal, weekday });
+                },
+                "BYMONTHDAY" => for day in value.split(',') {
+                    let day: i32 = day.parse().map_err(|_| invalid())?;
+                    if day == 0 || day.abs() > 31 {
+                        return Err(invalid())
+                    }
+                    rule.by_month_day.push(day);
+                },
+                _ => return Err(format!("Unsupported rule part {}", name)),
+            }
+        }
+
+        rule.freq = freq.ok_or("FREQ is required")?;
+        if rule.interval > max_interval(rule.freq) {
+            return Err(format!("INTERVAL must be at most {} with this FREQ", max_interval(rule.freq)))
+        }
+        if rule.count.is_some() && rule.until.is_some() {
+            return Err("COUNT and UNTIL can not be used together".to_owned())
+        }
+        if !rule.by_month_day.is_empty() && rule.freq != Frequency::Monthly {
+            return Err("BYMONTHDAY is supported only with FREQ=MONTHLY".to_owned())
+        }
+        if rule.by_day.iter().any(|d| d.ordinal.is_some()) && rule.freq != Frequency::Monthly {
+            return Err("BYDAY ordinal is supported only with FREQ=MONTHLY".to_owned())
+        }
+        if !rule.by_day.is_empty() && rule.freq == Frequency::Yearly {
+            return Err("BYDAY is not supported with FREQ=YEARLY".to_owned())
+        }
+        Ok(rule)
+    }
+}
+
+impl fmt::Display for RecurrenceRule {
+    /// Canonical RRULE value without prefix.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let freq = match self.freq {
+            Frequency::Daily => "DAILY",
+            Frequency::Weekly => "WEEKLY",
+            Frequency::Monthly => "MONTHLY",
+            Frequency::Yearly => "YEARLY",
+        };
+        write!(f, "FREQ={}", freq)?;
+        if self.interval != 1 {
+            write!(f, ";INTERVAL={}", self.interval)?;
+        }
+        if let Some(count) = self.count {
+            write!(f, ";COUNT={}", count)?;
+        }
+        if let Some(until) = self.until {
+            write!(f, ";UNTIL={}", until.format("%Y%m%d"))?;
+        }
+        if !self.by_day.is_empty() {
+            let days: Vec<String> = self.by_day.iter()
+                .map(|d| format!("{}{}", d.ordinal.map(|o| o.to_string()).unwrap_or_default(), weekday_to_str(d.weekday)))
+                .collect();
+            write!(f, ";BYDAY={}", days.join(","))?;
+        }
+        if !self.by_month_day.is_empty() {
+            let days: Vec<String> = self.by_month_day.iter().map(i32::to_string).collect();
+            write!(f, ";BYMONTHDAY={}", days.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+/// Last day of the month, None if next month is out of date range.
+fn month_length(year: i32, month: u32) -> Option<u32> {
+    let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+    Some(first.checked_add_months(Months::new(1))?.pred_opt()?.day())
+}
+
+impl RecurrenceRule {
+    /// Candidate dates of nth period from dtstart, sorted.
+    /// None if the period is out of date range, so there is no more occurrence.
+    fn period_dates(&self, dtstart: NaiveDate, n: u32) -> Option<Vec<NaiveDate>> {
+        let step = n.checked_mul(self.interval)?;
+        let mut dates = match self.freq {
+            Frequency::Daily => {
+                let date = dtstart.checked_add_signed(Duration::days(step as i64))?;
+                if self.by_day.is_empty() || self.by_day.iter().any(|d| d.weekday == date.weekday()) {
+                    vec![date]
+                } else {
+                    vec![]
+                }
+            },
+            Frequency::Weekly => {
+                // Week starts on Monday.
+                let monday = dtstart.checked_sub_signed(Duration::days(dtstart.weekday().num_days_from_monday() as i64))?
+                    .checked_add_signed(Duration::weeks(step as i64))?;
+                if self.by_day.is_empty() {
+                    vec![monday.checked_add_signed(Duration::days(dtstart.weekday().num_days_from_monday() as i64))?]
+                } else {
+                    self.by_day.iter()
+                        .map(|d| monday.checked_add_signed(Duration::days(d.weekday.num_days_from_monday() as i64)))
+                        .collect::<Option<_>>()?
+                }
+            },
+            Frequency::Monthly => {
+                let first = dtstart.with_day(1)?.checked_add_months(Months::new(step))?;
+                let (year, month) = (first.year(), first.month());
+                let length = month_length(year, month)?;
+
+                let mut dates = Vec::new();
+                for &day in &self.by_month_day {
+                    let day = if day > 0 { day } else { length as i32 + day + 1 };
+                    if day >= 1 {
+                        dates.extend(NaiveDate::from_ymd_opt(year, month, day as u32));
+                    }
+                }
+                for by_day in &self.by_day {
+                    let days = (1..=length)
+                        .filter_map(|d| NaiveDate::from_ymd_opt(year, month, d))
+                        .filter(|d| d.weekday() == by_day.weekday);
+                    match by_day.ordinal {
+                        None => dates.extend(days),
+                        Some(o) if o > 0 => dates.extend(days.clone().nth(o as usize - 1)),
+                        Some(o) => dates.extend(days.rev().nth((-o) as usize - 1)),
+                    }
+                }
+                if self.by_month_day.is_empty() && self.by_day.is_empty() {
+                    // Invalid date like 31th of April is skipped.
+                    dates.extend(NaiveDate::from_ymd_opt(year, month, dtstart.day()));
+                }
+                dates
+            },
+            Frequency::Yearly => {
+                let year = i32::try_from(step).ok().and_then(|step| dtstart.year().checked_add(step))?;
+                if year > NaiveDate::MAX.year() {
+                    return None
+                }
+                // Invalid date like 29th of February is skipped.
+                NaiveDate::from_ymd_opt(year, dtstart.month(), dtstart.day())
+                    .into_iter().collect()
+            },
+        };
+        dates.sort();
+        dates.dedup();
+        Some(dates)
+    }
+
+    /// First occurrence after the date. dtstart is always the first occurrence.
+    /// None if the series is over by COUNT or UNTIL.
+    pub fn next_after(&self, dtstart: NaiveDate, after: NaiveDate) -> Option<NaiveDate> {
+        let mut index = 1;
+        if dtstart > after {
+            return Some(dtstart)
+        }
+
+        for n in 0..MAX_PERIODS {
+            for date in self.period_dates(dtstart, n)? {
+                if date <= dtstart {
+                    continue
+                }
+                if self.until.is_some_and(|until| date > until) {
+                    return None
+                }
+                index += 1;
+                if self.count.is_some_and(|count| index > count) {
+                    return None
+                }
+                if date > after {
+                    return Some(date)
+                }
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,66 @@
+use base32::Alphabet;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use sha1::Sha1;
+
+/// Seconds of one time step.
+const STEP: i64 = 30;
+
+const DIGITS: u32 = 6;
+
+/// Steps before and after current step which are accepted, for clock drift.
+const SKEW: i64 = 1;
+
+const ALPHABET: Alphabet = Alphabet::RFC4648 { padding: false };
+
+/// TOTP of RFC 6238 with authenticator app defaults. (HMAC-SHA1, 6 digits, 30 seconds)
+/// Secret is base32 string, it is shown to user as is or in otpauth URI.
+pub struct Totp {
+    key: Vec<u8>,
+}
+
+impl Totp {
+    /// None if secret is not base32.
+    pub fn new(secret: &str) -> Option<Self> {
+        base32::decode(ALPHABET, secret).map(|key| Self { key })
+    }
+
+    /// New random secret of 160 bits, as recommended by RFC 4226.
+    pub fn generate_secret() -> String {
+        base32::encode(ALPHABET, &rand::random::<[u8; 20]>())
+    }
+
+    /// URI for authenticator apps, usually given as QR code.
+    pub fn uri(secret: &str, issuer: &str, account: &str) -> String {
+        let issuer = utf8_percent_encode(issuer, NON_ALPHANUMERIC).to_string();
+        let account = utf8_percent_encode(account, NON_ALPHANUMERIC);
+        format!(
+            "otpauth://totp/{}:{}?secret={}&issuer={}&algorithm=SHA1&digits={}&period={}",
+            issuer, account, secret, issuer, DIGITS, STEP
+        )
+    }
+
+    pub fn step(now: DateTime<Utc>) -> i64 {
+        now.timestamp().div_euclid(STEP)
+    }
+
+    /// Code of the time step.
+    pub fn code(&self, step: i64) -> String {
+        let mut mac = Hmac::<Sha1>::new_from_slice(&self.key).unwrap();
+        mac.update(&step.to_be_bytes());
+        let hash = mac.finalize().into_bytes();
+
+        // Dynamic truncation of RFC 4226.
+        let offset = (hash[hash.len() - 1] & 0x0f) as usize;
+        let binary = u32::from_be_bytes([hash[offset], hash[offset + 1], hash[offset + 2], hash[offset + 3]]) & 0x7fff_ffff;
+        format!("{:0width$}", binary % 10u32.pow(DIGITS), width = DIGITS as usize)
+    }
+
+    /// Step of matching code near now, None if code is wrong.
+    pub fn verify(&self, code: &str, now: DateTime<Utc>) -> Option<i64> {
+        let code = code.trim();
+        let step = Self::step(now);
+        (step - SKEW..=step + SKEW).find(|s| self.code(*s) == code)
+    }
+}
@@ -0,0 +1,76 @@
+use std::borrow::Cow;
+use serde_json::{Map, Value};
+use validator::{ValidationError, ValidationErrors, ValidationErrorsKind};
+use myerror::{FieldError, FieldErrors, ServerError};
+
+/// Rule error with message, for custom validators.
+pub fn rule(code: &'static str, message: impl Into<Cow<'static, str>>) -> ValidationError {
+    let mut e = ValidationError::new(code);
+    e.message = Some(message.into());
+    e
+}
+
+/// Error of a field checked outside of validator. (e.g. rule between fields)
+pub fn field_error(field: &str, code: &'static str, message: impl Into<Cow<'static, str>>) -> ServerError {
+    let mut errors = FieldErrors::new();
+    errors.insert(field.to_owned(), vec![field_error_of(rule(code, message))]);
+    ServerError::ValidationError { msg: "Validation error", errors }
+}
+
+/// Every broken rule by field name.
+/// Nested field is joined with dot, item of list with index. (e.g. "items[0].name")
+pub fn validation_error(e: ValidationErrors) -> ServerError {
+    let mut errors = FieldErrors::new();
+    collect(&mut errors, None, e);
+    ServerError::ValidationError { msg: "Validation error", errors }
+}
+
+fn collect(errors: &mut FieldErrors, prefix: Option<&str>, e: ValidationErrors) {
+    for (field, kind) in e.into_errors() {
+        let field = match prefix {
+            Some(prefix) => format!("{}.{}", prefix, field),
+            None => field.to_owned(),
+        };
+        match kind {
+            ValidationErrorsKind::Field(list) => {
+                errors.entry(field).or_default()
+                    .extend(list.into_iter().map(field_error_of));
+            },
+            ValidationErrorsKind::Struct(nested) => collect(errors, Some(&field), *nested),
+            ValidationErrorsKind::List(items) => {
+                for (i, nested) in items {
+                    collect(errors, Some(&format!("{}[{}]", field, i)), *nested);
+                }
+            },
+        }
+    }
+}
+
+fn field_error_of(e: ValidationError) -> FieldError {
+    let message = match &e.message {
+        Some(message) => message.to_string(),
+        None => default_message(&e),
+    };
+    // Value of request is not returned, it may be password.
+    let params: Map<String, Value> = e.params.into_iter()
+        .filter(|(name, _)| name != "value")
+        .map(|(name, value)| (name.into_owned(), value))
+        .collect();
+    FieldError { code: e.code.into_owned(), message, params }
+}
+
+/// Message of built-in rules of validator.
+fn default_message(e: &ValidationError) -> String {
+    let param = |name: &str| e.params.get(name).map(|v| v.to_string());
+    match (e.code.as_ref(), param("min"), param("max")) {
+        ("length", Some(min), Some(max)) => format!("Length must be between {} and {}", min, max),
+        ("length", Some(min), None) => format!("Length must be at least {}", min),
+        ("length", None, Some(max)) => format!("Length must be at most {}", max),
+        ("range", Some(min), Some(max)) => format!("Value must be between {} and {}", min, max),
+        ("range", Some(min), None) => format!("Value must be at least {}", min),
+        ("range", None, Some(max)) => format!("Value must be at most {}", max),
+        ("email", ..) => "Must be an email address".to_owned(),
+        ("required", ..) => "Value is required".to_owned(),
+        (code, ..) => format!("Invalid value ({})", code),
+    }
+}
@@ -0,0 +1,46 @@
+pub use sea_orm_migration::prelude::*;
+
+mod m20230401_000001_create_table;
+mod m20261018_000001_create_refresh_tokens;
+mod m20261018_000002_create_tags;
+mod m20261018_000003_create_projects;
+mod m20261018_000004_add_todo_parent;
+mod m20261018_000005_create_series;
+mod m20261018_000006_add_due_at_and_time_zone;
+mod m20261018_000007_create_reminders;
+mod m20261018_000008_add_token_revocation;
+mod m20261018_000009_create_sessions;
+mod m20261018_000010_create_personal_access_tokens;
+mod m20261018_000011_create_password_reset_tokens;
+mod m20261018_000012_add_email_verification;
+mod m20261018_000013_add_totp;
+mod m20261018_000014_widen_user_password;
+mod m20261018_000015_add_roles;
+
+/// Versioned schema migrations.
+/// Applied versions are recorded in `seaql_migrations` table.
+pub struct Migrator;
+
+#[async_trait::async_trait]
+impl MigratorTrait for Migrator {
+    fn migrations() -> Vec<Box<dyn MigrationTrait>> {
+        vec![
+            Box::new(m20230401_000001_create_table::Migration),
+            Box::new(m20261018_000001_create_refresh_tokens::Migration),
+            Box::new(m20261018_000002_create_tags::Migration),
+            Box::new(m20261018_000003_create_projects::Migration),
+            Box::new(m20261018_000004_add_todo_parent::Migration),
+            Box::new(m20261018_000005_create_series::Migration),
+            Box::new(m20261018_000006_add_due_at_and_time_zone::Migration),
+            Box::new(m20261018_000007_create_reminders::Migration),
+            Box::new(m20261018_000008_add_token_revocation::Migration),
+            Box::new(m20261018_000009_create_sessions::Migration),
+            Box::new(m20261018_000010_create_personal_access_tokens::Migration),
+            Box::new(m20261018_000011_create_password_reset_tokens::Migration),
+            Box::new(m20261018_000012_add_email_verification::Migration),
+            Box::new(m20261018_000013_add_totp::Migration),
+            Box::new(m20261018_000014_widen_user_password::Migration),
+            Box::new(m20261018_000015_add_roles::Migration),
+        ]
+    }
+}
@@ -0,0 +1,79 @@
+use sea_orm_migration::prelude::*;
+
+/// Initial schema, same as former postgre-db/schemas.sql.
+/// Tables are created only if not exist, so database made by schemas.sql can be adopted.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Users::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(Users::Id).integer().not_null().auto_increment().primary_key())
+                    .col(ColumnDef::new(Users::Username).string_len(20).not_null().unique_key())
+                    .col(ColumnDef::new(Users::Password).char_len(60).not_null())
+                    .col(ColumnDef::new(Users::Email).string_len(255).not_null().unique_key())
+                    .col(ColumnDef::new(Users::CreatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(Todos::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(Todos::Id).integer().not_null().auto_increment().primary_key())
+                    .col(ColumnDef::new(Todos::UserId).integer().not_null())
+                    .col(ColumnDef::new(Todos::Name).string_len(100).not_null())
+                    .col(ColumnDef::new(Todos::Contents).string_len(255))
+                    .col(ColumnDef::new(Todos::DueDate).date())
+                    .col(ColumnDef::new(Todos::Completed).boolean().not_null().default(false))
+                    .col(ColumnDef::new(Todos::CreatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Todos::Table, Todos::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Todos::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Users::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum Users {
+    Table,
+    Id,
+    Username,
+    Password,
+    Email,
+    CreatedAt,
+}
+
+#[derive(Iden)]
+enum Todos {
+    Table,
+    Id,
+    UserId,
+    Name,
+    Contents,
+    DueDate,
+    Completed,
+    CreatedAt,
+}
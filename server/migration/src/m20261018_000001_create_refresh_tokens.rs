@@ -0,0 +1,69 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(RefreshTokens::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(RefreshTokens::Id).integer().not_null().auto_increment().primary_key())
+                    .col(ColumnDef::new(RefreshTokens::UserId).integer().not_null())
+                    .col(ColumnDef::new(RefreshTokens::TokenHash).char_len(64).not_null().unique_key())
+                    .col(ColumnDef::new(RefreshTokens::Family).char_len(32).not_null())
+                    .col(ColumnDef::new(RefreshTokens::Used).boolean().not_null().default(false))
+                    .col(ColumnDef::new(RefreshTokens::Revoked).boolean().not_null().default(false))
+                    .col(ColumnDef::new(RefreshTokens::ExpiresAt).timestamp().not_null())
+                    .col(ColumnDef::new(RefreshTokens::CreatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(RefreshTokens::Table, RefreshTokens::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Revoke whole family at once.
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-refresh_tokens-family")
+                    .table(RefreshTokens::Table)
+                    .col(RefreshTokens::Family)
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(RefreshTokens::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum Users {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum RefreshTokens {
+    Table,
+    Id,
+    UserId,
+    TokenHash,
+    Family,
+    Used,
+    Revoked,
+    ExpiresAt,
+    CreatedAt,
+}
@@ -0,0 +1,116 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Tags::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(Tags::Id).integer().not_null().auto_increment().primary_key())
+                    .col(ColumnDef::new(Tags::UserId).integer().not_null())
+                    .col(ColumnDef::new(Tags::Name).string_len(50).not_null())
+                    .col(ColumnDef::new(Tags::CreatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Tags::Table, Tags::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Tag name is unique per user.
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-tags-user_id-name")
+                    .table(Tags::Table)
+                    .col(Tags::UserId)
+                    .col(Tags::Name)
+                    .unique()
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(TodoTags::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(TodoTags::TodoId).integer().not_null())
+                    .col(ColumnDef::new(TodoTags::TagId).integer().not_null())
+                    .primary_key(Index::create().col(TodoTags::TodoId).col(TodoTags::TagId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(TodoTags::Table, TodoTags::TodoId)
+                            .to(Todos::Table, Todos::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(TodoTags::Table, TodoTags::TagId)
+                            .to(Tags::Table, Tags::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Filter todos by tag.
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-todo_tags-tag_id")
+                    .table(TodoTags::Table)
+                    .col(TodoTags::TagId)
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(TodoTags::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Tags::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum Users {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum Todos {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum Tags {
+    Table,
+    Id,
+    UserId,
+    Name,
+    CreatedAt,
+}
+
+#[derive(Iden)]
+enum TodoTags {
+    Table,
+    TodoId,
+    TagId,
+}
@@ -0,0 +1,99 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Projects::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(Projects::Id).integer().not_null().auto_increment().primary_key())
+                    .col(ColumnDef::new(Projects::UserId).integer().not_null())
+                    .col(ColumnDef::new(Projects::Name).string_len(100).not_null())
+                    .col(ColumnDef::new(Projects::Color).char_len(7))
+                    .col(ColumnDef::new(Projects::SortOrder).integer().not_null().default(0))
+                    .col(ColumnDef::new(Projects::Archived).boolean().not_null().default(false))
+                    .col(ColumnDef::new(Projects::CreatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Projects::Table, Projects::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Todo without project is in inbox.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Todos::Table)
+                    .add_column(ColumnDef::new(Todos::ProjectId).integer())
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk-todos-project_id")
+                            .from_tbl(Todos::Table)
+                            .from_col(Todos::ProjectId)
+                            .to_tbl(Projects::Table)
+                            .to_col(Projects::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-todos-project_id")
+                    .table(Todos::Table)
+                    .col(Todos::ProjectId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Todos::Table)
+                    .drop_column(Todos::ProjectId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Projects::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum Users {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum Todos {
+    Table,
+    ProjectId,
+}
+
+#[derive(Iden)]
+enum Projects {
+    Table,
+    Id,
+    UserId,
+    Name,
+    Color,
+    SortOrder,
+    Archived,
+    CreatedAt,
+}
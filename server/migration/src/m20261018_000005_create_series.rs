@@ -0,0 +1,95 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Series::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(Series::Id).integer().not_null().auto_increment().primary_key())
+                    .col(ColumnDef::new(Series::UserId).integer().not_null())
+                    .col(ColumnDef::new(Series::Rrule).string_len(255).not_null())
+                    .col(ColumnDef::new(Series::Dtstart).date().not_null())
+                    .col(ColumnDef::new(Series::CreatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Series::Table, Series::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Todos are kept when series is stopped.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Todos::Table)
+                    .add_column(ColumnDef::new(Todos::SeriesId).integer())
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk-todos-series_id")
+                            .from_tbl(Todos::Table)
+                            .from_col(Todos::SeriesId)
+                            .to_tbl(Series::Table)
+                            .to_col(Series::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-todos-series_id")
+                    .table(Todos::Table)
+                    .col(Todos::SeriesId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Todos::Table)
+                    .drop_column(Todos::SeriesId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Series::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum Users {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum Todos {
+    Table,
+    SeriesId,
+}
+
+#[derive(Iden)]
+enum Series {
+    Table,
+    Id,
+    UserId,
+    Rrule,
+    Dtstart,
+    CreatedAt,
+}
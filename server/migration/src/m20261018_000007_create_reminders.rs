@@ -0,0 +1,150 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Reminders::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(Reminders::Id).integer().not_null().auto_increment().primary_key())
+                    .col(ColumnDef::new(Reminders::UserId).integer().not_null())
+                    .col(ColumnDef::new(Reminders::TodoId).integer().not_null())
+                    // Either absolute time or minutes before due.
+                    .col(ColumnDef::new(Reminders::RemindAt).timestamp_with_time_zone())
+                    .col(ColumnDef::new(Reminders::OffsetMinutes).integer())
+                    .col(ColumnDef::new(Reminders::Channel).string_len(16).not_null())
+                    // Next time to fire, null if todo has no due.
+                    .col(ColumnDef::new(Reminders::FireAt).timestamp_with_time_zone())
+                    .col(ColumnDef::new(Reminders::SentAt).timestamp_with_time_zone())
+                    .col(ColumnDef::new(Reminders::Attempts).integer().not_null().default(0))
+                    .col(ColumnDef::new(Reminders::LastError).string_len(255))
+                    .col(ColumnDef::new(Reminders::CreatedAt).timestamp_with_time_zone().not_null().default(Expr::current_timestamp()))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Reminders::Table, Reminders::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Reminders::Table, Reminders::TodoId)
+                            .to(Todos::Table, Todos::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-reminders-todo_id")
+                    .table(Reminders::Table)
+                    .col(Reminders::TodoId)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Scheduler looks up only unsent reminders.
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"CREATE INDEX "idx-reminders-fire_at" ON "reminders" ("fire_at") WHERE "sent_at" IS NULL"#
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(Notifications::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(Notifications::Id).integer().not_null().auto_increment().primary_key())
+                    .col(ColumnDef::new(Notifications::UserId).integer().not_null())
+                    .col(ColumnDef::new(Notifications::TodoId).integer())
+                    .col(ColumnDef::new(Notifications::Title).string_len(100).not_null())
+                    .col(ColumnDef::new(Notifications::Body).string_len(255).not_null())
+                    .col(ColumnDef::new(Notifications::Read).boolean().not_null().default(false))
+                    .col(ColumnDef::new(Notifications::CreatedAt).timestamp_with_time_zone().not_null().default(Expr::current_timestamp()))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Notifications::Table, Notifications::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    // Notification is kept after todo is deleted.
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Notifications::Table, Notifications::TodoId)
+                            .to(Todos::Table, Todos::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-notifications-user_id")
+                    .table(Notifications::Table)
+                    .col(Notifications::UserId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Notifications::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Reminders::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum Users {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum Todos {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum Reminders {
+    Table,
+    Id,
+    UserId,
+    TodoId,
+    RemindAt,
+    OffsetMinutes,
+    Channel,
+    FireAt,
+    SentAt,
+    Attempts,
+    LastError,
+    CreatedAt,
+}
+
+#[derive(Iden)]
+enum Notifications {
+    Table,
+    Id,
+    UserId,
+    TodoId,
+    Title,
+    Body,
+    Read,
+    CreatedAt,
+}
@@ -0,0 +1,81 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Access tokens revoked by logout, kept until they expire.
+        manager
+            .create_table(
+                Table::create()
+                    .table(RevokedTokens::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(RevokedTokens::Jti).char_len(32).not_null().primary_key())
+                    .col(ColumnDef::new(RevokedTokens::UserId).integer().not_null())
+                    .col(ColumnDef::new(RevokedTokens::ExpiresAt).timestamp_with_time_zone().not_null())
+                    .col(ColumnDef::new(RevokedTokens::CreatedAt).timestamp_with_time_zone().not_null().default(Expr::current_timestamp()))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(RevokedTokens::Table, RevokedTokens::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Servers load revocations created after their last sync.
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-revoked_tokens-created_at")
+                    .table(RevokedTokens::Table)
+                    .col(RevokedTokens::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Every token issued before this time is revoked. (log out all sessions)
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Users::Table)
+                    .add_column(ColumnDef::new(Users::TokensValidAfter).timestamp_with_time_zone())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Users::Table)
+                    .drop_column(Users::TokensValidAfter)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(RevokedTokens::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum Users {
+    Table,
+    Id,
+    TokensValidAfter,
+}
+
+#[derive(Iden)]
+enum RevokedTokens {
+    Table,
+    Jti,
+    UserId,
+    ExpiresAt,
+    CreatedAt,
+}
@@ -0,0 +1,67 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(PersonalAccessTokens::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(PersonalAccessTokens::Id).integer().not_null().auto_increment().primary_key())
+                    .col(ColumnDef::new(PersonalAccessTokens::UserId).integer().not_null())
+                    .col(ColumnDef::new(PersonalAccessTokens::Name).string_len(50).not_null())
+                    .col(ColumnDef::new(PersonalAccessTokens::TokenHash).char_len(64).not_null().unique_key())
+                    // Space separated scopes.
+                    .col(ColumnDef::new(PersonalAccessTokens::Scopes).string_len(255).not_null())
+                    // Null if token never expires.
+                    .col(ColumnDef::new(PersonalAccessTokens::ExpiresAt).timestamp_with_time_zone())
+                    .col(ColumnDef::new(PersonalAccessTokens::CreatedAt).timestamp_with_time_zone().not_null().default(Expr::current_timestamp()))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(PersonalAccessTokens::Table, PersonalAccessTokens::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-personal_access_tokens-user_id")
+                    .table(PersonalAccessTokens::Table)
+                    .col(PersonalAccessTokens::UserId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PersonalAccessTokens::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum Users {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum PersonalAccessTokens {
+    Table,
+    Id,
+    UserId,
+    Name,
+    TokenHash,
+    Scopes,
+    ExpiresAt,
+    CreatedAt,
+}
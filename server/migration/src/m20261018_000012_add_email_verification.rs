@@ -0,0 +1,87 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Null until user confirms the email.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Users::Table)
+                    .add_column(ColumnDef::new(Users::EmailVerifiedAt).timestamp_with_time_zone())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(EmailVerificationTokens::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(EmailVerificationTokens::Id).integer().not_null().auto_increment().primary_key())
+                    .col(ColumnDef::new(EmailVerificationTokens::UserId).integer().not_null())
+                    // Address to verify, user's email becomes this when confirmed.
+                    .col(ColumnDef::new(EmailVerificationTokens::Email).string_len(255).not_null())
+                    .col(ColumnDef::new(EmailVerificationTokens::TokenHash).char_len(64).not_null().unique_key())
+                    .col(ColumnDef::new(EmailVerificationTokens::ExpiresAt).timestamp_with_time_zone().not_null())
+                    // Single-use, set when confirmed or replaced by new token.
+                    .col(ColumnDef::new(EmailVerificationTokens::UsedAt).timestamp_with_time_zone())
+                    .col(ColumnDef::new(EmailVerificationTokens::CreatedAt).timestamp_with_time_zone().not_null().default(Expr::current_timestamp()))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(EmailVerificationTokens::Table, EmailVerificationTokens::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-email_verification_tokens-user_id")
+                    .table(EmailVerificationTokens::Table)
+                    .col(EmailVerificationTokens::UserId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(EmailVerificationTokens::Table).to_owned())
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Users::Table)
+                    .drop_column(Users::EmailVerifiedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum Users {
+    Table,
+    Id,
+    EmailVerifiedAt,
+}
+
+#[derive(Iden)]
+enum EmailVerificationTokens {
+    Table,
+    Id,
+    UserId,
+    Email,
+    TokenHash,
+    ExpiresAt,
+    UsedAt,
+    CreatedAt,
+}
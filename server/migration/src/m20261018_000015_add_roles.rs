@@ -0,0 +1,111 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Users::Table)
+                    // "user" or "admin"
+                    .add_column(ColumnDef::new(Users::Role).string_len(16).not_null().default("user"))
+                    // Disabled user can not login.
+                    .add_column(ColumnDef::new(Users::DisabledAt).timestamp_with_time_zone())
+                    // Set by admin, cleared when password is reset.
+                    .add_column(ColumnDef::new(Users::PasswordResetRequired).boolean().not_null().default(false))
+                    .to_owned(),
+            )
+            .await?;
+
+        // Logs stay after admin or target user is deleted.
+        manager
+            .create_table(
+                Table::create()
+                    .table(AdminAuditLogs::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(AdminAuditLogs::Id).integer().not_null().auto_increment().primary_key())
+                    // Null if admin is deleted, or action is done by server command.
+                    .col(ColumnDef::new(AdminAuditLogs::AdminId).integer())
+                    .col(ColumnDef::new(AdminAuditLogs::Action).string_len(50).not_null())
+                    .col(ColumnDef::new(AdminAuditLogs::TargetUserId).integer())
+                    .col(ColumnDef::new(AdminAuditLogs::Detail).text())
+                    .col(ColumnDef::new(AdminAuditLogs::Ip).string_len(45))
+                    .col(ColumnDef::new(AdminAuditLogs::CreatedAt).timestamp_with_time_zone().not_null().default(Expr::current_timestamp()))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(AdminAuditLogs::Table, AdminAuditLogs::AdminId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(AdminAuditLogs::Table, AdminAuditLogs::TargetUserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-admin_audit_logs-admin_id")
+                    .table(AdminAuditLogs::Table)
+                    .col(AdminAuditLogs::AdminId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-admin_audit_logs-target_user_id")
+                    .table(AdminAuditLogs::Table)
+                    .col(AdminAuditLogs::TargetUserId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AdminAuditLogs::Table).to_owned())
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Users::Table)
+                    .drop_column(Users::Role)
+                    .drop_column(Users::DisabledAt)
+                    .drop_column(Users::PasswordResetRequired)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum Users {
+    Table,
+    Id,
+    Role,
+    DisabledAt,
+    PasswordResetRequired,
+}
+
+#[derive(Iden)]
+enum AdminAuditLogs {
+    Table,
+    Id,
+    AdminId,
+    Action,
+    TargetUserId,
+    Detail,
+    Ip,
+    CreatedAt,
+}
@@ -0,0 +1,864 @@
+use std::{fs, time::Duration};
+use clap::{Args, ValueEnum};
+use derive_more::{Display, Error as Err};
+use serde::Deserialize;
+
+/// Minimum length of HS256 secret (256 bit).
+const MIN_JWT_SECRET_LEN: usize = 32;
+
+#[derive(Debug, Display, Err)]
+pub enum ConfigError {
+    #[display(fmt = "Can not read config file {path}: {detail}")]
+    ReadError { path: String, detail: String },
+
+    #[display(fmt = "Can not parse config file {path}: {detail}")]
+    ParseError { path: String, detail: String },
+
+    #[display(fmt = "Missing config value {field}")]
+    MissingError { field: &'static str },
+
+    #[display(fmt = "Invalid config value {field}: {detail}")]
+    InvalidError { field: &'static str, detail: String },
+}
+
+/// Values from command line flags and environment variables.
+/// Command line flag has priority over environment variable.
+#[derive(Args, Debug, Clone, Default)]
+pub struct ConfigArgs {
+    /// Path of TOML config file.
+    #[arg(long = "config", env = "TODO_CONFIG")]
+    pub config_path: Option<String>,
+
+    #[arg(long, env = "TODO_HOST")]
+    pub host: Option<String>,
+
+    #[arg(long, env = "TODO_PORT")]
+    pub port: Option<u16>,
+
+    /// Show detail of internal errors to clients, only for development. It is always logged.
+    #[arg(long, env = "TODO_EXPOSE_ERROR_DETAIL")]
+    pub expose_error_detail: Option<bool>,
+
+    #[arg(long, env = "TODO_DATABASE_URL")]
+    pub database_url: Option<String>,
+
+    #[arg(long, env = "TODO_DB_MAX_CONNECTIONS")]
+    pub db_max_connections: Option<u32>,
+
+    #[arg(long, env = "TODO_DB_MIN_CONNECTIONS")]
+    pub db_min_connections: Option<u32>,
+
+    /// Seconds.
+    #[arg(long, env = "TODO_DB_CONNECT_TIMEOUT")]
+    pub db_connect_timeout: Option<u64>,
+
+    /// Seconds.
+    #[arg(long, env = "TODO_DB_ACQUIRE_TIMEOUT")]
+    pub db_acquire_timeout: Option<u64>,
+
+    /// Seconds.
+    #[arg(long, env = "TODO_DB_IDLE_TIMEOUT")]
+    pub db_idle_timeout: Option<u64>,
+
+    /// Seconds.
+    #[arg(long, env = "TODO_DB_MAX_LIFETIME")]
+    pub db_max_lifetime: Option<u64>,
+
+    /// Apply pending migrations when server starts.
+    #[arg(long, env = "TODO_DB_AUTO_MIGRATE")]
+    pub db_auto_migrate: Option<bool>,
+
+    /// HS256 secret. Required if jwt algorithm is HS256, tokens without kid are verified with it.
+    #[arg(long, env = "TODO_JWT_SECRET", hide_env_values = true)]
+    pub jwt_secret: Option<String>,
+
+    /// Algorithm to sign access token.
+    #[arg(long, env = "TODO_JWT_ALGORITHM")]
+    pub jwt_algorithm: Option<JwtAlgorithm>,
+
+    /// kid of signing key. Required if jwt algorithm is asymmetric.
+    #[arg(long, env = "TODO_JWT_KEY_ID")]
+    pub jwt_key_id: Option<String>,
+
+    /// Path of PEM private key. Required if jwt algorithm is asymmetric.
+    #[arg(long, env = "TODO_JWT_PRIVATE_KEY")]
+    pub jwt_private_key: Option<String>,
+
+    #[arg(long, env = "TODO_ACCESS_TOKEN_EXP_MIN")]
+    pub access_token_exp_min: Option<u64>,
+
+    #[arg(long, env = "TODO_REFRESH_TOKEN_EXP_DAYS")]
+    pub refresh_token_exp_days: Option<i64>,
+
+    /// Seconds. Token revocations by other servers are loaded in this interval.
+    #[arg(long, env = "TODO_REVOCATION_SYNC_INTERVAL")]
+    pub revocation_sync_interval: Option<u64>,
+
+    /// Minutes. Password reset token expires after this.
+    #[arg(long, env = "TODO_PASSWORD_RESET_EXP_MIN")]
+    pub password_reset_exp_min: Option<i64>,
+
+    /// Link in password reset mail, "{token}" is replaced with reset token.
+    /// (e.g. "https://todo.example.com/reset-password?token={token}")
+    #[arg(long, env = "TODO_PASSWORD_RESET_URL")]
+    pub password_reset_url: Option<String>,
+
+    /// Hours. Email verification token expires after this.
+    #[arg(long, env = "TODO_EMAIL_VERIFICATION_EXP_HOURS")]
+    pub email_verification_exp_hours: Option<i64>,
+
+    /// Link in email verification mail, "{token}" is replaced with verification token.
+    /// (e.g. "https://todo.example.com/verify-email?token={token}")
+    #[arg(long, env = "TODO_EMAIL_VERIFICATION_URL")]
+    pub email_verification_url: Option<String>,
+
+    /// Creating personal access tokens and email reminders requires verified email.
+    #[arg(long, env = "TODO_REQUIRE_VERIFIED_EMAIL")]
+    pub require_verified_email: Option<bool>,
+
+    /// Minutes. Token of first login step expires after this, second step needs TOTP code.
+    #[arg(long, env = "TODO_MFA_TOKEN_EXP_MIN")]
+    pub mfa_token_exp_min: Option<u64>,
+
+    /// Issuer shown in authenticator apps.
+    #[arg(long, env = "TODO_TOTP_ISSUER")]
+    pub totp_issuer: Option<String>,
+
+    /// Algorithm of new password hashes. Hashes of other algorithm are still verified and upgraded on login.
+    #[arg(long, env = "TODO_PASSWORD_ALGORITHM")]
+    pub password_algorithm: Option<PasswordAlgorithm>,
+
+    /// KiB of memory for Argon2id.
+    #[arg(long, env = "TODO_PASSWORD_ARGON2_MEMORY_KIB")]
+    pub password_argon2_memory_kib: Option<u32>,
+
+    /// Number of passes of Argon2id.
+    #[arg(long, env = "TODO_PASSWORD_ARGON2_ITERATIONS")]
+    pub password_argon2_iterations: Option<u32>,
+
+    /// Number of lanes of Argon2id.
+    #[arg(long, env = "TODO_PASSWORD_ARGON2_PARALLELISM")]
+    pub password_argon2_parallelism: Option<u32>,
+
+    /// Cost of bcrypt, 4..=31.
+    #[arg(long, env = "TODO_PASSWORD_BCRYPT_COST")]
+    pub password_bcrypt_cost: Option<u32>,
+
+    /// Threads hashing passwords, off the async workers. Default is number of CPUs.
+    #[arg(long, env = "TODO_PASSWORD_HASH_WORKERS")]
+    pub password_hash_workers: Option<usize>,
+
+    /// Hashing jobs waiting for a thread, more requests get 503.
+    #[arg(long, env = "TODO_PASSWORD_HASH_QUEUE_SIZE")]
+    pub password_hash_queue_size: Option<usize>,
+
+    /// Failed logins of a username without delay.
+    #[arg(long, env = "TODO_LOGIN_FREE_ATTEMPTS")]
+    pub login_free_attempts: Option<u32>,
+
+    /// Seconds. Delay after free attempts, doubled on every failure.
+    #[arg(long, env = "TODO_LOGIN_BACKOFF_BASE")]
+    pub login_backoff_base: Option<u64>,
+
+    /// Failed logins of a username until lockout.
+    #[arg(long, env = "TODO_LOGIN_MAX_FAILURES")]
+    pub login_max_failures: Option<u32>,
+
+    /// Failed logins from a client IP until lockout.
+    #[arg(long, env = "TODO_LOGIN_IP_MAX_FAILURES")]
+    pub login_ip_max_failures: Option<u32>,
+
+    /// Seconds. Locked username or IP can not login for this long.
+    /// Failures are forgotten when this passes after last failure or end of lock.
+    #[arg(long, env = "TODO_LOGIN_LOCKOUT")]
+    pub login_lockout: Option<u64>,
+
+    /// Maximum number of levels in todo tree, top level todo is level 1.
+    #[arg(long, env = "TODO_MAX_TODO_DEPTH")]
+    pub max_todo_depth: Option<u32>,
+
+    /// Completing parent completes its subtasks, and parent follows completion of its subtasks.
+    #[arg(long, env = "TODO_COMPLETION_ROLL_UP")]
+    pub completion_roll_up: Option<bool>,
+
+    /// Run reminder scheduler in this server.
+    #[arg(long, env = "TODO_REMINDER_ENABLED")]
+    pub reminder_enabled: Option<bool>,
+
+    /// Seconds.
+    #[arg(long, env = "TODO_REMINDER_POLL_INTERVAL")]
+    pub reminder_poll_interval: Option<u64>,
+
+    /// Maximum number of reminders fired in one poll.
+    #[arg(long, env = "TODO_REMINDER_BATCH_SIZE")]
+    pub reminder_batch_size: Option<u64>,
+
+    /// Failed reminder is retried until this number of attempts.
+    #[arg(long, env = "TODO_REMINDER_MAX_ATTEMPTS")]
+    pub reminder_max_attempts: Option<i32>,
+
+    /// Seconds. Doubled on every failure.
+    #[arg(long, env = "TODO_REMINDER_RETRY_DELAY")]
+    pub reminder_retry_delay: Option<u64>,
+
+    /// URL which webhook reminders are posted to. Webhook channel is disabled if not set.
+    #[arg(long, env = "TODO_WEBHOOK_URL")]
+    pub webhook_url: Option<String>,
+
+    /// SMTP server host. Email channel is disabled if not set.
+    #[arg(long, env = "TODO_SMTP_HOST")]
+    pub smtp_host: Option<String>,
+
+    #[arg(long, env = "TODO_SMTP_PORT")]
+    pub smtp_port: Option<u16>,
+
+    #[arg(long, env = "TODO_SMTP_TLS")]
+    pub smtp_tls: Option<SmtpTls>,
+
+    #[arg(long, env = "TODO_SMTP_USERNAME")]
+    pub smtp_username: Option<String>,
+
+    #[arg(long, env = "TODO_SMTP_PASSWORD", hide_env_values = true)]
+    pub smtp_password: Option<String>,
+
+    /// Sender address. (e.g. "Todo <noreply@example.com>")
+    #[arg(long, env = "TODO_SMTP_FROM")]
+    pub smtp_from: Option<String>,
+
+    /// Mail backend, smtp if SMTP host is set or log.
+    #[arg(long, env = "TODO_MAIL_BACKEND")]
+    pub mail_backend: Option<MailBackend>,
+
+    /// Directory which log backend saves mails to as files.
+    #[arg(long, env = "TODO_MAIL_DIR")]
+    pub mail_dir: Option<String>,
+}
+
+/// Layout of TOML config file. Every value is optional.
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct FileConfig {
+    server: FileServerConfig,
+    database: FileDatabaseConfig,
+    auth: FileAuthConfig,
+    password: FilePasswordConfig,
+    login: FileLoginConfig,
+    todo: FileTodoConfig,
+    reminder: FileReminderConfig,
+    smtp: FileSmtpConfig,
+    mail: FileMailConfig,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct FileServerConfig {
+    host: Option<String>,
+    port: Option<u16>,
+    expose_error_detail: Option<bool>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct FileDatabaseConfig {
+    url: Option<String>,
+    max_connections: Option<u32>,
+    min_connections: Option<u32>,
+    connect_timeout: Option<u64>,
+    acquire_timeout: Option<u64>,
+    idle_timeout: Option<u64>,
+    max_lifetime: Option<u64>,
+    auto_migrate: Option<bool>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct FileAuthConfig {
+    jwt_secret: Option<String>,
+    jwt_algorithm: Option<JwtAlgorithm>,
+    jwt_key_id: Option<String>,
+    jwt_private_key: Option<String>,
+    jwt_public_keys: Vec<FileJwtPublicKey>,
+    access_token_exp_min: Option<u64>,
+    refresh_token_exp_days: Option<i64>,
+    revocation_sync_interval: Option<u64>,
+    password_reset_exp_min: Option<i64>,
+    password_reset_url: Option<String>,
+    email_verification_exp_hours: Option<i64>,
+    email_verification_url: Option<String>,
+    require_verified_email: Option<bool>,
+    mfa_token_exp_min: Option<u64>,
+    totp_issuer: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FileJwtPublicKey {
+    kid: String,
+    /// Same as jwt_algorithm if not set.
+    algorithm: Option<JwtAlgorithm>,
+    path: String,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct FileTodoConfig {
+    max_depth: Option<u32>,
+    completion_roll_up: Option<bool>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct FilePasswordConfig {
+    algorithm: Option<PasswordAlgorithm>,
+    argon2_memory_kib: Option<u32>,
+    argon2_iterations: Option<u32>,
+    argon2_parallelism: Option<u32>,
+    bcrypt_cost: Option<u32>,
+    hash_workers: Option<usize>,
+    hash_queue_size: Option<usize>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct FileLoginConfig {
+    free_attempts: Option<u32>,
+    backoff_base: Option<u64>,
+    max_failures: Option<u32>,
+    ip_max_failures: Option<u32>,
+    lockout: Option<u64>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct FileReminderConfig {
+    enabled: Option<bool>,
+    poll_interval: Option<u64>,
+    batch_size: Option<u64>,
+    max_attempts: Option<i32>,
+    retry_delay: Option<u64>,
+    webhook_url: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct FileSmtpConfig {
+    host: Option<String>,
+    port: Option<u16>,
+    tls: Option<SmtpTls>,
+    username: Option<String>,
+    password: Option<String>,
+    from: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct FileMailConfig {
+    backend: Option<MailBackend>,
+    dir: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    pub host: String,
+    pub port: u16,
+    pub expose_error_detail: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct DatabaseConfig {
+    pub url: String,
+    pub max_connections: u32,
+    pub min_connections: u32,
+    pub connect_timeout: Duration,
+    pub acquire_timeout: Duration,
+    pub idle_timeout: Duration,
+    pub max_lifetime: Duration,
+    pub auto_migrate: bool,
+}
+
+/// Algorithm of access token signature.
+#[derive(Deserialize, ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum JwtAlgorithm {
+    /// HMAC with shared secret.
+    #[value(name = "HS256")]
+    HS256,
+    /// RSA PKCS#1 v1.5 with SHA-256.
+    #[value(name = "RS256")]
+    RS256,
+    /// Ed25519.
+    #[value(name = "EdDSA")]
+    EdDSA,
+}
+
+/// Public key to verify access tokens with the kid.
+#[derive(Debug, Clone)]
+pub struct JwtPublicKey {
+    pub kid: String,
+    pub algorithm: JwtAlgorithm,
+    /// Path of PEM public key.
+    pub path: String,
+}
+
+#[derive(Clone)]
+pub struct AuthConfig {
+    pub jwt_secret: Option<String>,
+    pub jwt_algorithm: JwtAlgorithm,
+    /// Set if jwt algorithm is asymmetric.
+    pub jwt_key_id: Option<String>,
+    pub jwt_private_key: Option<String>,
+    /// Every active verification key, including one of signing key.
+    pub jwt_public_keys: Vec<JwtPublicKey>,
+    pub access_token_exp_min: u64,
+    pub refresh_token_exp_days: i64,
+    pub revocation_sync_interval: Duration,
+    pub password_reset_exp_min: i64,
+    pub password_reset_url: Option<String>,
+    pub email_verification_exp_hours: i64,
+    pub email_verification_url: Option<String>,
+    pub require_verified_email: bool,
+    pub mfa_token_exp_min: u64,
+    pub totp_issuer: String,
+}
+
+// Never print secret.
+impl std::fmt::Debug for AuthConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AuthConfig")
+            .field("jwt_secret", &self.jwt_secret.as_ref().map(|_| "***"))
+            .field("jwt_algorithm", &self.jwt_algorithm)
+            .field("jwt_key_id", &self.jwt_key_id)
+            .field("jwt_private_key", &self.jwt_private_key)
+            .field("jwt_public_keys", &self.jwt_public_keys)
+            .field("access_token_exp_min", &self.access_token_exp_min)
+            .field("refresh_token_exp_days", &self.refresh_token_exp_days)
+            .field("revocation_sync_interval", &self.revocation_sync_interval)
+            .field("password_reset_exp_min", &self.password_reset_exp_min)
+            .field("password_reset_url", &self.password_reset_url)
+            .field("email_verification_exp_hours", &self.email_verification_exp_hours)
+            .field("email_verification_url", &self.email_verification_url)
+            .field("require_verified_email", &self.require_verified_email)
+            .field("mfa_token_exp_min", &self.mfa_token_exp_min)
+            .field("totp_issuer", &self.totp_issuer)
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TodoConfig {
+    pub max_depth: u32,
+    pub completion_roll_up: bool,
+}
+
+/// Algorithm of password hash.
+#[derive(Deserialize, ValueEnum, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum PasswordAlgorithm {
+    Argon2id,
+    Bcrypt,
+}
+
+/// Hashing of passwords. Parameters of both algorithms are kept to find outdated hashes.
+#[derive(Debug, Clone)]
+pub struct PasswordConfig {
+    pub algorithm: PasswordAlgorithm,
+    pub argon2_memory_kib: u32,
+    pub argon2_iterations: u32,
+    pub argon2_parallelism: u32,
+    pub bcrypt_cost: u32,
+    pub hash_workers: usize,
+    pub hash_queue_size: usize,
+}
+
+/// Throttling of failed logins.
+#[derive(Debug, Clone)]
+pub struct LoginConfig {
+    pub free_attempts: u32,
+    pub backoff_base: Duration,
+    pub max_failures: u32,
+    pub ip_max_failures: u32,
+    pub lockout: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReminderConfig {
+    pub enabled: bool,
+    pub poll_interval: Duration,
+    pub batch_size: u64,
+    pub max_attempts: i32,
+    pub retry_delay: Duration,
+    pub webhook_url: Option<String>,
+}
+
+/// Connection security of SMTP.
+#[derive(Deserialize, ValueEnum, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpTls {
+    /// Plain text, for local relay only.
+    None,
+    /// Upgrade plain connection by STARTTLS. (port 587)
+    Starttls,
+    /// TLS from the start. (port 465)
+    Tls,
+}
+
+#[derive(Clone)]
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: u16,
+    pub tls: SmtpTls,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub from: String,
+}
+
+// Never print secret.
+impl std::fmt::Debug for SmtpConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SmtpConfig")
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("tls", &self.tls)
+            .field("username", &self.username)
+            .field("password", &self.password.as_ref().map(|_| "***"))
+            .field("from", &self.from)
+            .finish()
+    }
+}
+
+/// Where mails are sent.
+#[derive(Deserialize, ValueEnum, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum MailBackend {
+    /// Send by SMTP server of smtp config.
+    Smtp,
+    /// Write to log and mail directory, for local development.
+    Log,
+}
+
+#[derive(Debug, Clone)]
+pub struct MailConfig {
+    pub backend: MailBackend,
+    /// Log backend saves mails in this directory if set.
+    pub dir: Option<String>,
+}
+
+/// Validated runtime configuration.
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub server: ServerConfig,
+    pub database: DatabaseConfig,
+    pub auth: AuthConfig,
+    pub password: PasswordConfig,
+    pub login: LoginConfig,
+    pub todo: TodoConfig,
+    pub reminder: ReminderConfig,
+    /// None if SMTP host is not set.
+    pub smtp: Option<SmtpConfig>,
+    pub mail: MailConfig,
+}
+
+impl Config {
+    /// Load config to run server. Priority is command line flag > environment variable > TOML file > default.
+    /// Database url and jwt secret have no default.
+    pub fn load(args: &ConfigArgs) -> Result<Config, ConfigError> {
+        let file = Self::read_args_file(args)?;
+        let database = Self::database_config(args, &file.database)?;
+        let jwt_algorithm = args.jwt_algorithm.or(file.auth.jwt_algorithm).unwrap_or(JwtAlgorithm::HS256);
+        let smtp_host = args.smtp_host.clone().or(file.smtp.host);
+        let mail_backend = args.mail_backend.or(file.mail.backend)
+            .unwrap_or(if smtp_host.is_some() { MailBackend::Smtp } else { MailBackend::Log });
+
+        let config = Config {
+            server: ServerConfig {
+                host: args.host.clone().or(file.server.host).unwrap_or_else(|| "0.0.0.0".to_owned()),
+                port: args.port.or(file.server.port).unwrap_or(8080),
+                expose_error_detail: args.expose_error_detail.or(file.server.expose_error_detail).unwrap_or(false),
+            },
+            database,
+            auth: AuthConfig {
+                jwt_secret: args.jwt_secret.clone().or(file.auth.jwt_secret),
+                jwt_algorithm,
+                jwt_key_id: args.jwt_key_id.clone().or(file.auth.jwt_key_id),
+                jwt_private_key: args.jwt_private_key.clone().or(file.auth.jwt_private_key),
+                jwt_public_keys: file.auth.jwt_public_keys.into_iter()
+                    .map(|key| JwtPublicKey {
+                        kid: key.kid,
+                        algorithm: key.algorithm.unwrap_or(jwt_algorithm),
+                        path: key.path,
+                    })
+                    .collect(),
+                access_token_exp_min: args.access_token_exp_min.or(file.auth.access_token_exp_min).unwrap_or(15),
+                refresh_token_exp_days: args.refresh_token_exp_days.or(file.auth.refresh_token_exp_days).unwrap_or(14),
+                revocation_sync_interval: Duration::from_secs(args.revocation_sync_interval.or(file.auth.revocation_sync_interval).unwrap_or(10)),
+                password_reset_exp_min: args.password_reset_exp_min.or(file.auth.password_reset_exp_min).unwrap_or(30),
+                password_reset_url: args.password_reset_url.clone().or(file.auth.password_reset_url),
+                email_verification_exp_hours: args.email_verification_exp_hours.or(file.auth.email_verification_exp_hours).unwrap_or(24),
+                email_verification_url: args.email_verification_url.clone().or(file.auth.email_verification_url),
+                require_verified_email: args.require_verified_email.or(file.auth.require_verified_email).unwrap_or(false),
+                mfa_token_exp_min: args.mfa_token_exp_min.or(file.auth.mfa_token_exp_min).unwrap_or(5),
+                totp_issuer: args.totp_issuer.clone().or(file.auth.totp_issuer).unwrap_or_else(|| "Todo".to_owned()),
+            },
+            // Argon2id defaults are minimum of OWASP recommendation.
+            password: PasswordConfig {
+                algorithm: args.password_algorithm.or(file.password.algorithm).unwrap_or(PasswordAlgorithm::Argon2id),
+                argon2_memory_kib: args.password_argon2_memory_kib.or(file.password.argon2_memory_kib).unwrap_or(19456),
+                argon2_iterations: args.password_argon2_iterations.or(file.password.argon2_iterations).unwrap_or(2),
+                argon2_parallelism: args.password_argon2_parallelism.or(file.password.argon2_parallelism).unwrap_or(1),
+                bcrypt_cost: args.password_bcrypt_cost.or(file.password.bcrypt_cost).unwrap_or(12),
+                hash_workers: args.password_hash_workers.or(file.password.hash_workers)
+                    .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get())),
+                hash_queue_size: args.password_hash_queue_size.or(file.password.hash_queue_size).unwrap_or(64),
+            },
+            login: LoginConfig {
+                free_attempts: args.login_free_attempts.or(file.login.free_attempts).unwrap_or(3),
+                backoff_base: Duration::from_secs(args.login_backoff_base.or(file.login.backoff_base).unwrap_or(1)),
+                max_failures: args.login_max_failures.or(file.login.max_failures).unwrap_or(10),
+                ip_max_failures: args.login_ip_max_failures.or(file.login.ip_max_failures).unwrap_or(50),
+                lockout: Duration::from_secs(args.login_lockout.or(file.login.lockout).unwrap_or(900)),
+            },
+            todo: TodoConfig {
+                max_depth: args.max_todo_depth.or(file.todo.max_depth).unwrap_or(5),
+                completion_roll_up: args.completion_roll_up.or(file.todo.completion_roll_up).unwrap_or(false),
+            },
+            reminder: ReminderConfig {
+                enabled: args.reminder_enabled.or(file.reminder.enabled).unwrap_or(true),
+                poll_interval: Duration::from_secs(args.reminder_poll_interval.or(file.reminder.poll_interval).unwrap_or(30)),
+                batch_size: args.reminder_batch_size.or(file.reminder.batch_size).unwrap_or(100),
+                max_attempts: args.reminder_max_attempts.or(file.reminder.max_attempts).unwrap_or(5),
+                retry_delay: Duration::from_secs(args.reminder_retry_delay.or(file.reminder.retry_delay).unwrap_or(60)),
+                webhook_url: args.webhook_url.clone().or(file.reminder.webhook_url),
+            },
+            mail: MailConfig {
+                backend: mail_backend,
+                dir: args.mail_dir.clone().or(file.mail.dir),
+            },
+            smtp: match smtp_host {
+                Some(host) => {
+                    let tls = args.smtp_tls.or(file.smtp.tls).unwrap_or(SmtpTls::Starttls);
+                    let default_port = match tls {
+                        SmtpTls::None => 25,
+                        SmtpTls::Starttls => 587,
+                        SmtpTls::Tls => 465,
+                    };
+                    Some(SmtpConfig {
+                        host,
+                        port: args.smtp_port.or(file.smtp.port).unwrap_or(default_port),
+                        tls,
+                        username: args.smtp_username.clone().or(file.smtp.username),
+                        password: args.smtp_password.clone().or(file.smtp.password),
+                        from: args.smtp_from.clone().or(file.smtp.from)
+                            .ok_or(ConfigError::MissingError { field: "smtp.from" })?,
+                    })
+                },
+                None => None,
+            },
+        };
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Load only database config, for commands which do not run server. (e.g. migrate)
+    /// Other values are not required nor validated.
+    pub fn load_database(args: &ConfigArgs) -> Result<DatabaseConfig, ConfigError> {
+        let file = Self::read_args_file(args)?;
+        Self::database_config(args, &file.database)
+    }
+
+    fn read_args_file(args: &ConfigArgs) -> Result<FileConfig, ConfigError> {
+        match &args.config_path {
+            Some(path) => Self::read_file(path),
+            None => Ok(FileConfig::default()),
+        }
+    }
+
+    fn database_config(args: &ConfigArgs, file: &FileDatabaseConfig) -> Result<DatabaseConfig, ConfigError> {
+        let db = DatabaseConfig {
+            url: args.database_url.clone().or(file.url.clone())
+                .ok_or(ConfigError::MissingError { field: "database.url" })?,
+            max_connections: args.db_max_connections.or(file.max_connections).unwrap_or(100),
+            min_connections: args.db_min_connections.or(file.min_connections).unwrap_or(5),
+            connect_timeout: Duration::from_secs(args.db_connect_timeout.or(file.connect_timeout).unwrap_or(8)),
+            acquire_timeout: Duration::from_secs(args.db_acquire_timeout.or(file.acquire_timeout).unwrap_or(8)),
+            idle_timeout: Duration::from_secs(args.db_idle_timeout.or(file.idle_timeout).unwrap_or(8)),
+            max_lifetime: Duration::from_secs(args.db_max_lifetime.or(file.max_lifetime).unwrap_or(8)),
+            auto_migrate: args.db_auto_migrate.or(file.auto_migrate).unwrap_or(true),
+        };
+
+        let invalid = |field, detail: &str| Err(ConfigError::InvalidError { field, detail: detail.to_owned() });
+        if !(db.url.starts_with("postgres://") || db.url.starts_with("postgresql://")) {
+            return invalid("database.url", "must start with postgres:// or postgresql://")
+        }
+        if db.max_connections == 0 {
+            return invalid("database.max_connections", "must be greater than 0")
+        }
+        if db.min_connections > db.max_connections {
+            return invalid("database.min_connections", "must not be greater than max_connections")
+        }
+        for (field, timeout) in [
+            ("database.connect_timeout", db.connect_timeout),
+            ("database.acquire_timeout", db.acquire_timeout),
+            ("database.idle_timeout", db.idle_timeout),
+            ("database.max_lifetime", db.max_lifetime),
+        ] {
+            if timeout.is_zero() {
+                return invalid(field, "must be greater than 0")
+            }
+        }
+        Ok(db)
+    }
+
+    fn read_file(path: &str) -> Result<FileConfig, ConfigError> {
+        let raw = fs::read_to_string(path)
+            .map_err(|e|
+                ConfigError::ReadError { path: path.to_owned(), detail: e.to_string() }
+            )?;
+        toml::from_str(&raw)
+            .map_err(|e|
+                ConfigError::ParseError { path: path.to_owned(), detail: e.to_string() }
+            )
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        let invalid = |field, detail: &str| Err(ConfigError::InvalidError { field, detail: detail.to_owned() });
+
+        if self.server.host.is_empty() {
+            return invalid("server.host", "must not be empty")
+        }
+        if self.server.port == 0 {
+            return invalid("server.port", "must be 1..=65535")
+        }
+
+        // Database config is validated when loaded.
+        let auth = &self.auth;
+        match &auth.jwt_secret {
+            None if auth.jwt_algorithm == JwtAlgorithm::HS256 => {
+                return Err(ConfigError::MissingError { field: "auth.jwt_secret" })
+            },
+            Some(secret) if secret.len() < MIN_JWT_SECRET_LEN => {
+                return invalid("auth.jwt_secret", &format!("must be at least {} bytes", MIN_JWT_SECRET_LEN))
+            },
+            _ => (),
+        }
+        for (i, key) in auth.jwt_public_keys.iter().enumerate() {
+            if key.kid.is_empty() {
+                return invalid("auth.jwt_public_keys", "kid must not be empty")
+            }
+            if auth.jwt_public_keys[..i].iter().any(|k| k.kid == key.kid) {
+                return invalid("auth.jwt_public_keys", &format!("kid {} is duplicated", key.kid))
+            }
+            if key.algorithm == JwtAlgorithm::HS256 {
+                return invalid("auth.jwt_public_keys", "algorithm must be RS256 or EdDSA")
+            }
+        }
+        if auth.jwt_algorithm != JwtAlgorithm::HS256 {
+            let key_id = auth.jwt_key_id.as_ref()
+                .ok_or(ConfigError::MissingError { field: "auth.jwt_key_id" })?;
+            if auth.jwt_private_key.is_none() {
+                return Err(ConfigError::MissingError { field: "auth.jwt_private_key" })
+            }
+            // Signed tokens must be verifiable by this server and others.
+            if !auth.jwt_public_keys.iter().any(|k| &k.kid == key_id && k.algorithm == auth.jwt_algorithm) {
+                return invalid("auth.jwt_public_keys", "must include public key of jwt_key_id with jwt_algorithm")
+            }
+        }
+        if auth.access_token_exp_min == 0 {
+            return invalid("auth.access_token_exp_min", "must be greater than 0")
+        }
+        if auth.refresh_token_exp_days <= 0 {
+            return invalid("auth.refresh_token_exp_days", "must be greater than 0")
+        }
+        if auth.revocation_sync_interval.is_zero() {
+            return invalid("auth.revocation_sync_interval", "must be greater than 0")
+        }
+        if auth.password_reset_exp_min <= 0 {
+            return invalid("auth.password_reset_exp_min", "must be greater than 0")
+        }
+        if auth.password_reset_url.as_ref().is_some_and(|url| !url.contains("{token}")) {
+            return invalid("auth.password_reset_url", "must contain {token}")
+        }
+        if auth.email_verification_exp_hours <= 0 {
+            return invalid("auth.email_verification_exp_hours", "must be greater than 0")
+        }
+        if auth.email_verification_url.as_ref().is_some_and(|url| !url.contains("{token}")) {
+            return invalid("auth.email_verification_url", "must contain {token}")
+        }
+        if auth.mfa_token_exp_min == 0 {
+            return invalid("auth.mfa_token_exp_min", "must be greater than 0")
+        }
+        if auth.totp_issuer.is_empty() || auth.totp_issuer.contains(':') {
+            return invalid("auth.totp_issuer", "must not be empty or contain ':'")
+        }
+
+        let password = &self.password;
+        // Minimums of argon2 crate.
+        if password.argon2_parallelism == 0 || password.argon2_parallelism > 0xff_ffff {
+            return invalid("password.argon2_parallelism", "must be 1..=16777215")
+        }
+        if password.argon2_memory_kib < 8 * password.argon2_parallelism {
+            return invalid("password.argon2_memory_kib", "must be at least 8 times password.argon2_parallelism")
+        }
+        if password.argon2_iterations == 0 {
+            return invalid("password.argon2_iterations", "must be greater than 0")
+        }
+        if !(4..=31).contains(&password.bcrypt_cost) {
+            return invalid("password.bcrypt_cost", "must be 4..=31")
+        }
+        if password.hash_workers == 0 {
+            return invalid("password.hash_workers", "must be greater than 0")
+        }
+
+        let login = &self.login;
+        if login.backoff_base.is_zero() {
+            return invalid("login.backoff_base", "must be greater than 0")
+        }
+        if login.max_failures <= login.free_attempts {
+            return invalid("login.max_failures", "must be greater than login.free_attempts")
+        }
+        if login.ip_max_failures == 0 {
+            return invalid("login.ip_max_failures", "must be greater than 0")
+        }
+        if login.lockout.is_zero() {
+            return invalid("login.lockout", "must be greater than 0")
+        }
+
+        if self.todo.max_depth == 0 {
+            return invalid("todo.max_depth", "must be greater than 0")
+        }
+
+        let reminder = &self.reminder;
+        if reminder.poll_interval.is_zero() {
+            return invalid("reminder.poll_interval", "must be greater than 0")
+        }
+        if reminder.batch_size == 0 {
+            return invalid("reminder.batch_size", "must be greater than 0")
+        }
+        if reminder.max_attempts <= 0 {
+            return invalid("reminder.max_attempts", "must be greater than 0")
+        }
+        if reminder.webhook_url.as_ref().is_some_and(|url| !(url.starts_with("http://") || url.starts_with("https://"))) {
+            return invalid("reminder.webhook_url", "must start with http:// or https://")
+        }
+
+        if let Some(smtp) = &self.smtp {
+            if smtp.host.is_empty() {
+                return invalid("smtp.host", "must not be empty")
+            }
+            if smtp.port == 0 {
+                return invalid("smtp.port", "must be 1..=65535")
+            }
+            if smtp.username.is_some() != smtp.password.is_some() {
+                return invalid("smtp.username", "must be set with smtp.password")
+            }
+            if !smtp.from.contains('@') {
+                return invalid("smtp.from", "must be email address")
+            }
+        }
+
+        if self.mail.backend == MailBackend::Smtp && self.smtp.is_none() {
+            return Err(ConfigError::MissingError { field: "smtp.host" })
+        }
+
+        Ok(())
+    }
+}
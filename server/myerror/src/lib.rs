@@ -1,45 +1,162 @@
-use actix_web::{error, HttpResponse, http::{header::ContentType, StatusCode}};
+use std::{collections::BTreeMap, sync::atomic::{AtomicBool, Ordering}};
+use actix_web::{error::ResponseError, HttpResponse, http::{header, StatusCode}};
 use derive_more::{Display, Error as Err};
 use serde::Serialize;
-use serde_json::json;
+use serde_json::{json, Map, Value};
 
-#[derive(Serialize)]
-struct ErrorJson {
-    msg: &'static str,
+/// Media type of RFC 7807 problem detail.
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
+/// Code of every internal error, its cause is only in server log.
+pub const INTERNAL_ERROR: &str = "INTERNAL_ERROR";
+
+/// Code of invalid request fields, which fields are wrong is in errors.
+pub const VALIDATION_FAILED: &str = "VALIDATION_FAILED";
+
+/// Broken rule of a request field.
+/// code is stable (e.g. "too_short"), params are values of the rule (e.g. min), message is for human.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldError {
+    pub code: String,
+    pub message: String,
+    pub params: Map<String, Value>,
+}
+
+/// Broken rules by field name.
+pub type FieldErrors = BTreeMap<String, Vec<FieldError>>;
+
+static EXPOSE_INTERNAL_DETAIL: AtomicBool = AtomicBool::new(false);
+
+/// Show detail of internal errors, such as database error, to clients.
+/// It is hidden by default for production, and always logged.
+pub fn expose_internal_detail(expose: bool) {
+    EXPOSE_INTERNAL_DETAIL.store(expose, Ordering::Relaxed);
 }
 
-#[derive(Debug, Display, Err, Serialize)]
+/// Error response in RFC 7807 problem detail.
+/// code is stable for clients to handle failure, msg and detail are for human and can be changed.
+#[derive(Debug, Display, Err)]
 pub enum ServerError {
-    #[display(fmt = "{msg}")]
+    /// Detail is logged, and hidden from client unless internal detail is exposed.
+    #[display(fmt = "{msg}: {detail}")]
     InternalServerError { msg: &'static str , detail: String},
 
     #[display(fmt = "{msg}")]
-    BadRequestError { msg: &'static str , detail: String},
+    BadRequestError { code: &'static str, msg: &'static str , detail: String},
+
+    /// Request fields are invalid, 400 with code VALIDATION_FAILED.
+    #[display(fmt = "{msg}")]
+    ValidationError { msg: &'static str, errors: FieldErrors },
+
+    #[display(fmt = "{msg}")]
+    UnauthorizedError { code: &'static str, msg: &'static str , detail: String},
+
+    #[display(fmt = "{msg}")]
+    ForbiddenError { code: &'static str, msg: &'static str , detail: String},
+
+    #[display(fmt = "{msg}")]
+    NotFound { code: &'static str, msg: &'static str },
 
+    /// Request conflicts with current state of resource. (e.g. already taken username)
+    /// field is request field causing conflict, if any.
     #[display(fmt = "{msg}")]
-    UnauthorizedError { msg: &'static str , detail: String},
+    ConflictError { code: &'static str, msg: &'static str , detail: String, field: Option<String>},
 
-    NotFound
+    /// Request is well-formed but can not be processed. (e.g. it refers to missing resource)
+    #[display(fmt = "{msg}")]
+    UnprocessableEntityError { code: &'static str, msg: &'static str , detail: String, field: Option<String>},
+
+    /// Client can try again after retry_after seconds.
+    #[display(fmt = "{msg}")]
+    TooManyRequestsError { code: &'static str, msg: &'static str , detail: String, retry_after: u64},
+
+    /// Server is too busy, client can try again later.
+    #[display(fmt = "{msg}")]
+    ServiceUnavailableError { code: &'static str, msg: &'static str , detail: String},
 }
 
 
 
-impl error::ResponseError for ServerError {
+impl ResponseError for ServerError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Self::InternalServerError{..} => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::BadRequestError{..} | Self::ValidationError{..} => StatusCode::BAD_REQUEST,
+            Self::UnauthorizedError{..} => StatusCode::UNAUTHORIZED,
+            Self::ForbiddenError{..} => StatusCode::FORBIDDEN,
+            Self::NotFound{..} => StatusCode::NOT_FOUND,
+            Self::ConflictError{..} => StatusCode::CONFLICT,
+            Self::UnprocessableEntityError{..} => StatusCode::UNPROCESSABLE_ENTITY,
+            Self::TooManyRequestsError{..} => StatusCode::TOO_MANY_REQUESTS,
+            Self::ServiceUnavailableError{..} => StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+
     fn error_response(&self) -> actix_web::HttpResponse<actix_web::body::BoxBody> {
-        let (status_code, error_json) = self.return_body();
-        HttpResponse::build(status_code)
-            .insert_header(ContentType::json())
-            .json(error_json)
+        if let Self::InternalServerError { msg, detail } = self {
+            log::error!("{}: {}", msg, detail);
+        }
+        let mut resp = HttpResponse::build(self.status_code());
+        resp.insert_header((header::CONTENT_TYPE, PROBLEM_JSON));
+        if let Self::TooManyRequestsError { retry_after, .. } = self {
+            resp.insert_header((header::RETRY_AFTER, retry_after.to_string()));
+        }
+        resp.body(self.return_body().to_string())
     }
 }
 
 impl ServerError {
-    fn return_body(&self) -> (actix_web::http::StatusCode, serde_json::Value) {
+    /// Stable code of failure.
+    pub fn code(&self) -> &'static str {
         match self {
-            Self::InternalServerError{msg: m, detail: d} => (StatusCode::INTERNAL_SERVER_ERROR, json!({"msg":m, "detail":d})),
-            Self::BadRequestError{msg: m, detail: d} => (StatusCode::BAD_REQUEST, json!({"msg":m, "detail":d})),
-            Self::UnauthorizedError{msg: m, detail: d} => (StatusCode::UNAUTHORIZED, json!({"msg":m, "detail":d})),
-            Self::NotFound => (StatusCode::NOT_FOUND, json!({"msg":"Not Found"}))
+            Self::InternalServerError{..} => INTERNAL_ERROR,
+            Self::ValidationError{..} => VALIDATION_FAILED,
+            Self::BadRequestError{code, ..}
+            | Self::UnauthorizedError{code, ..}
+            | Self::ForbiddenError{code, ..}
+            | Self::NotFound{code, ..}
+            | Self::ConflictError{code, ..}
+            | Self::UnprocessableEntityError{code, ..}
+            | Self::TooManyRequestsError{code, ..}
+            | Self::ServiceUnavailableError{code, ..} => code,
+        }
+    }
+
+    /// Problem detail. type is about:blank, failure is identified by code.
+    fn return_body(&self) -> Value {
+        let (title, detail) = match self {
+            Self::InternalServerError{msg: m, detail: d} => {
+                (*m, EXPOSE_INTERNAL_DETAIL.load(Ordering::Relaxed).then_some(d.as_str()))
+            },
+            Self::ValidationError{msg: m, ..} => (*m, Some("Some fields are invalid, refer to errors")),
+            Self::NotFound{msg: m, ..} => (*m, None),
+            Self::BadRequestError{msg: m, detail: d, ..}
+            | Self::UnauthorizedError{msg: m, detail: d, ..}
+            | Self::ForbiddenError{msg: m, detail: d, ..}
+            | Self::ConflictError{msg: m, detail: d, ..}
+            | Self::UnprocessableEntityError{msg: m, detail: d, ..}
+            | Self::TooManyRequestsError{msg: m, detail: d, ..}
+            | Self::ServiceUnavailableError{msg: m, detail: d, ..} => (*m, Some(d.as_str())),
+        };
+        let field = match self {
+            Self::ConflictError{field, ..} | Self::UnprocessableEntityError{field, ..} => field.as_deref(),
+            _ => None,
+        };
+        let mut body = json!({
+            "type": "about:blank",
+            "title": title,
+            "status": self.status_code().as_u16(),
+            "code": self.code(),
+        });
+        if let Some(detail) = detail {
+            body["detail"] = json!(detail);
+        }
+        if let Some(field) = field {
+            body["field"] = json!(field);
+        }
+        if let Self::ValidationError { errors, .. } = self {
+            body["errors"] = json!(errors);
         }
+        body
     }
 }
@@ -0,0 +1,26 @@
+use async_trait::async_trait;
+use sea_orm::DatabaseConnection;
+use entity::mutation::Mutation;
+use super::{Notifier, Notification, NotifyError};
+
+/// Save notification to user's in-app inbox.
+pub struct InboxNotifier {
+    conn: DatabaseConnection,
+}
+
+impl InboxNotifier {
+    pub fn new(conn: DatabaseConnection) -> InboxNotifier {
+        InboxNotifier { conn }
+    }
+}
+
+#[async_trait]
+impl Notifier for InboxNotifier {
+    async fn notify(&self, notification: &Notification) -> Result<(), NotifyError> {
+        Mutation::create_notification(&self.conn, notification.user_id, Some(notification.todo_id), &notification.title, &notification.body).await
+            .map_err(|e|
+                NotifyError::DeliveryError { channel: "inbox", detail: format!("{:?}", e) }
+            )?;
+        Ok(())
+    }
+}
@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+use async_trait::async_trait;
+use chrono_tz::Tz;
+use derive_more::{Display, Error as Err};
+use sea_orm::{DatabaseConnection, prelude::{Date, DateTimeWithTimeZone}};
+use serde::Serialize;
+use entity::mutation::DueReminder;
+use myconfig::Config;
+
+mod inbox;
+mod mailer;
+mod smtp;
+mod webhook;
+
+pub use inbox::InboxNotifier;
+pub use mailer::{Mail, Mailer, LogMailer, new_mailer};
+pub use smtp::{SmtpMailer, SmtpNotifier};
+pub use webhook::WebhookNotifier;
+
+#[derive(Debug, Display, Err)]
+pub enum NotifyError {
+    #[display(fmt = "Invalid {channel} notifier config: {detail}")]
+    ConfigError { channel: &'static str, detail: String },
+
+    #[display(fmt = "{channel} delivery failed: {detail}")]
+    DeliveryError { channel: &'static str, detail: String },
+}
+
+/// Message of fired reminder. Webhook posts it as JSON.
+#[derive(Serialize, Clone, Debug)]
+pub struct Notification {
+    pub reminder_id: i32,
+    pub user_id: i32,
+    pub todo_id: i32,
+
+    /// Recipient of email channel.
+    #[serde(skip_serializing)]
+    pub email: String,
+
+    pub title: String,
+    pub body: String,
+    pub due_date: Option<Date>,
+    pub due_at: Option<DateTimeWithTimeZone>,
+}
+
+impl From<&DueReminder> for Notification {
+    fn from(due: &DueReminder) -> Self {
+        let DueReminder { reminder, todo, user } = due;
+        let time_zone: Tz = user.time_zone.parse().unwrap_or(Tz::UTC);
+        let body = match (todo.due_at, todo.due_date) {
+            (Some(due_at), _) => format!("Due at {}", due_at.with_timezone(&time_zone).format("%Y-%m-%d %H:%M %Z")),
+            (None, Some(due_date)) => format!("Due on {}", due_date),
+            (None, None) => "Reminder".to_owned(),
+        };
+        Notification {
+            reminder_id: reminder.id,
+            user_id: user.id,
+            todo_id: todo.id,
+            email: user.email.clone(),
+            title: todo.name.clone(),
+            body,
+            due_date: todo.due_date,
+            due_at: todo.due_at,
+        }
+    }
+}
+
+/// Delivery channel of reminder.
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    async fn notify(&self, notification: &Notification) -> Result<(), NotifyError>;
+}
+
+/// Notifiers by channel name of reminder. (inbox, webhook, email)
+#[derive(Default)]
+pub struct Notifiers {
+    channels: HashMap<String, Box<dyn Notifier>>,
+}
+
+impl Notifiers {
+    /// Inbox is always available, webhook and email only when configured.
+    pub fn new(config: &Config, conn: DatabaseConnection) -> Result<Notifiers, NotifyError> {
+        let mut notifiers = Notifiers::default();
+        notifiers.insert("inbox", InboxNotifier::new(conn));
+        if let Some(url) = &config.reminder.webhook_url {
+            notifiers.insert("webhook", WebhookNotifier::new(url)?);
+        }
+        if let Some(smtp) = &config.smtp {
+            notifiers.insert("email", SmtpNotifier::new(smtp)?);
+        }
+        Ok(notifiers)
+    }
+
+    /// Add or replace notifier of the channel.
+    pub fn insert(&mut self, channel: &str, notifier: impl Notifier + 'static) {
+        self.channels.insert(channel.to_owned(), Box::new(notifier));
+    }
+
+    pub fn get(&self, channel: &str) -> Option<&dyn Notifier> {
+        self.channels.get(channel).map(|n| n.as_ref())
+    }
+}
@@ -0,0 +1,57 @@
+use std::{fs, path::PathBuf};
+use async_trait::async_trait;
+use chrono::Utc;
+use myconfig::{Config, MailBackend};
+use super::{NotifyError, SmtpMailer};
+
+/// Plain text mail to a user.
+#[derive(Clone, Debug)]
+pub struct Mail {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Sender of account mails. (e.g. password reset)
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, mail: &Mail) -> Result<(), NotifyError>;
+}
+
+/// Mailer of config backend.
+pub fn new_mailer(config: &Config) -> Result<Box<dyn Mailer>, NotifyError> {
+    match (config.mail.backend, &config.smtp) {
+        (MailBackend::Smtp, Some(smtp)) => Ok(Box::new(SmtpMailer::new(smtp)?)),
+        (MailBackend::Smtp, None) => Err(NotifyError::ConfigError { channel: "mail", detail: "SMTP is not configured".to_owned() }),
+        (MailBackend::Log, _) => Ok(Box::new(LogMailer::new(config.mail.dir.as_ref().map(PathBuf::from)))),
+    }
+}
+
+/// Write mail to log instead of sending, for local development.
+/// Mail is also saved as a file if directory is set.
+pub struct LogMailer {
+    dir: Option<PathBuf>,
+}
+
+impl LogMailer {
+    pub fn new(dir: Option<PathBuf>) -> LogMailer {
+        LogMailer { dir }
+    }
+}
+
+#[async_trait]
+impl Mailer for LogMailer {
+    async fn send(&self, mail: &Mail) -> Result<(), NotifyError> {
+        let raw = format!("To: {}\nSubject: {}\n\n{}\n", mail.to, mail.subject, mail.body);
+        log::info!("Mail is not sent by log backend\n{}", raw);
+
+        if let Some(dir) = &self.dir {
+            let error = |detail: String| NotifyError::DeliveryError { channel: "mail", detail };
+            fs::create_dir_all(dir).map_err(|e| error(e.to_string()))?;
+            // Sortable by time, unique for recipient.
+            let path = dir.join(format!("{}-{}.eml", Utc::now().format("%Y%m%dT%H%M%S%.6f"), mail.to));
+            fs::write(path, raw).map_err(|e| error(e.to_string()))?;
+        }
+        Ok(())
+    }
+}
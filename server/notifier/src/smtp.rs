@@ -0,0 +1,79 @@
+use std::time::Duration;
+use async_trait::async_trait;
+use lettre::{
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+    message::{Mailbox, header::ContentType},
+    transport::smtp::authentication::Credentials,
+};
+use myconfig::{SmtpConfig, SmtpTls};
+use super::{Mail, Mailer, Notifier, Notification, NotifyError};
+
+/// Send mail by SMTP server.
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpMailer {
+    pub fn new(config: &SmtpConfig) -> Result<SmtpMailer, NotifyError> {
+        let error = |detail: String| NotifyError::ConfigError { channel: "email", detail };
+        let builder = match config.tls {
+            SmtpTls::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&config.host),
+            SmtpTls::Starttls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.host)
+                .map_err(|e| error(e.to_string()))?,
+            SmtpTls::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&config.host)
+                .map_err(|e| error(e.to_string()))?,
+        };
+        let mut builder = builder
+            .port(config.port)
+            .timeout(Some(Duration::from_secs(10)));
+        if let (Some(username), Some(password)) = (&config.username, &config.password) {
+            builder = builder.credentials(Credentials::new(username.clone(), password.clone()));
+        }
+
+        let from = config.from.parse()
+            .map_err(|e: lettre::address::AddressError| error(format!("from: {}", e)))?;
+        Ok(SmtpMailer { transport: builder.build(), from })
+    }
+}
+
+#[async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, mail: &Mail) -> Result<(), NotifyError> {
+        let error = |detail: String| NotifyError::DeliveryError { channel: "email", detail };
+        let to: Mailbox = mail.to.parse()
+            .map_err(|e: lettre::address::AddressError| error(format!("to: {}", e)))?;
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(mail.subject.clone())
+            .header(ContentType::TEXT_PLAIN)
+            .body(mail.body.clone())
+            .map_err(|e| error(e.to_string()))?;
+        self.transport.send(message).await
+            .map_err(|e| error(e.to_string()))?;
+        Ok(())
+    }
+}
+
+/// Send notification by email to the user.
+pub struct SmtpNotifier {
+    mailer: SmtpMailer,
+}
+
+impl SmtpNotifier {
+    pub fn new(config: &SmtpConfig) -> Result<SmtpNotifier, NotifyError> {
+        Ok(SmtpNotifier { mailer: SmtpMailer::new(config)? })
+    }
+}
+
+#[async_trait]
+impl Notifier for SmtpNotifier {
+    async fn notify(&self, notification: &Notification) -> Result<(), NotifyError> {
+        self.mailer.send(&Mail {
+            to: notification.email.clone(),
+            subject: format!("[Todo] {}", notification.title),
+            body: format!("{}\n{}", notification.title, notification.body),
+        }).await
+    }
+}
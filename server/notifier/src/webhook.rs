@@ -0,0 +1,36 @@
+use std::time::Duration;
+use async_trait::async_trait;
+use super::{Notifier, Notification, NotifyError};
+
+/// Post notification as JSON to configured URL. Non 2xx response is failure.
+pub struct WebhookNotifier {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl WebhookNotifier {
+    pub fn new(url: &str) -> Result<WebhookNotifier, NotifyError> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .map_err(|e|
+                NotifyError::ConfigError { channel: "webhook", detail: e.to_string() }
+            )?;
+        Ok(WebhookNotifier { client, url: url.to_owned() })
+    }
+}
+
+#[async_trait]
+impl Notifier for WebhookNotifier {
+    async fn notify(&self, notification: &Notification) -> Result<(), NotifyError> {
+        let error = |detail: String| NotifyError::DeliveryError { channel: "webhook", detail };
+        let resp = self.client.post(&self.url)
+            .json(notification)
+            .send().await
+            .map_err(|e| error(e.to_string()))?;
+        if !resp.status().is_success() {
+            return Err(error(format!("response status {}", resp.status())))
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,144 @@
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+use clap::Parser;
+use serde_json::Value;
+
+/// Concurrent login throughput of a running server.
+/// Run against builds before and after a change with same config to compare.
+#[derive(Parser)]
+#[command(about = "Measure concurrent login throughput of a running server")]
+struct Args {
+    /// Base URL of the server.
+    #[arg(long, default_value = "http://127.0.0.1:8080")]
+    url: String,
+
+    /// Number of clients logging in at the same time.
+    #[arg(short, long, default_value_t = 32)]
+    concurrency: usize,
+
+    /// Total number of login requests.
+    #[arg(short = 'n', long, default_value_t = 512)]
+    requests: usize,
+
+    /// Milliseconds between requests of a light endpoint, to see whether logins block other requests.
+    #[arg(long, default_value_t = 50)]
+    probe_interval: u64,
+}
+
+const PASSWORD: &str = "Bench123!!";
+
+#[tokio::main]
+async fn main() {
+    let args = Args::parse();
+    let client = reqwest::Client::new();
+
+    // Temporary user, deleted after run.
+    let username = format!("bench_{}", hex::encode(rand::random::<[u8; 4]>()));
+    let resp = client.post(format!("{}/user/register", args.url))
+        .form(&[("username", username.as_str()), ("password", PASSWORD), ("email", &format!("{}@example.com", username))])
+        .send().await
+        .unwrap_or_else(|e| exit(&format!("Sign up error: {}", e)));
+    if resp.status() != 201 {
+        exit(&format!("Sign up error: {} {}", resp.status(), resp.text().await.unwrap_or_default()));
+    }
+
+    // Latency of a request without password hashing, while logins are running.
+    let done = Arc::new(AtomicBool::new(false));
+    let probe = tokio::spawn({
+        let (client, url, done) = (client.clone(), args.url.clone(), done.clone());
+        let interval = Duration::from_millis(args.probe_interval);
+        async move {
+            let mut latencies = Vec::new();
+            while !done.load(Ordering::Relaxed) {
+                let start = Instant::now();
+                if client.get(format!("{}/.well-known/jwks.json", url)).send().await.is_ok() {
+                    latencies.push(start.elapsed());
+                }
+                tokio::time::sleep(interval).await;
+            }
+            latencies
+        }
+    });
+
+    let next = Arc::new(AtomicUsize::new(0));
+    let start = Instant::now();
+    let clients: Vec<_> = (0..args.concurrency)
+        .map(|_| {
+            let (client, url, username, next) = (client.clone(), args.url.clone(), username.clone(), next.clone());
+            let requests = args.requests;
+            tokio::spawn(async move {
+                let mut results = Vec::new();
+                while next.fetch_add(1, Ordering::Relaxed) < requests {
+                    let start = Instant::now();
+                    let resp = client.post(format!("{}/user/login", url))
+                        .form(&[("username", username.as_str()), ("password", PASSWORD)])
+                        .send().await;
+                    let status = resp.as_ref().map_or(0, |resp| resp.status().as_u16());
+                    let body = match resp {
+                        Ok(resp) if status == 200 => resp.json::<Value>().await.ok(),
+                        _ => None,
+                    };
+                    results.push((status, start.elapsed(), body));
+                }
+                results
+            })
+        })
+        .collect();
+
+    let mut results = Vec::new();
+    for client in clients {
+        results.extend(client.await.unwrap());
+    }
+    let elapsed = start.elapsed();
+    done.store(true, Ordering::Relaxed);
+    let mut probe_latencies = probe.await.unwrap();
+
+    let mut statuses = BTreeMap::new();
+    for (status, _, _) in &results {
+        *statuses.entry(*status).or_insert(0) += 1;
+    }
+    let mut login_latencies: Vec<_> = results.iter()
+        .filter(|(status, _, _)| *status == 200)
+        .map(|(_, latency, _)| *latency)
+        .collect();
+    let succeeded = login_latencies.len();
+
+    println!("requests:     {} ({} concurrent)", results.len(), args.concurrency);
+    println!("elapsed:      {:.2?}", elapsed);
+    println!("throughput:   {:.1} logins/s", succeeded as f64 / elapsed.as_secs_f64());
+    // Status 0 is connection error.
+    println!("status:       {}", statuses.iter().map(|(status, count)| format!("{}={}", status, count)).collect::<Vec<_>>().join(" "));
+    println!("login:        {}", percentiles(&mut login_latencies));
+    println!("probe:        {}", percentiles(&mut probe_latencies));
+
+    // Delete temporary user with any issued token.
+    let login = results.iter().find_map(|(_, _, body)| body.as_ref());
+    match login.and_then(|body| Some((body["user"]["id"].as_i64()?, body["access_token"].as_str()?))) {
+        Some((user_id, access_token)) => {
+            let _ = client.delete(format!("{}/user/{}", args.url, user_id))
+                .bearer_auth(access_token)
+                .send().await;
+        },
+        None => eprintln!("No login succeeded, delete user {} manually", username),
+    }
+}
+
+fn percentiles(latencies: &mut [Duration]) -> String {
+    if latencies.is_empty() {
+        return "-".to_owned()
+    }
+    latencies.sort();
+    let at = |p: usize| latencies[(latencies.len() - 1) * p / 100];
+    format!("p50={:.2?} p95={:.2?} p99={:.2?} max={:.2?}", at(50), at(95), at(99), at(100))
+}
+
+fn exit(msg: &str) -> ! {
+    eprintln!("{}", msg);
+    std::process::exit(1)
+}
@@ -1,3 +1,126 @@
+use clap::{Parser, Subcommand};
+use env_logger::Env;
+use migration::{Migrator, MigratorTrait, DbErr};
+use myconfig::{Config, ConfigArgs, ConfigError, DatabaseConfig};
+use entity::mutation::{AuditActor, Mutation};
+
+#[derive(Parser)]
+#[command(about = "Rust simple user todos server")]
+struct Cli {
+    #[command(flatten)]
+    config: ConfigArgs,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run server. (default)
+    Serve,
+
+    /// Manage database schema migrations.
+    Migrate {
+        #[command(subcommand)]
+        action: MigrateAction,
+    },
+
+    /// Manage users.
+    User {
+        #[command(subcommand)]
+        action: UserAction,
+    },
+}
+
+#[derive(Subcommand)]
+enum UserAction {
+    /// Change role of user, "user" or "admin". Tokens of the user are revoked.
+    SetRole {
+        username: String,
+        role: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum MigrateAction {
+    /// Apply pending migrations.
+    Up {
+        /// Number of migrations to apply, all pending if not set.
+        #[arg(short = 'n', long)]
+        steps: Option<u32>,
+    },
+
+    /// Rollback applied migrations.
+    Down {
+        /// Number of migrations to rollback.
+        #[arg(short = 'n', long, default_value_t = 1)]
+        steps: u32,
+    },
+
+    /// Show applied and pending migrations.
+    Status,
+}
+
+#[tokio::main]
+async fn migrate(config: DatabaseConfig, action: MigrateAction) -> Result<(), DbErr> {
+    env_logger::init_from_env(Env::default().default_filter_or("info"));
+    let conn = entity::database_connect(&config).await?;
+    match action {
+        MigrateAction::Up { steps } => Migrator::up(&conn, steps).await,
+        MigrateAction::Down { steps } => Migrator::down(&conn, Some(steps)).await,
+        MigrateAction::Status => Migrator::status(&conn).await,
+    }
+}
+
+#[tokio::main]
+async fn manage_user(config: DatabaseConfig, action: UserAction) -> Result<(), String> {
+    let conn = entity::database_connect(&config).await
+        .map_err(|e| e.to_string())?;
+    match action {
+        UserAction::SetRole { username, role } => {
+            // Recorded in audit log without admin.
+            let actor = AuditActor { admin_id: None, ip: None };
+            let user = Mutation::set_user_role(&conn, &actor, &username, &role).await
+                .map_err(|e| format!("{:?}", e))?;
+            println!("{} is {}", user.username, user.role);
+        },
+    }
+    Ok(())
+}
+
+/// Fail fast on invalid configuration.
+fn or_exit<T>(config: Result<T, ConfigError>) -> T {
+    config.unwrap_or_else(|e| {
+        eprintln!("Configuration error: {}", e);
+        std::process::exit(1);
+    })
+}
+
 fn main() {
-    api::main();
-}
\ No newline at end of file
+    let cli = Cli::parse();
+
+    // Only server needs whole config, other commands need database only.
+    match cli.command.unwrap_or(Command::Serve) {
+        Command::Serve => {
+            let config = or_exit(Config::load(&cli.config));
+            if let Err(e) = api::main(config) {
+                eprintln!("Server error: {}", e);
+                std::process::exit(1);
+            }
+        },
+        Command::Migrate { action } => {
+            let config = or_exit(Config::load_database(&cli.config));
+            if let Err(e) = migrate(config, action) {
+                eprintln!("Migration error: {}", e);
+                std::process::exit(1);
+            }
+        },
+        Command::User { action } => {
+            let config = or_exit(Config::load_database(&cli.config));
+            if let Err(e) = manage_user(config, action) {
+                eprintln!("User error: {}", e);
+                std::process::exit(1);
+            }
+        },
+    }
+}